@@ -1,13 +1,31 @@
 // - STD
-use std::io::Read;
+use std::io::{Read, Write, Cursor};
 use std::borrow::Borrow;
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 // - internal
 use crate::{
 	Result,
+	ZffError,
+	ZffErrorKind,
+	io::buffer_chunk,
+	header::CompressionHeader,
+	XZ_MAX_COMPRESSION_LEVEL,
+	XZ_MIN_DICTIONARY_SIZE,
+	BROTLI_MAX_COMPRESSION_LEVEL,
+	BROTLI_BUFFER_SIZE,
+	BROTLI_MIN_WINDOW_SIZE,
+	BROTLI_MAX_WINDOW_SIZE,
+	XZ_STREAM_FOOTER_SIZE,
+	XZ_STREAM_FOOTER_MAGIC,
+	ERROR_BUFFER_TOO_SMALL,
 };
 
+// - external
+use xz2::stream::{Stream as XzStream, LzmaOptions, Filters as XzFilters, Check as XzCheck, Action as XzAction, Error as XzError};
+use brotli::{BrotliState, BrotliDecompressStream, BrotliResult, HuffmanCode, Allocator, SliceWrapper, SliceWrapperMut};
+
 /// Defines all compression algorithms, which are implemented in zff.
 #[repr(u8)]
 #[non_exhaustive]
@@ -19,6 +37,12 @@ pub enum CompressionAlgorithm {
 	Zstd = 1,
 	/// LZ4 compression - encoded as 2 in the header. LZ4 frame format is used (not the LZ4 block format) for compression.
 	Lz4 = 2,
+	/// Xz compression (LZMA2) - encoded as 3 in the header. The compression level should be between 0 and 9 (higher values will be clamped to 9).
+	/// This algorithm is slow, but provides a high compression ratio (e.g. for long-term archival copies).
+	Xz = 3,
+	/// Brotli compression - encoded as 4 in the header. The compression level (quality) should be between 0 and 11 (higher values will be clamped to 11).
+	/// This algorithm is slow, but provides a high compression ratio (e.g. for long-term archival copies).
+	Brotli = 4,
 }
 
 impl From<&str> for CompressionAlgorithm {
@@ -27,6 +51,8 @@ impl From<&str> for CompressionAlgorithm {
 		match algorithm.as_str() {
 			"zstd" => CompressionAlgorithm::Zstd,
 			"lz4" => CompressionAlgorithm::Lz4,
+			"xz" | "lzma" => CompressionAlgorithm::Xz,
+			"brotli" => CompressionAlgorithm::Brotli,
 			_ => CompressionAlgorithm::None,
 		}
 	}
@@ -37,14 +63,570 @@ impl fmt::Display for CompressionAlgorithm {
     	let value = match self {
     		CompressionAlgorithm::Zstd => "Zstd",
     		CompressionAlgorithm::Lz4 => "Lz4",
+    		CompressionAlgorithm::Xz => "Xz",
+    		CompressionAlgorithm::Brotli => "Brotli",
     		CompressionAlgorithm::None => "None",
     	};
         write!(f, "{value}")
     }
 }
 
+/// The [Compressor] trait is implemented by the compressors of each [CompressionAlgorithm] (see [Compression::new_compressor]).
+/// A compressor should be reused to compress multiple chunks: the underlying compression context will be reused,
+/// if this is supported by the appropriate library (e.g. zstd).
+/// # Example
+/// ```
+/// use zff::{Compression, CompressionAlgorithm, decompress_buffer, header::CompressionHeader};
+///
+/// let chunk_size = 32768;
+/// // well compressible data and pseudo-random (incompressible) data of the chunk size (the compressed data could be greater than the input).
+/// let mut state = 1u32;
+/// let random_data: Vec<u8> = (0..chunk_size).map(|_| {
+///     state = state.wrapping_mul(1103515245).wrapping_add(12345);
+///     (state >> 16) as u8
+/// }).collect();
+/// let inputs = [b"zff zff zff zff zff zff zff zff zff zff zff zff zff zff zff zff".repeat(64), random_data];
+/// let levels = vec![
+///     (CompressionAlgorithm::None, vec![0]),
+///     (CompressionAlgorithm::Lz4, vec![0, 1]),
+///     // negative zstd levels are stored as i8 (two's complement).
+///     (CompressionAlgorithm::Zstd, (-7i8..=22).map(|level| level as u8).collect()),
+///     (CompressionAlgorithm::Xz, (0..=9).collect()),
+///     (CompressionAlgorithm::Brotli, (0..=11).collect()),
+/// ];
+/// for (algorithm, levels) in levels {
+///     for level in levels {
+///         let compression_header = CompressionHeader::new(2, algorithm.clone(), level, 1.05);
+///         let mut compressor = Compression::new_compressor(&compression_header, chunk_size).unwrap();
+///         for data in inputs.iter().chain(inputs.iter()) {
+///             let compressed_data = compressor.compress(data).unwrap();
+///             assert_eq!(&decompress_buffer(&compressed_data, &algorithm).unwrap(), data);
+///         }
+///     }
+/// }
+/// ```
+pub trait Compressor: Send {
+	/// returns the appropriate [CompressionAlgorithm].
+	fn algorithm(&self) -> CompressionAlgorithm;
+
+	/// compresses the given buffer and returns the compressed data.
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// The [Decompressor] trait is implemented by the decompressors of each [CompressionAlgorithm] (see [Compression::new_decompressor]).
+/// A decompressor should be reused to decompress multiple chunks: the underlying decompression context (and a loaded dictionary)
+/// and the allocated buffers (e.g. the lz4 block buffers or the xz dictionary) will be reused for each chunk.
+/// # Example
+/// ```
+/// use zff::{Compression, CompressionAlgorithm, header::CompressionHeader};
+///
+/// let data = b"zff zff zff zff zff zff zff zff zff zff zff zff zff zff zff zff".repeat(64);
+/// for algorithm in [CompressionAlgorithm::None, CompressionAlgorithm::Zstd, CompressionAlgorithm::Lz4, CompressionAlgorithm::Xz, CompressionAlgorithm::Brotli] {
+///     let compression_header = CompressionHeader::new(2, algorithm, 3, 1.05);
+///     let compressed_data = Compression::new_compressor(&compression_header, data.len()).unwrap().compress(&data).unwrap();
+///     let mut decompressor = Compression::new_decompressor(&compression_header).unwrap();
+///     let mut buffer = vec![0u8; data.len()];
+///     let decompressed_bytes = decompressor.decompress_into(&compressed_data, &mut buffer).unwrap();
+///     assert_eq!(&buffer[..decompressed_bytes], &data[..]);
+///     assert!(decompressor.decompress_into(&compressed_data, &mut buffer[..data.len()-1]).is_err());
+/// }
+/// ```
+pub trait Decompressor: Send {
+	/// returns the appropriate [CompressionAlgorithm].
+	fn algorithm(&self) -> CompressionAlgorithm;
+
+	/// decompresses the given buffer into the given output buffer and returns the number of decompressed bytes.
+	/// # Error
+	/// Fails, if the data could not be decompressed or if the output buffer is too small to hold the decompressed data.
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize>;
+}
+
+/// structure contains serveral methods to handle compression.
+pub struct Compression;
+
+impl Compression {
+	/// returns a new [Compressor] for the given [CompressionHeader]. The chunk size is used to choose appropriate window/dictionary sizes
+	/// for the xz and brotli compressors.
+	/// The compression level will be clamped to the valid range of the appropriate algorithm. For zstd, the level will be interpreted
+	/// as i8 (see [CompressionHeader::signed_level]), so negative (fast) levels can be used. A zstd level of 0 uses the default zstd level.
+	/// # Error
+	/// Fails, if the compression context could not be initialized (e.g. if the zstd dictionary is invalid).
+	pub fn new_compressor(compression_header: &CompressionHeader, chunk_size: usize) -> Result<Box<dyn Compressor>> {
+		let compressor: Box<dyn Compressor> = match compression_header.algorithm() {
+			CompressionAlgorithm::None => Box::new(NoneCompressor),
+			CompressionAlgorithm::Zstd => Box::new(ZstdCompressor::new(compression_header.signed_level() as i32, compression_header.dictionary())?),
+			CompressionAlgorithm::Lz4 => Box::new(Lz4Compressor),
+			CompressionAlgorithm::Xz => Box::new(XzCompressor::new(*compression_header.level() as u32, chunk_size)),
+			CompressionAlgorithm::Brotli => Box::new(BrotliCompressor::new(*compression_header.level() as u32, chunk_size)),
+		};
+		Ok(compressor)
+	}
+
+	/// returns a new [Decompressor] for the given [CompressionHeader].
+	/// # Error
+	/// Fails, if the decompression context could not be initialized (e.g. if the zstd dictionary is invalid).
+	pub fn new_decompressor(compression_header: &CompressionHeader) -> Result<Box<dyn Decompressor>> {
+		let decompressor: Box<dyn Decompressor> = match compression_header.algorithm() {
+			CompressionAlgorithm::None => Box::new(NoneDecompressor),
+			CompressionAlgorithm::Zstd => Box::new(ZstdDecompressor::new(compression_header.dictionary())?),
+			CompressionAlgorithm::Lz4 => Box::new(Lz4Decompressor::new()),
+			CompressionAlgorithm::Xz => Box::new(XzDecompressor::new()?),
+			CompressionAlgorithm::Brotli => Box::new(BrotliDecompressor::new()),
+		};
+		Ok(decompressor)
+	}
+}
+
+/// The [Compressor] for [CompressionAlgorithm::None] (returns a copy of the given data).
+pub struct NoneCompressor;
+
+impl Compressor for NoneCompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::None
+	}
+
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>> {
+		Ok(buffer.to_vec())
+	}
+}
+
+/// The [Compressor] for [CompressionAlgorithm::Zstd]. The underlying compression context (and the loaded dictionary) will be reused for each chunk.
+pub struct ZstdCompressor {
+	compressor: zstd::bulk::Compressor<'static>,
+}
+
+impl ZstdCompressor {
+	/// returns a new [ZstdCompressor] with the given level (will be clamped to the range of the supported zstd levels) and the optional dictionary.
+	pub fn new(level: i32, dictionary: Option<&[u8]>) -> Result<ZstdCompressor> {
+		let level_range = zstd::compression_level_range();
+		let level = level.clamp(*level_range.start(), *level_range.end());
+		let compressor = match dictionary {
+			Some(dictionary) => zstd::bulk::Compressor::with_dictionary(level, dictionary)?,
+			None => zstd::bulk::Compressor::new(level)?,
+		};
+		Ok(Self {
+			compressor,
+		})
+	}
+}
+
+impl Compressor for ZstdCompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Zstd
+	}
+
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>> {
+		// the output buffer will be allocated with the appropriate compression bound of the given buffer.
+		Ok(self.compressor.compress(buffer)?)
+	}
+}
+
+/// The [Compressor] for [CompressionAlgorithm::Lz4] (LZ4 frame format).
+pub struct Lz4Compressor;
+
+impl Compressor for Lz4Compressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Lz4
+	}
+
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>> {
+		let mut compressor = lz4_flex::frame::FrameEncoder::new(Vec::with_capacity(buffer.len()));
+		compressor.write_all(buffer)?;
+		Ok(compressor.finish()?)
+	}
+}
+
+/// The [Compressor] for [CompressionAlgorithm::Xz]. The dictionary size will be limited to the chunk size
+/// (the presets of the higher levels would allocate up to 64 MiB for each chunk otherwise).
+pub struct XzCompressor {
+	preset: u32,
+	dictionary_size: u32,
+}
+
+impl XzCompressor {
+	/// returns a new [XzCompressor] with the given level (will be clamped to 9) for chunks of the given size.
+	pub fn new(level: u32, chunk_size: usize) -> XzCompressor {
+		let dictionary_size = std::cmp::max(std::cmp::min(chunk_size, u32::MAX as usize) as u32, XZ_MIN_DICTIONARY_SIZE);
+		Self {
+			preset: std::cmp::min(level, XZ_MAX_COMPRESSION_LEVEL as u32),
+			dictionary_size,
+		}
+	}
+}
+
+impl Compressor for XzCompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Xz
+	}
+
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>> {
+		let mut options = LzmaOptions::new_preset(self.preset).map_err(std::io::Error::from)?;
+		options.dict_size(self.dictionary_size);
+		let mut filters = XzFilters::new();
+		filters.lzma2(&options);
+		let stream = XzStream::new_stream_encoder(&filters, XzCheck::Crc64).map_err(std::io::Error::from)?;
+		let mut compressor = xz2::write::XzEncoder::new_stream(Vec::with_capacity(buffer.len()), stream);
+		compressor.write_all(buffer)?;
+		Ok(compressor.finish()?)
+	}
+}
+
+/// The [Compressor] for [CompressionAlgorithm::Brotli]. The window size will be fitted to the chunk size.
+pub struct BrotliCompressor {
+	quality: u32,
+	window_size: u32,
+}
+
+impl BrotliCompressor {
+	/// returns a new [BrotliCompressor] with the given level (quality; will be clamped to 11) for chunks of the given size.
+	pub fn new(level: u32, chunk_size: usize) -> BrotliCompressor {
+		let window_size = (usize::BITS - chunk_size.saturating_sub(1).leading_zeros()).clamp(BROTLI_MIN_WINDOW_SIZE, BROTLI_MAX_WINDOW_SIZE);
+		Self {
+			quality: std::cmp::min(level, BROTLI_MAX_COMPRESSION_LEVEL as u32),
+			window_size,
+		}
+	}
+}
+
+impl Compressor for BrotliCompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Brotli
+	}
+
+	fn compress(&mut self, buffer: &[u8]) -> Result<Vec<u8>> {
+		let mut compressed_data = Vec::with_capacity(buffer.len());
+		{
+			let mut compressor = brotli::CompressorWriter::new(&mut compressed_data, BROTLI_BUFFER_SIZE, self.quality, self.window_size);
+			compressor.write_all(buffer)?;
+			compressor.flush()?;
+		}
+		Ok(compressed_data)
+	}
+}
+
+/// The [Decompressor] for [CompressionAlgorithm::None] (copies the given data into the output buffer).
+pub struct NoneDecompressor;
+
+impl Decompressor for NoneDecompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::None
+	}
+
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		if buffer.len() > output.len() {
+			return Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", output.len())));
+		}
+		output[..buffer.len()].copy_from_slice(buffer);
+		Ok(buffer.len())
+	}
+}
+
+/// The [Decompressor] for [CompressionAlgorithm::Zstd]. The underlying decompression context (and the loaded dictionary) will be reused for each chunk.
+pub struct ZstdDecompressor {
+	decompressor: zstd::bulk::Decompressor<'static>,
+}
+
+impl ZstdDecompressor {
+	/// returns a new [ZstdDecompressor] with the optional dictionary.
+	pub fn new(dictionary: Option<&[u8]>) -> Result<ZstdDecompressor> {
+		let decompressor = match dictionary {
+			Some(dictionary) => zstd::bulk::Decompressor::with_dictionary(dictionary)?,
+			None => zstd::bulk::Decompressor::new()?,
+		};
+		Ok(Self {
+			decompressor,
+		})
+	}
+}
+
+impl Decompressor for ZstdDecompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Zstd
+	}
+
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		match self.decompressor.decompress_to_buffer(buffer, output) {
+			Ok(decompressed_bytes) => Ok(decompressed_bytes),
+			Err(e) => {
+				// zstd reports only a generic error, if the output buffer is too small
+				// (the content sizes u64::MAX and u64::MAX-1 are used by zstd for unknown content sizes and errors).
+				let content_size = zstd::zstd_safe::get_frame_content_size(buffer);
+				if content_size > output.len() as u64 && content_size < u64::MAX - 1 {
+					Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", output.len())))
+				} else {
+					Err(e.into())
+				}
+			},
+		}
+	}
+}
+
+/// The [Decompressor] for [CompressionAlgorithm::Lz4] (LZ4 frame format). The block buffers of the underlying frame decoder will be reused for each chunk.
+pub struct Lz4Decompressor {
+	decoder: lz4_flex::frame::FrameDecoder<Cursor<Vec<u8>>>,
+}
+
+impl Lz4Decompressor {
+	/// returns a new [Lz4Decompressor].
+	pub fn new() -> Lz4Decompressor {
+		Self {
+			decoder: lz4_flex::frame::FrameDecoder::new(Cursor::new(Vec::new())),
+		}
+	}
+}
+
+impl Default for Lz4Decompressor {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Decompressor for Lz4Decompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Lz4
+	}
+
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		let input = self.decoder.get_mut();
+		input.get_mut().clear();
+		input.get_mut().extend_from_slice(buffer);
+		input.set_position(0);
+		let result = read_into(&mut self.decoder, output);
+		// the decoder could be stuck in the middle of a frame.
+		if result.is_err() {
+			self.decoder = lz4_flex::frame::FrameDecoder::new(Cursor::new(Vec::new()));
+		}
+		result
+	}
+}
+
+/// The [Decompressor] for [CompressionAlgorithm::Xz]. The underlying decoder (and its dictionary) will be reused for each chunk:
+/// the xz streams of the chunks will be decoded as concatenated streams.
+pub struct XzDecompressor {
+	stream: XzStream,
+}
+
+impl XzDecompressor {
+	/// returns a new [XzDecompressor].
+	/// # Error
+	/// Fails, if the decoder could not be initialized.
+	pub fn new() -> Result<XzDecompressor> {
+		Ok(Self {
+			stream: new_xz_decoder()?,
+		})
+	}
+
+	// decodes the given xz stream (the uncompressed size is known by the index of the stream) into the output buffer.
+	fn decode(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		let uncompressed_size = match xz_uncompressed_size(buffer) {
+			Some(uncompressed_size) => uncompressed_size,
+			None => return Err(std::io::Error::from(XzError::Data).into()),
+		};
+		if uncompressed_size > output.len() as u64 {
+			return Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", output.len())));
+		}
+		let output = &mut output[..uncompressed_size as usize];
+		let initial_total_in = self.stream.total_in();
+		let initial_total_out = self.stream.total_out();
+		loop {
+			let read_bytes = (self.stream.total_in() - initial_total_in) as usize;
+			let written_bytes = (self.stream.total_out() - initial_total_out) as usize;
+			self.stream.process(&buffer[read_bytes..], &mut output[written_bytes..], XzAction::Run).map_err(std::io::Error::from)?;
+			let read_bytes_after = (self.stream.total_in() - initial_total_in) as usize;
+			let written_bytes_after = (self.stream.total_out() - initial_total_out) as usize;
+			if read_bytes_after == read_bytes && written_bytes_after == written_bytes {
+				break;
+			}
+		}
+		// the stream is truncated or corrupted, if the decoder did not consume the whole stream or does not return the whole content.
+		if self.stream.total_in() - initial_total_in != buffer.len() as u64 || self.stream.total_out() - initial_total_out != uncompressed_size {
+			return Err(std::io::Error::from(XzError::Data).into());
+		}
+		Ok(uncompressed_size as usize)
+	}
+}
+
+impl Decompressor for XzDecompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Xz
+	}
+
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		let result = self.decode(buffer, output);
+		// the decoder could be stuck in the middle of a stream.
+		if result.is_err() {
+			self.stream = new_xz_decoder()?;
+		}
+		result
+	}
+}
+
+// returns a new xz decoder, which decodes concatenated streams (without memory limit).
+fn new_xz_decoder() -> Result<XzStream> {
+	Ok(XzStream::new_stream_decoder(u64::MAX, xz2::stream::CONCATENATED).map_err(std::io::Error::from)?)
+}
+
+// returns the uncompressed size of the given xz stream, which is stored in the index of the stream (see the xz file format specification).
+// Returns None, if the stream does not end with a valid stream footer and index.
+fn xz_uncompressed_size(stream: &[u8]) -> Option<u64> {
+	// stream footer: CRC32 (4 bytes), backward size (4 bytes), stream flags (2 bytes), footer magic bytes (2 bytes).
+	let footer = stream.get(stream.len().checked_sub(XZ_STREAM_FOOTER_SIZE)?..)?;
+	if footer[10..] != XZ_STREAM_FOOTER_MAGIC {
+		return None;
+	}
+	let backward_size = (u32::from_le_bytes(footer[4..8].try_into().ok()?) as usize + 1) * 4;
+	let index_start = stream.len().checked_sub(XZ_STREAM_FOOTER_SIZE + backward_size)?;
+	let index = &stream[index_start..stream.len() - XZ_STREAM_FOOTER_SIZE];
+	// index: index indicator (0x00), number of records, records (unpadded size, uncompressed size), padding, CRC32.
+	if index.first() != Some(&0) {
+		return None;
+	}
+	let mut position = 1;
+	let number_of_records = read_xz_multibyte_integer(index, &mut position)?;
+	let mut uncompressed_size: u64 = 0;
+	for _ in 0..number_of_records {
+		read_xz_multibyte_integer(index, &mut position)?;
+		uncompressed_size = uncompressed_size.checked_add(read_xz_multibyte_integer(index, &mut position)?)?;
+	}
+	Some(uncompressed_size)
+}
+
+// reads a variable-length integer (7 bits per byte, little endian, up to 9 bytes) of the xz file format.
+fn read_xz_multibyte_integer(data: &[u8], position: &mut usize) -> Option<u64> {
+	let mut value: u64 = 0;
+	for i in 0..9 {
+		let byte = *data.get(*position)?;
+		*position += 1;
+		value |= ((byte & 0x7F) as u64) << (i * 7);
+		if byte & 0x80 == 0 {
+			return Some(value);
+		}
+	}
+	None
+}
+
+/// The [Decompressor] for [CompressionAlgorithm::Brotli]. The chunks will be decoded directly into the output buffer and
+/// the memory of the decoder (e.g. the ring buffer) will be reused for each chunk.
+#[derive(Default)]
+pub struct BrotliDecompressor {
+	pool_u8: BrotliMemoryPool<u8>,
+	pool_u32: BrotliMemoryPool<u32>,
+	pool_hc: BrotliMemoryPool<HuffmanCode>,
+}
+
+impl BrotliDecompressor {
+	/// returns a new [BrotliDecompressor].
+	pub fn new() -> BrotliDecompressor {
+		Self::default()
+	}
+}
+
+impl Decompressor for BrotliDecompressor {
+	fn algorithm(&self) -> CompressionAlgorithm {
+		CompressionAlgorithm::Brotli
+	}
+
+	fn decompress_into(&mut self, buffer: &[u8], output: &mut [u8]) -> Result<usize> {
+		let mut state = BrotliState::new(self.pool_u8.clone(), self.pool_u32.clone(), self.pool_hc.clone());
+		let mut available_in = buffer.len();
+		let mut input_offset = 0;
+		let mut available_out = output.len();
+		let mut output_offset = 0;
+		let mut written_bytes = 0;
+		match BrotliDecompressStream(&mut available_in, &mut input_offset, buffer, &mut available_out, &mut output_offset, output, &mut written_bytes, &mut state) {
+			BrotliResult::ResultSuccess => Ok(output_offset),
+			BrotliResult::NeedsMoreOutput => Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", output.len()))),
+			BrotliResult::NeedsMoreInput | BrotliResult::ResultFailure => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid Data").into()),
+		}
+	}
+}
+
+// An allocator for the brotli decoder, which keeps the freed memory for the next chunks.
+// The pool will be shared between the decompressor and the decoder state of the current chunk.
+#[derive(Clone, Default)]
+struct BrotliMemoryPool<T> {
+	free_memory: Arc<Mutex<Vec<Vec<T>>>>,
+}
+
+impl<T: Clone + Default> Allocator<T> for BrotliMemoryPool<T> {
+	type AllocatedMemory = BrotliMemory<T>;
+
+	fn alloc_cell(&mut self, len: usize) -> BrotliMemory<T> {
+		let mut free_memory = match self.free_memory.lock() {
+			Ok(free_memory) => free_memory,
+			Err(poisoned) => poisoned.into_inner(),
+		};
+		// uses the smallest free memory, which is large enough.
+		let best_fit = free_memory.iter().enumerate()
+			.filter(|(_, memory)| memory.capacity() >= len)
+			.min_by_key(|(_, memory)| memory.capacity())
+			.map(|(index, _)| index);
+		let mut memory = match best_fit {
+			Some(index) => free_memory.swap_remove(index),
+			None => Vec::with_capacity(len),
+		};
+		memory.clear();
+		memory.resize(len, T::default());
+		BrotliMemory(memory)
+	}
+
+	fn free_cell(&mut self, memory: BrotliMemory<T>) {
+		if memory.0.capacity() == 0 {
+			return;
+		}
+		match self.free_memory.lock() {
+			Ok(mut free_memory) => free_memory.push(memory.0),
+			Err(poisoned) => poisoned.into_inner().push(memory.0),
+		}
+	}
+}
+
+// The memory, which will be allocated by the [BrotliMemoryPool].
+#[derive(Default)]
+struct BrotliMemory<T>(Vec<T>);
+
+impl<T> SliceWrapper<T> for BrotliMemory<T> {
+	fn slice(&self) -> &[T] {
+		&self.0
+	}
+}
+
+impl<T> SliceWrapperMut<T> for BrotliMemory<T> {
+	fn slice_mut(&mut self) -> &mut [T] {
+		&mut self.0
+	}
+}
+
+// reads the given (decompressing) reader until EOF into the output buffer and returns the number of read bytes.
+// Fails, if the output buffer is too small.
+fn read_into<R: Read>(reader: &mut R, output: &mut [u8]) -> Result<usize> {
+	let mut read_bytes = 0;
+	loop {
+		if read_bytes == output.len() {
+			// checks, if there are remaining bytes.
+			return match reader.read(&mut [0u8; 1])? {
+				0 => Ok(read_bytes),
+				_ => Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", output.len()))),
+			};
+		}
+		match reader.read(&mut output[read_bytes..])? {
+			0 => return Ok(read_bytes),
+			bytes => read_bytes += bytes,
+		}
+	}
+}
+
 /// decompresses a buffer with the given [CompressionAlgorithm].
 pub fn decompress_buffer<C>(buffer: &[u8], compression_algorithm: C) -> Result<Vec<u8>>
+where
+	C: Borrow<CompressionAlgorithm>,
+{
+	decompress_buffer_with_dictionary(buffer, compression_algorithm, None)
+}
+
+/// decompresses a buffer with the given [CompressionAlgorithm] and the given (optional) zstd dictionary.
+/// The dictionary will be ignored, if the compression algorithm is not [CompressionAlgorithm::Zstd].
+pub fn decompress_buffer_with_dictionary<C>(buffer: &[u8], compression_algorithm: C, dictionary: Option<&[u8]>) -> Result<Vec<u8>>
 where
 	C: Borrow<CompressionAlgorithm>,
 {
@@ -52,8 +634,16 @@ where
     	CompressionAlgorithm::None => Ok(buffer.to_vec()),
     	CompressionAlgorithm::Zstd => {
     		let mut decompressed_buffer = Vec::new();
-			let mut decoder = zstd::stream::read::Decoder::new(buffer)?;
-			decoder.read_to_end(&mut decompressed_buffer)?;
+    		match dictionary {
+    			Some(dictionary) => {
+    				let mut decoder = zstd::stream::read::Decoder::with_dictionary(buffer, dictionary)?;
+    				decoder.read_to_end(&mut decompressed_buffer)?;
+    			},
+    			None => {
+					let mut decoder = zstd::stream::read::Decoder::new(buffer)?;
+					decoder.read_to_end(&mut decompressed_buffer)?;
+    			}
+    		}
 			Ok(decompressed_buffer)
     	},
     	CompressionAlgorithm::Lz4 => {
@@ -61,6 +651,37 @@ where
 			let mut decompressor = lz4_flex::frame::FrameDecoder::new(buffer);
 			decompressor.read_to_end(&mut decompressed_buffer)?;
 			Ok(decompressed_buffer)
-    	}
+    	},
+    	CompressionAlgorithm::Xz => {
+    		let mut decompressed_buffer = Vec::new();
+			let mut decompressor = xz2::read::XzDecoder::new(buffer);
+			decompressor.read_to_end(&mut decompressed_buffer)?;
+			Ok(decompressed_buffer)
+    	},
+    	CompressionAlgorithm::Brotli => {
+    		let mut decompressed_buffer = Vec::new();
+			let mut decompressor = brotli::Decompressor::new(buffer, BROTLI_BUFFER_SIZE);
+			decompressor.read_to_end(&mut decompressed_buffer)?;
+			Ok(decompressed_buffer)
+    	},
     }
-}
\ No newline at end of file
+}
+
+/// trains a zstd dictionary with the data of the given reader. The data will be splitted into samples of the given sample size
+/// (the chunk size of the appropriate container should be used here). The input will be read until EOF, so you should
+/// limit the input by yourself (e.g. by using [Read::take]).\
+/// The trained dictionary can be used by setting it to the appropriate [CompressionHeader](crate::header::CompressionHeader)
+/// (see [CompressionHeader::set_dictionary](crate::header::CompressionHeader::set_dictionary)).
+/// # Error
+/// Fails, if the input could not be read or if the dictionary could not be trained (e.g. if there is not enough input data).
+pub fn train_zstd_dictionary<R: Read>(input: &mut R, sample_size: usize, max_dictionary_size: usize) -> Result<Vec<u8>> {
+	let mut samples = Vec::new();
+	loop {
+		let (sample, _) = buffer_chunk(input, sample_size)?;
+		if sample.is_empty() {
+			break;
+		}
+		samples.push(sample);
+	}
+	Ok(zstd::dict::from_samples(&samples, max_dictionary_size)?)
+}
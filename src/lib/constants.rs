@@ -47,9 +47,54 @@ pub(crate) const ENCODING_KEY_DESCRIPTION_NOTES: &str = "dn";
 // chunk header flags
 pub(crate) const ERROR_FLAG_VALUE: u8 = 1<<0;
 pub(crate) const COMPRESSION_FLAG_VALUE: u8 = 1<<1;
+pub(crate) const SPARSE_FLAG_VALUE: u8 = 1<<2;
+
+// NBD protocol values (see https://github.com/NetworkBlockDevice/nbd/blob/master/doc/proto.md)
+pub(crate) const NBD_MAGIC: u64 = 0x4e42444d41474943; // "NBDMAGIC"
+pub(crate) const NBD_IHAVEOPT: u64 = 0x49484156454F5054; // "IHAVEOPT"
+pub(crate) const NBD_OPTION_REPLY_MAGIC: u64 = 0x0003e889045565a9;
+pub(crate) const NBD_REQUEST_MAGIC: u32 = 0x25609513;
+pub(crate) const NBD_SIMPLE_REPLY_MAGIC: u32 = 0x67446698;
+pub(crate) const NBD_FLAG_FIXED_NEWSTYLE: u16 = 1<<0;
+pub(crate) const NBD_FLAG_NO_ZEROES: u16 = 1<<1;
+pub(crate) const NBD_FLAG_C_FIXED_NEWSTYLE: u32 = 1<<0;
+pub(crate) const NBD_FLAG_C_NO_ZEROES: u32 = 1<<1;
+pub(crate) const NBD_FLAG_HAS_FLAGS: u16 = 1<<0;
+pub(crate) const NBD_FLAG_READ_ONLY: u16 = 1<<1;
+pub(crate) const NBD_FLAG_SEND_FLUSH: u16 = 1<<2;
+pub(crate) const NBD_FLAG_CAN_MULTI_CONN: u16 = 1<<8;
+pub(crate) const NBD_OPT_EXPORT_NAME: u32 = 1;
+pub(crate) const NBD_OPT_ABORT: u32 = 2;
+pub(crate) const NBD_OPT_LIST: u32 = 3;
+pub(crate) const NBD_OPT_INFO: u32 = 6;
+pub(crate) const NBD_OPT_GO: u32 = 7;
+pub(crate) const NBD_REP_ACK: u32 = 1;
+pub(crate) const NBD_REP_SERVER: u32 = 2;
+pub(crate) const NBD_REP_INFO: u32 = 3;
+pub(crate) const NBD_REP_FLAG_ERROR: u32 = 1<<31;
+pub(crate) const NBD_REP_ERR_UNSUP: u32 = NBD_REP_FLAG_ERROR | 1;
+pub(crate) const NBD_REP_ERR_INVALID: u32 = NBD_REP_FLAG_ERROR | 3;
+pub(crate) const NBD_REP_ERR_UNKNOWN: u32 = NBD_REP_FLAG_ERROR | 6;
+pub(crate) const NBD_INFO_EXPORT: u16 = 0;
+pub(crate) const NBD_CMD_READ: u16 = 0;
+pub(crate) const NBD_CMD_WRITE: u16 = 1;
+pub(crate) const NBD_CMD_DISC: u16 = 2;
+pub(crate) const NBD_CMD_FLUSH: u16 = 3;
+pub(crate) const NBD_CMD_TRIM: u16 = 4;
+pub(crate) const NBD_CMD_WRITE_ZEROES: u16 = 6;
+pub(crate) const NBD_EPERM: u32 = 1;
+pub(crate) const NBD_EIO: u32 = 5;
+pub(crate) const NBD_EINVAL: u32 = 22;
+pub(crate) const NBD_MAX_OPTION_LENGTH: u32 = 4096;
+pub(crate) const NBD_MAX_REQUEST_LENGTH: u32 = 32 * 1024 * 1024;
+
+// HTTP
+#[cfg(feature = "http")]
+pub(crate) const HTTP_DEFAULT_READ_AHEAD_SIZE: usize = 1024 * 1024;
 
 // - Error messages
 pub(crate) const ERROR_HEADER_DECODER_UNKNOWN_HASH_TYPE: &str = "Unknown hash type value.";
+pub(crate) const ERROR_HEADER_DECODER_UNKNOWN_FUZZY_HASH_TYPE: &str = "Unknown fuzzy hash type value.";
 pub(crate) const ERROR_HEADER_DECODER_MISMATCH_IDENTIFIER_KDF: &str = "The read identifier does not match to any known KDF header identifier.";
 pub(crate) const ERROR_HEADER_DECODER_UNKNOWN_PBE_SCHEME: &str = "Unknown PBEncryption scheme value.";
 pub(crate) const ERROR_HEADER_DECODER_UNKNOWN_KDF_SCHEME: &str = "Unknown KDF scheme value.";
@@ -76,6 +121,43 @@ pub(crate) const ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION: &str = "Unseekable pos
 pub(crate) const ERROR_ZFFREADER_MISSING_OBJECT: &str = "Missing object number in zffreader: ";
 pub(crate) const ERROR_ZFFREADER_MISSING_FILE: &str = "Missing file number in zffreader: ";
 
+pub(crate) const ERROR_SEGMENT_SINK_MISSING_SEGMENT: &str = "Missing segment in segment sink: ";
+pub(crate) const ERROR_NBD_INVALID_MAGIC: &str = "Invalid NBD magic value: ";
+pub(crate) const ERROR_NBD_UNKNOWN_EXPORT: &str = "Unknown NBD export: ";
+pub(crate) const ERROR_NBD_OPTION_TOO_LONG: &str = "The length of the NBD option data exceeds the limit: ";
+pub(crate) const ERROR_NBD_OPTION_REPLY: &str = "The NBD server replied with an error to the option request: ";
+pub(crate) const ERROR_NBD_COMMAND_REPLY: &str = "The NBD server replied with an error to the command request: ";
+pub(crate) const ERROR_NBD_CLIENT_NOT_FIXED_NEWSTYLE: &str = "The NBD client does not support the fixed newstyle negotiation. Client flags: ";
+#[cfg(feature = "http")]
+pub(crate) const ERROR_HTTP_MISSING_CONTENT_LENGTH: &str = "The HTTP server did not send the length of the requested resource: ";
+#[cfg(feature = "http")]
+pub(crate) const ERROR_HTTP_RANGE_NOT_SUPPORTED: &str = "The HTTP server does not support range requests for the requested resource: ";
+#[cfg(feature = "http")]
+pub(crate) const ERROR_HTTP_SERVER: &str = "Unable to start the HTTP server: ";
+#[cfg(feature = "http")]
+pub(crate) const ERROR_HTTP_MISSING_RESOURCE: &str = "The requested resource is not available on the HTTP server: ";
+#[cfg(feature = "http")]
+pub(crate) const ERROR_HTTP_INVALID_SEGMENT_URL: &str = "The url does not point to a zff segment (e.g. \"http://example.com/container.z01\"): ";
+pub(crate) const ERROR_VFS_PATH_NOT_FOUND: &str = "No such file or directory: ";
+pub(crate) const ERROR_VFS_TOO_MANY_LINKS: &str = "Too many levels of symbolic links: ";
+pub(crate) const ERROR_VFS_NOT_A_DIRECTORY: &str = "Not a directory: ";
+pub(crate) const ERROR_VFS_NOT_A_SYMLINK: &str = "Not a symbolic link: ";
+pub(crate) const ERROR_VFS_NOT_A_HARDLINK: &str = "Not a hard link: ";
+pub(crate) const ERROR_VFS_INVALID_FILENAME: &str = "The filename is not a single path component: ";
+pub(crate) const ERROR_VFS_DIRECTORY_CYCLE: &str = "The directory is its own (indirect) child: ";
+pub(crate) const ERROR_VFS_INVALID_HOLES: &str = "The holes of the file are unsorted, overlapping or out of range. File number: ";
+pub(crate) const ERROR_HASH_SET_INVALID_HEX: &str = "Invalid hexadecimal hash value in hash set: ";
+pub(crate) const ERROR_HASH_SET_MISSING_NSRL_HASH_COLUMN: &str = "The NSRL CSV header does not contain a supported hash column: ";
+pub(crate) const ERROR_BUFFER_TOO_SMALL: &str = "The output buffer is too small for the chunk data. Size of the output buffer: ";
+pub(crate) const ERROR_MERKLE_TREE_CHUNK_VERIFICATION_FAILED: &str = "The chunk could not be verified by the merkle tree of the object. Chunk number: ";
+pub(crate) const ERROR_DEDUPLICATION_MISSING_HASH_TYPE: &str = "The deduplication needs at least one collision resistant hash type (e.g. SHA256 or Blake3). Given hash types: ";
+pub(crate) const ERROR_RECOMPRESSION_LOGICAL_OBJECT: &str = "Only physical objects can be recompressed. Logical object number: ";
+pub(crate) const ERROR_RECOMPRESSION_MISSING_HASH_TYPE: &str = "None of the given hash types is stored in the source object, so the recompressed object could not be compared with the source object. Given hash types: ";
+pub(crate) const ERROR_RECOMPRESSION_HASH_MISMATCH: &str = "The hash value of the recompressed object does not match the hash value of the source object. Hash type: ";
+pub(crate) const ERROR_STREAM_MULTIPLE_OBJECTS: &str = "Each object is stored in its own segment(s), so a single output stream can only contain a single object. Use multiple output streams. Number of objects: ";
+pub(crate) const ERROR_STREAM_LIMITED_SEGMENT_SIZE: &str = "A single output stream needs an unlimited segment size (a segment size of 0). Use multiple output streams or an unlimited segment size. Segment size: ";
+pub(crate) const ERROR_STREAM_SEGMENT_SIZE_TO_SMALL: &str = "The zff container does not fit into a single output stream. Increase the segment size or use multiple output streams. Requested segment number: ";
+
 // Default values
 /// the default chunk size as 2^x. This value is 15, so the default chunk size is 2^15 = 32768 bytes.
 pub const DEFAULT_CHUNK_SIZE: u8 = 15;
@@ -86,6 +168,34 @@ pub const INITIAL_OBJECT_NUMBER: u64 = 1;
 
 /// The default compression ratio threshold
 pub const DEFAULT_COMPRESSION_RATIO_THRESHOLD: &str = "1.05";
+/// The maximum compression level of the xz compression algorithm (higher levels will be clamped to this value).
+pub const XZ_MAX_COMPRESSION_LEVEL: u8 = 9;
+/// The maximum compression level (quality) of the brotli compression algorithm (higher levels will be clamped to this value).
+pub const BROTLI_MAX_COMPRESSION_LEVEL: u8 = 11;
+pub(crate) const BROTLI_BUFFER_SIZE: usize = 4096;
+/// The entropy threshold (in bits per byte) of the adaptive compression. Chunks with a greater (or equal) estimated entropy will not be compressed.
+pub const DEFAULT_ADAPTIVE_COMPRESSION_ENTROPY_THRESHOLD: f32 = 7.9;
+// the number of bytes of a chunk, which will be used to estimate the entropy (splitted into ENTROPY_PROBE_NUMBER_OF_SAMPLES evenly distributed samples).
+pub(crate) const ENTROPY_PROBE_SIZE: usize = 4096;
+pub(crate) const ENTROPY_PROBE_NUMBER_OF_SAMPLES: usize = 4;
+pub(crate) const BROTLI_MIN_WINDOW_SIZE: u32 = 10;
+pub(crate) const BROTLI_MAX_WINDOW_SIZE: u32 = 24;
+pub(crate) const XZ_MIN_DICTIONARY_SIZE: u32 = 4096;
+pub(crate) const XZ_STREAM_FOOTER_SIZE: usize = 12;
+pub(crate) const XZ_STREAM_FOOTER_MAGIC: [u8; 2] = *b"YZ";
+
+// merkle tree (domain separation of leaves and inner nodes, see RFC 6962)
+pub(crate) const MERKLE_TREE_LEAF_PREFIX: u8 = 0x00;
+pub(crate) const MERKLE_TREE_NODE_PREFIX: u8 = 0x01;
+
+// ssdeep (see the spamsum algorithm of Andrew Tridgell and the libfuzzy implementation)
+pub(crate) const SSDEEP_ROLLING_WINDOW: usize = 7;
+pub(crate) const SSDEEP_MIN_BLOCKSIZE: u32 = 3;
+pub(crate) const SSDEEP_SPAMSUM_LENGTH: usize = 64;
+pub(crate) const SSDEEP_NUM_BLOCKHASHES: usize = 31;
+pub(crate) const SSDEEP_HASH_INIT: u32 = 0x28021967;
+pub(crate) const SSDEEP_HASH_PRIME: u32 = 0x01000193;
+pub(crate) const SSDEEP_BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
 /// the default key length of a public signature key
 pub const ED25519_DALEK_PUBKEY_LEN: usize = PUBLIC_KEY_LENGTH;
@@ -100,7 +210,7 @@ pub const FILE_EXTENSION_FIRST_VALUE: &str = "z01";
 
 // default versions
 /// current header version for the [ChunkHeader](crate::header::ChunkHeader).
-pub const DEFAULT_HEADER_VERSION_CHUNK_HEADER: u8 = 1;
+pub const DEFAULT_HEADER_VERSION_CHUNK_HEADER: u8 = 2;
 /// current header version for the [HashValue](crate::header::HashValue) structure.
 pub const DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER: u8 = 2;
 /// current header version for the [HashHeader](crate::header::HashHeader).
@@ -118,20 +228,20 @@ pub const DEFAULT_HEADER_VERSION_ENCRYPTION_HEADER: u8 = 2;
 /// current header version for the [MainHeader](crate::header::MainHeader).
 pub const DEFAULT_HEADER_VERSION_MAIN_HEADER: u8 = 2;
 /// current header version for the [FileHeader](crate::header::FileHeader).
-pub const DEFAULT_HEADER_VERSION_FILE_HEADER: u8 = 1;
+pub const DEFAULT_HEADER_VERSION_FILE_HEADER: u8 = 4;
 /// current header version for the [ObjectHeader](crate::header::ObjectHeader).
 pub const DEFAULT_HEADER_VERSION_OBJECT_HEADER: u8 = 1;
 
 /// current footer version for the [ObjectFooterPhysical](crate::footer::ObjectFooterPhysical).
-pub const DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_PHYSICAL: u8 = 1;
+pub const DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_PHYSICAL: u8 = 4;
 /// current footer version for the [ObjectFooterLogical](crate::header::ObjectFooterLogical).
-pub const DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_LOGICAL: u8 = 1;
+pub const DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_LOGICAL: u8 = 2;
 /// current footer version for the [SegmentFooter](crate::header::SegmentFooter).
-pub const DEFAULT_FOOTER_VERSION_SEGMENT_FOOTER: u8 = 2;
+pub const DEFAULT_FOOTER_VERSION_SEGMENT_FOOTER: u8 = 3;
 /// current footer version for the [MainFooter](crate::header::MainFooter).
 pub const DEFAULT_FOOTER_VERSION_MAIN_FOOTER: u8 = 1;
 /// current footer version for the [FileFooter](crate::header::FileFooter).
-pub const DEFAULT_FOOTER_VERSION_FILE_FOOTER: u8 = 1;
+pub const DEFAULT_FOOTER_VERSION_FILE_FOOTER: u8 = 5;
 
 /// The default header signature length.
 pub const HEADER_SIGNATURE_LENGTH: usize = 4;
@@ -153,4 +263,9 @@ pub(crate) const METADATA_EXT_KEY_UID: &str = "uid";
 #[cfg(target_os = "linux")]
 pub(crate) const METADATA_EXT_KEY_GID: &str = "gid";
 #[cfg(target_os = "windows")]
-pub(crate) const METADATA_EXT_DW_FILE_ATTRIBUTES: &str = "dwFileAttributes";
\ No newline at end of file
+pub(crate) const METADATA_EXT_DW_FILE_ATTRIBUTES: &str = "dwFileAttributes";
+
+// named data streams
+/// the extended attribute of ntfs-3g, which contains the names of the alternate data streams (separated by null bytes).
+#[cfg(target_os = "linux")]
+pub(crate) const NTFS_STREAMS_LIST_XATTR: &str = "ntfs.streams.list";
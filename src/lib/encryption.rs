@@ -14,7 +14,7 @@ use pkcs5::{
 use scrypt::Params as ScryptParams;
 use aes_gcm_siv::{
 	Aes256GcmSiv, Aes128GcmSiv, Nonce, Key,
-	aead::{Aead, NewAead},
+	aead::{Aead, AeadInPlace, NewAead},
 };
 use byteorder::{LittleEndian, WriteBytesExt};
 use rand::{rngs::OsRng, RngCore};
@@ -251,6 +251,28 @@ impl Encryption {
 		};
 	}
 
+	/// method to decrypt a message with a key and and the given chunk number in place (the given buffer will contain the plaintext afterwards).
+	/// In contrast to [Encryption::decrypt_message], no additional buffer will be allocated.
+	/// # Error
+	/// This method will fail, if the decryption fails.
+	pub fn decrypt_message_in_place<K, A>(key: K, buffer: &mut Vec<u8>, chunk_no: u64, algorithm: A) -> Result<()>
+	where
+		K: AsRef<[u8]>,
+		A: Borrow<EncryptionAlgorithm>,
+	{
+		let nonce = Encryption::chunk_as_crypto_nonce(chunk_no)?;
+		match algorithm.borrow() {
+			EncryptionAlgorithm::AES256GCMSIV => {
+				let cipher = Aes256GcmSiv::new(Key::from_slice(key.as_ref()));
+				Ok(cipher.decrypt_in_place(&nonce, b"", buffer)?)
+			},
+			EncryptionAlgorithm::AES128GCMSIV => {
+				let cipher = Aes128GcmSiv::new(Key::from_slice(key.as_ref()));
+				Ok(cipher.decrypt_in_place(&nonce, b"", buffer)?)
+			},
+		}
+	}
+
 	/// encrypts the given header with the given nonce.
 	/// This method should primary used to encrypt the given header.
 	/// Returns a the cipthertext as ```Vec<u8>```.
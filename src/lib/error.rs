@@ -13,6 +13,8 @@ use ed25519_dalek::ed25519::Error as Ed25519Error;
 use base64::DecodeError as Base64DecodingError;
 use lz4_flex::frame::Error as Lz4Error;
 use time::error::ComponentRange as ComponentRangeError;
+#[cfg(feature = "http")]
+use ureq::Error as HttpRequestError;
 
 /// The main error-type of this crate.
 #[derive(Debug)]
@@ -115,6 +117,32 @@ pub enum ZffErrorKind {
 	NoChunksLeft,
 	/// Error for Seek.
 	Seek,
+	/// Error will be returned, if the NBD peer violates the NBD protocol or replies with an error.
+	NbdProtocolError,
+	/// Error will be returned, if the given glob pattern is invalid.
+	InvalidGlobPattern,
+	/// Error will be returned, if a path could not be found (e.g. in a [ZffVfs](crate::ZffVfs)).
+	PathNotFound,
+	/// Error will be returned, if a HTTP request fails or the HTTP peer replies with an unexpected response.
+	HttpError,
+	/// Error will be returned, if the file number of a given [FileHeader](crate::header::FileHeader) does not match the expected file number.
+	InvalidFileNumber,
+	/// Error will be returned, if a hash set could not be parsed (e.g. an invalid hexadecimal hash value or a malformed NSRL CSV file).
+	HashSetParserError,
+	/// Error will be returned, if the requested named data stream does not exist.
+	MissingDataStream,
+	/// Error will be returned, if the given output buffer is too small to hold the (decompressed) chunk data.
+	BufferTooSmall,
+	/// Error will be returned, if the object contains no piecewise hashes (see [ObjectFooterPhysical::piecewise_hashes](crate::footer::ObjectFooterPhysical::piecewise_hashes)).
+	MissingPiecewiseHashes,
+	/// Error will be returned, if the object contains no merkle tree (see [ObjectFooterPhysical::merkle_root](crate::footer::ObjectFooterPhysical::merkle_root)).
+	MissingMerkleTree,
+	/// Error will be returned, if a feature needs a (collision resistant) hash type, which is not set (e.g. the deduplication of logical objects).
+	MissingHashType,
+	/// Error will be returned, if a calculated hash value does not match the expected hash value (e.g. of a recompressed object).
+	HashMismatch,
+	/// Error will be returned, if the zff container could not be written into a single output stream (e.g. a container with multiple objects).
+	UnsupportedStreamOutput,
 }
 
 impl fmt::Display for ZffErrorKind {
@@ -165,6 +193,19 @@ impl fmt::Display for ZffErrorKind {
 			ZffErrorKind::NoObjectsLeft => "NoObjectsLeft",
 			ZffErrorKind::NoChunksLeft => "NoChunksLeft",
 			ZffErrorKind::Seek => "Seek",
+			ZffErrorKind::NbdProtocolError => "NbdProtocolError",
+			ZffErrorKind::InvalidGlobPattern => "InvalidGlobPattern",
+			ZffErrorKind::PathNotFound => "PathNotFound",
+			ZffErrorKind::HttpError => "HttpError",
+			ZffErrorKind::InvalidFileNumber => "InvalidFileNumber",
+			ZffErrorKind::HashSetParserError => "HashSetParserError",
+			ZffErrorKind::MissingDataStream => "MissingDataStream",
+			ZffErrorKind::BufferTooSmall => "BufferTooSmall",
+			ZffErrorKind::MissingPiecewiseHashes => "MissingPiecewiseHashes",
+			ZffErrorKind::MissingMerkleTree => "MissingMerkleTree",
+			ZffErrorKind::MissingHashType => "MissingHashType",
+			ZffErrorKind::HashMismatch => "HashMismatch",
+			ZffErrorKind::UnsupportedStreamOutput => "UnsupportedStreamOutput",
 		};
 	write!(f, "{}", err_msg)
 	}
@@ -331,6 +372,13 @@ impl From<FromUtf8Error> for ZffError {
 	}
 }
 
+#[cfg(feature = "http")]
+impl From<HttpRequestError> for ZffError {
+	fn from(e: HttpRequestError) -> ZffError {
+		ZffError::new(ZffErrorKind::HttpError, e.to_string())
+	}
+}
+
 impl fmt::Display for ZffError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		let err_msg = format!("{}: {}", self.kind, self.details);
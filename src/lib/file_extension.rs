@@ -6,6 +6,18 @@ use crate::{
 	FILE_EXTENSION_PARSER_ERROR,
 };
 
+/// Returns the appropriate file extension of the given segment number (e.g. "z01" for segment number 1).
+/// # Example
+/// ```
+/// use zff::*;
+/// 
+/// assert_eq!(file_extension_from_segment_number(1), "z01");
+/// assert_eq!(file_extension_from_segment_number(123), "z123");
+/// ```
+pub fn file_extension_from_segment_number(segment_number: u64) -> String {
+	format!("{FILE_EXTENSION_START}{segment_number:02}")
+}
+
 /// Returns the next file extension value.
 /// # Example
 /// ```
@@ -29,11 +41,7 @@ pub fn file_extension_next_value<V: Into<String>>(value: V) -> Result<String> {
 		Err(e) => return Err(ZffError::new(ZffErrorKind::FileExtensionParserError, e.to_string())),
 	};
 	next_value += 1;
-	if next_value <= 9 {
-		Ok(String::from("z0") + &next_value.to_string())
-	} else {
-		Ok(String::from("z") + &next_value.to_string())
-	}
+	Ok(file_extension_from_segment_number(next_value))
 }
 
 /// Returns the previous file extension value.
@@ -59,9 +67,5 @@ pub fn file_extension_previous_value<V: Into<String>>(value: V) -> Result<String
 		Err(e) => return Err(ZffError::new(ZffErrorKind::FileExtensionParserError, e.to_string())),
 	};
 	previous_value -= 1;
-	if previous_value <= 9 {
-		Ok(String::from("z0") + &previous_value.to_string())
-	} else {
-		Ok(String::from("z") + &previous_value.to_string())
-	}
+	Ok(file_extension_from_segment_number(previous_value))
 }
\ No newline at end of file
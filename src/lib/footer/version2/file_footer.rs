@@ -1,5 +1,5 @@
 // - STD
-use std::io::Cursor;
+use std::io::{Cursor, Read};
 
 // - internal
 use crate::{
@@ -7,7 +7,10 @@ use crate::{
 	HeaderCoding,
 	ValueDecoder,
 	ValueEncoder,
+	ZffError,
+	FuzzyHashType,
 	FOOTER_IDENTIFIER_FILE_FOOTER,
+	ERROR_HEADER_DECODER_UNKNOWN_FUZZY_HASH_TYPE,
 };
 use crate::header::{
 	HashHeader,
@@ -33,11 +36,20 @@ pub struct FileFooter {
 	number_of_chunks: u64,
 	/// the original (uncompressed & unencrypted) length of the file.
 	length_of_data: u64,
+	/// the holes (offset, length) of a sparse file (since footer version 2).
+	holes: Vec<(u64, u64)>,
+	/// the file number of the file, whose chunks are used by this (deduplicated) file (since footer version 3).
+	deduplicated_file_number: Option<u64>,
+	/// the named (secondary) data streams of the file (since footer version 4).
+	data_streams: Vec<DataStream>,
+	/// the fuzzy (similarity) hashes of the file (since footer version 5).
+	fuzzy_hashes: Vec<FuzzyHashValue>,
 }
 
 impl FileFooter {
 	/// creates a new FileFooter by given values/hashes.
-	pub fn new(version: u8, acquisition_start: u64, acquisition_end: u64, hash_header: HashHeader, first_chunk_number: u64, number_of_chunks: u64, length_of_data: u64) -> FileFooter {
+	#[allow(clippy::too_many_arguments)]
+	pub fn new(version: u8, acquisition_start: u64, acquisition_end: u64, hash_header: HashHeader, first_chunk_number: u64, number_of_chunks: u64, length_of_data: u64, holes: Vec<(u64, u64)>) -> FileFooter {
 		Self {
 			version,
 			acquisition_start,
@@ -46,6 +58,10 @@ impl FileFooter {
 			first_chunk_number,
 			number_of_chunks,
 			length_of_data,
+			holes,
+			deduplicated_file_number: None,
+			data_streams: Vec::new(),
+			fuzzy_hashes: Vec::new(),
 		}
 	}
 
@@ -81,6 +97,61 @@ impl FileFooter {
 	pub fn length_of_data(&self) -> u64 {
 		self.length_of_data
 	}
+
+	/// returns the holes (offset, length) of the file, if the file is a sparse file. The holes are sorted by their offsets.
+	pub fn holes(&self) -> &Vec<(u64, u64)> {
+		&self.holes
+	}
+
+	/// returns true, if the file is a sparse file.
+	pub fn is_sparse(&self) -> bool {
+		!self.holes.is_empty()
+	}
+
+	/// sets the file number of the file, whose chunks are used by this file (the content of both files is identical).
+	pub fn set_deduplicated_file_number(&mut self, file_number: u64) {
+		self.deduplicated_file_number = Some(file_number);
+	}
+
+	/// returns the file number of the file, whose chunks are used by this file, if this file was deduplicated.
+	pub fn deduplicated_file_number(&self) -> Option<u64> {
+		self.deduplicated_file_number
+	}
+
+	/// returns true, if the file was deduplicated (the chunks of this file belong to another file with identical content).
+	pub fn is_deduplicated(&self) -> bool {
+		self.deduplicated_file_number.is_some()
+	}
+
+	/// sets the named (secondary) data streams of the file.
+	pub fn set_data_streams(&mut self, data_streams: Vec<DataStream>) {
+		self.data_streams = data_streams;
+	}
+
+	/// returns the named (secondary) data streams of the file.
+	pub fn data_streams(&self) -> &Vec<DataStream> {
+		&self.data_streams
+	}
+
+	/// returns the named (secondary) data stream with the given name, if available.
+	pub fn data_stream<N: AsRef<str>>(&self, name: N) -> Option<&DataStream> {
+		self.data_streams.iter().find(|data_stream| data_stream.name() == name.as_ref())
+	}
+
+	/// sets the fuzzy (similarity) hashes of the file.
+	pub fn set_fuzzy_hashes(&mut self, fuzzy_hashes: Vec<FuzzyHashValue>) {
+		self.fuzzy_hashes = fuzzy_hashes;
+	}
+
+	/// returns the fuzzy (similarity) hashes of the file.
+	pub fn fuzzy_hashes(&self) -> &Vec<FuzzyHashValue> {
+		&self.fuzzy_hashes
+	}
+
+	/// returns the fuzzy hash of the given [FuzzyHashType], if available.
+	pub fn fuzzy_hash(&self, hash_type: &FuzzyHashType) -> Option<&str> {
+		self.fuzzy_hashes.iter().find(|fuzzy_hash| fuzzy_hash.hash_type() == hash_type).map(|fuzzy_hash| fuzzy_hash.hash())
+	}
 }
 
 impl HeaderCoding for FileFooter {
@@ -99,6 +170,29 @@ impl HeaderCoding for FileFooter {
 		vec.append(&mut self.first_chunk_number.encode_directly());
 		vec.append(&mut self.number_of_chunks.encode_directly());
 		vec.append(&mut self.length_of_data.encode_directly());
+		if self.version >= 2 {
+			vec.append(&mut (self.holes.len() as u64).encode_directly());
+			for (offset, length) in &self.holes {
+				vec.append(&mut offset.encode_directly());
+				vec.append(&mut length.encode_directly());
+			}
+		}
+		if self.version >= 3 {
+			// the file number 0 is reserved for the virtual root directory and marks a not deduplicated file.
+			vec.append(&mut self.deduplicated_file_number.unwrap_or(0).encode_directly());
+		}
+		if self.version >= 4 {
+			vec.append(&mut (self.data_streams.len() as u64).encode_directly());
+			for data_stream in &self.data_streams {
+				vec.append(&mut data_stream.encode());
+			}
+		}
+		if self.version >= 5 {
+			vec.append(&mut (self.fuzzy_hashes.len() as u64).encode_directly());
+			for fuzzy_hash in &self.fuzzy_hashes {
+				vec.append(&mut fuzzy_hash.encode());
+			}
+		}
 		vec
 	}
 	fn decode_content(data: Vec<u8>) -> Result<FileFooter> {
@@ -110,6 +204,153 @@ impl HeaderCoding for FileFooter {
 		let first_chunk_number = u64::decode_directly(&mut cursor)?;
 		let number_of_chunks = u64::decode_directly(&mut cursor)?;
 		let length_of_data = u64::decode_directly(&mut cursor)?;
-		Ok(FileFooter::new(footer_version, acquisition_start, acquisition_end, hash_header, first_chunk_number, number_of_chunks, length_of_data))
+		let mut holes = Vec::new();
+		if footer_version >= 2 {
+			let number_of_holes = u64::decode_directly(&mut cursor)?;
+			for _ in 0..number_of_holes {
+				let offset = u64::decode_directly(&mut cursor)?;
+				let length = u64::decode_directly(&mut cursor)?;
+				holes.push((offset, length));
+			}
+		}
+		let mut file_footer = FileFooter::new(footer_version, acquisition_start, acquisition_end, hash_header, first_chunk_number, number_of_chunks, length_of_data, holes);
+		if footer_version >= 3 {
+			match u64::decode_directly(&mut cursor)? {
+				0 => (),
+				deduplicated_file_number => file_footer.set_deduplicated_file_number(deduplicated_file_number),
+			}
+		}
+		if footer_version >= 4 {
+			let number_of_data_streams = u64::decode_directly(&mut cursor)?;
+			let mut data_streams = Vec::new();
+			for _ in 0..number_of_data_streams {
+				data_streams.push(DataStream::decode(&mut cursor)?);
+			}
+			file_footer.set_data_streams(data_streams);
+		}
+		if footer_version >= 5 {
+			let number_of_fuzzy_hashes = u64::decode_directly(&mut cursor)?;
+			let mut fuzzy_hashes = Vec::new();
+			for _ in 0..number_of_fuzzy_hashes {
+				fuzzy_hashes.push(FuzzyHashValue::decode(&mut cursor)?);
+			}
+			file_footer.set_fuzzy_hashes(fuzzy_hashes);
+		}
+		Ok(file_footer)
+	}
+}
+/// A named (secondary) data stream of a file (e.g. an NTFS alternate data stream or a HFS+ resource fork).
+/// Each data stream has its own chunks and hash values.
+#[derive(Debug,Clone,Eq,PartialEq)]
+pub struct DataStream {
+	/// the name of the data stream.
+	name: String,
+	/// the first chunk number which was used for this data stream.
+	first_chunk_number: u64,
+	/// The full number of chunks for this data stream.
+	number_of_chunks: u64,
+	/// the original (uncompressed & unencrypted) length of the data stream.
+	length_of_data: u64,
+	/// The appropriate hash header for this data stream.
+	hash_header: HashHeader,
+}
+
+impl DataStream {
+	/// creates a new [DataStream] by the given values.
+	pub fn new<N: Into<String>>(name: N, first_chunk_number: u64, number_of_chunks: u64, length_of_data: u64, hash_header: HashHeader) -> DataStream {
+		Self {
+			name: name.into(),
+			first_chunk_number,
+			number_of_chunks,
+			length_of_data,
+			hash_header,
+		}
 	}
-}
\ No newline at end of file
+
+	/// returns the name of the data stream.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// returns the first chunk number, used for the data stream.
+	pub fn first_chunk_number(&self) -> u64 {
+		self.first_chunk_number
+	}
+
+	/// returns the total number of chunks, used for the data stream.
+	pub fn number_of_chunks(&self) -> u64 {
+		self.number_of_chunks
+	}
+
+	/// returns the original (uncompressed, unencrypted) size of the data stream.
+	pub fn length_of_data(&self) -> u64 {
+		self.length_of_data
+	}
+
+	/// returns the hash header of the data stream.
+	pub fn hash_header(&self) -> &HashHeader {
+		&self.hash_header
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut vec = self.name.encode_directly();
+		vec.append(&mut self.first_chunk_number.encode_directly());
+		vec.append(&mut self.number_of_chunks.encode_directly());
+		vec.append(&mut self.length_of_data.encode_directly());
+		vec.append(&mut self.hash_header.encode_directly());
+		vec
+	}
+
+	fn decode<R: Read>(data: &mut R) -> Result<DataStream> {
+		let name = String::decode_directly(data)?;
+		let first_chunk_number = u64::decode_directly(data)?;
+		let number_of_chunks = u64::decode_directly(data)?;
+		let length_of_data = u64::decode_directly(data)?;
+		let hash_header = HashHeader::decode_directly(data)?;
+		Ok(DataStream::new(name, first_chunk_number, number_of_chunks, length_of_data, hash_header))
+	}
+}
+
+/// A fuzzy (similarity) hash of a file (e.g. a ssdeep hash), which can be used to find similar files.
+#[derive(Debug,Clone,Eq,PartialEq)]
+pub struct FuzzyHashValue {
+	/// the type of the fuzzy hash.
+	hash_type: FuzzyHashType,
+	/// the fuzzy hash in its usual string representation (e.g. "blocksize:hash:hash" for ssdeep).
+	hash: String,
+}
+
+impl FuzzyHashValue {
+	/// creates a new [FuzzyHashValue] by the given values.
+	pub fn new<H: Into<String>>(hash_type: FuzzyHashType, hash: H) -> FuzzyHashValue {
+		Self {
+			hash_type,
+			hash: hash.into(),
+		}
+	}
+
+	/// returns the type of the fuzzy hash.
+	pub fn hash_type(&self) -> &FuzzyHashType {
+		&self.hash_type
+	}
+
+	/// returns the fuzzy hash.
+	pub fn hash(&self) -> &str {
+		&self.hash
+	}
+
+	fn encode(&self) -> Vec<u8> {
+		let mut vec = vec![self.hash_type.clone() as u8];
+		vec.append(&mut self.hash.encode_directly());
+		vec
+	}
+
+	fn decode<R: Read>(data: &mut R) -> Result<FuzzyHashValue> {
+		let hash_type = match u8::decode_directly(data)? {
+			0 => FuzzyHashType::Ssdeep,
+			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_FUZZY_HASH_TYPE)),
+		};
+		let hash = String::decode_directly(data)?;
+		Ok(FuzzyHashValue::new(hash_type, hash))
+	}
+}
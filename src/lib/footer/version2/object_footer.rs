@@ -19,6 +19,7 @@ use crate::{
 };
 use crate::header::{
 	HashHeader,
+	HashValue,
 };
 
 // - external
@@ -94,6 +95,9 @@ impl ObjectFooter {
 /// - the first chunk number, which is used for this physical dump
 /// - the total number of chunks, used for this physical dump
 /// - a hash header with the appropriate hash values of the underlying physical dump
+/// - the number of chunks, whose compression was skipped by the adaptive compression (since footer version 2)
+/// - the size of the hash windows and a hash header for each hash window of the underlying physical dump (piecewise hashes, since footer version 3)
+/// - the roots of the merkle subtrees of each segment and the (signed) root of the merkle tree over all chunks (since footer version 4)
 #[derive(Debug,Clone)]
 pub struct ObjectFooterPhysical {
 	version: u8,
@@ -103,6 +107,11 @@ pub struct ObjectFooterPhysical {
 	first_chunk_number: u64,
 	number_of_chunks: u64,
 	hash_header: HashHeader,
+	skipped_compression_chunks: u64,
+	piecewise_hash_size: u64,
+	piecewise_hashes: Vec<HashHeader>,
+	merkle_segment_roots: HashMap<u64, Vec<u8>>, //<segment number, root of the appropriate subtree>
+	merkle_root: Option<HashValue>,
 }
 
 impl ObjectFooterPhysical {
@@ -116,9 +125,58 @@ impl ObjectFooterPhysical {
 			first_chunk_number,
 			number_of_chunks,
 			hash_header,
+			skipped_compression_chunks: 0,
+			piecewise_hash_size: 0,
+			piecewise_hashes: Vec::new(),
+			merkle_segment_roots: HashMap::new(),
+			merkle_root: None,
 		}
 	}
 
+	/// sets the merkle tree of this object: the root of the merkle tree (the hash type of the tree, the root hash and an optional signature)
+	/// and the roots of the subtrees over the chunks of each segment (the leaves of these subtrees are stored in the appropriate segment footers).
+	pub fn set_merkle_tree(&mut self, merkle_root: HashValue, merkle_segment_roots: HashMap<u64, Vec<u8>>) {
+		self.merkle_root = Some(merkle_root);
+		self.merkle_segment_roots = merkle_segment_roots;
+	}
+
+	/// returns the root of the merkle tree over all chunks of this object (if available).
+	pub fn merkle_root(&self) -> Option<&HashValue> {
+		self.merkle_root.as_ref()
+	}
+
+	/// returns the roots of the merkle subtrees of each segment (the root of the merkle tree is calculated over these roots, ordered by the segment number).
+	pub fn merkle_segment_roots(&self) -> &HashMap<u64, Vec<u8>> {
+		&self.merkle_segment_roots
+	}
+
+	/// sets the piecewise hashes: the given hash window size (in bytes) and a [HashHeader] for each hash window.
+	/// The last hash window could be smaller than the given hash window size.
+	pub fn set_piecewise_hashes(&mut self, piecewise_hash_size: u64, piecewise_hashes: Vec<HashHeader>) {
+		self.piecewise_hash_size = piecewise_hash_size;
+		self.piecewise_hashes = piecewise_hashes;
+	}
+
+	/// returns the size of the hash windows (in bytes), used for the piecewise hashes. Returns 0, if no piecewise hashes were calculated.
+	pub fn piecewise_hash_size(&self) -> u64 {
+		self.piecewise_hash_size
+	}
+
+	/// returns the piecewise hashes (a [HashHeader] for each hash window).
+	pub fn piecewise_hashes(&self) -> &Vec<HashHeader> {
+		&self.piecewise_hashes
+	}
+
+	/// sets the number of chunks, whose compression was skipped by the adaptive compression.
+	pub fn set_skipped_compression_chunks(&mut self, skipped_compression_chunks: u64) {
+		self.skipped_compression_chunks = skipped_compression_chunks;
+	}
+
+	/// returns the number of chunks, whose compression was skipped by the adaptive compression.
+	pub fn skipped_compression_chunks(&self) -> u64 {
+		self.skipped_compression_chunks
+	}
+
 	/// returns the appropriate acquisition start time.
 	pub fn acquisition_start(&self) -> u64 {
 		self.acquisition_start
@@ -166,6 +224,23 @@ impl HeaderCoding for ObjectFooterPhysical {
 		vec.append(&mut self.first_chunk_number.encode_directly());
 		vec.append(&mut self.number_of_chunks.encode_directly());
 		vec.append(&mut self.hash_header.encode_directly());
+		if self.version >= 2 {
+			vec.append(&mut self.skipped_compression_chunks.encode_directly());
+		}
+		if self.version >= 3 {
+			vec.append(&mut self.piecewise_hash_size.encode_directly());
+			vec.append(&mut self.piecewise_hashes.encode_directly());
+		}
+		if self.version >= 4 {
+			match &self.merkle_root {
+				Some(merkle_root) => {
+					vec.append(&mut self.merkle_segment_roots.encode_directly());
+					vec.append(&mut merkle_root.encode_directly());
+				},
+				// an empty map of segment roots marks an object without merkle tree.
+				None => vec.append(&mut HashMap::<u64, Vec<u8>>::new().encode_directly()),
+			}
+		}
 		vec
 	}
 	fn decode_content(data: Vec<u8>) -> Result<ObjectFooterPhysical> {
@@ -177,7 +252,23 @@ impl HeaderCoding for ObjectFooterPhysical {
 		let first_chunk_number = u64::decode_directly(&mut cursor)?;
 		let number_of_chunks = u64::decode_directly(&mut cursor)?;
 		let hash_header = HashHeader::decode_directly(&mut cursor)?;
-		Ok(ObjectFooterPhysical::new(footer_version, acquisition_start, acquisition_end, length_of_data, first_chunk_number, number_of_chunks, hash_header))
+		let mut footer = ObjectFooterPhysical::new(footer_version, acquisition_start, acquisition_end, length_of_data, first_chunk_number, number_of_chunks, hash_header);
+		if footer_version >= 2 {
+			footer.set_skipped_compression_chunks(u64::decode_directly(&mut cursor)?);
+		}
+		if footer_version >= 3 {
+			let piecewise_hash_size = u64::decode_directly(&mut cursor)?;
+			let piecewise_hashes = Vec::<HashHeader>::decode_directly(&mut cursor)?;
+			footer.set_piecewise_hashes(piecewise_hash_size, piecewise_hashes);
+		}
+		if footer_version >= 4 {
+			let merkle_segment_roots = HashMap::<u64, Vec<u8>>::decode_directly(&mut cursor)?;
+			if !merkle_segment_roots.is_empty() {
+				let merkle_root = HashValue::decode_directly(&mut cursor)?;
+				footer.set_merkle_tree(merkle_root, merkle_segment_roots);
+			}
+		}
+		Ok(footer)
 	}
 }
 
@@ -190,6 +281,7 @@ impl HeaderCoding for ObjectFooterPhysical {
 /// - a [HashMap] in which offsets of the corresponding file headers can be found.
 /// - a [HashMap] in which segment numbers the corresponding file footers can be found.
 /// - a [HashMap] in which offsets the corresponding file footers can be found.
+/// - the number of chunks, whose compression was skipped by the adaptive compression (since footer version 2)
 #[derive(Debug,Clone)]
 pub struct ObjectFooterLogical {
 	version: u8,
@@ -200,6 +292,7 @@ pub struct ObjectFooterLogical {
 	file_header_offsets: HashMap<u64, u64>,
 	file_footer_segment_numbers: HashMap<u64, u64>,
 	file_footer_offsets: HashMap<u64, u64>,
+	skipped_compression_chunks: u64,
 }
 
 impl ObjectFooterLogical {
@@ -213,7 +306,8 @@ impl ObjectFooterLogical {
 			file_header_segment_numbers: HashMap::new(),
 			file_header_offsets: HashMap::new(),
 			file_footer_segment_numbers: HashMap::new(),
-			file_footer_offsets: HashMap::new()
+			file_footer_offsets: HashMap::new(),
+			skipped_compression_chunks: 0,
 		}
 	}
 
@@ -236,6 +330,7 @@ impl ObjectFooterLogical {
 			file_header_offsets,
 			file_footer_segment_numbers,
 			file_footer_offsets,
+			skipped_compression_chunks: 0,
 		}
 	}
 
@@ -312,6 +407,16 @@ impl ObjectFooterLogical {
 	pub fn set_acquisition_end(&mut self, end: u64) {
 		self.acquisition_end = end;
 	}
+
+	/// sets the number of chunks, whose compression was skipped by the adaptive compression.
+	pub fn set_skipped_compression_chunks(&mut self, skipped_compression_chunks: u64) {
+		self.skipped_compression_chunks = skipped_compression_chunks;
+	}
+
+	/// returns the number of chunks, whose compression was skipped by the adaptive compression.
+	pub fn skipped_compression_chunks(&self) -> u64 {
+		self.skipped_compression_chunks
+	}
 }
 
 impl HeaderCoding for ObjectFooterLogical {
@@ -332,6 +437,9 @@ impl HeaderCoding for ObjectFooterLogical {
 		vec.append(&mut self.file_header_offsets.encode_directly());
 		vec.append(&mut self.file_footer_segment_numbers.encode_directly());
 		vec.append(&mut self.file_footer_offsets.encode_directly());
+		if self.version >= 2 {
+			vec.append(&mut self.skipped_compression_chunks.encode_directly());
+		}
 		vec
 	}
 	fn decode_content(data: Vec<u8>) -> Result<ObjectFooterLogical> {
@@ -344,7 +452,11 @@ impl HeaderCoding for ObjectFooterLogical {
 		let file_header_offsets = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
 		let file_footer_segment_numbers = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
 		let file_footer_offsets = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
-		Ok(ObjectFooterLogical::new(footer_version, acquisition_start, acquisition_end, root_dir_filenumbers, file_header_segment_numbers, file_header_offsets, file_footer_segment_numbers, file_footer_offsets))
+		let mut footer = ObjectFooterLogical::new(footer_version, acquisition_start, acquisition_end, root_dir_filenumbers, file_header_segment_numbers, file_header_offsets, file_footer_segment_numbers, file_footer_offsets);
+		if footer_version >= 2 {
+			footer.set_skipped_compression_chunks(u64::decode_directly(&mut cursor)?);
+		}
+		Ok(footer)
 	}
 
 }
\ No newline at end of file
@@ -18,6 +18,7 @@ use crate::{
 /// The SegmentFooter is a footer which is be written at the end of each segment.
 /// The footer contains a table on the chunks, present in the appropriate segment.
 /// The offset table is internally managed as a ```Vec<u64>```.
+/// Since footer version 3, the footer also contains the merkle tree leaves of the chunks, present in the appropriate segment (if available).
 #[derive(Debug,Clone)]
 pub struct SegmentFooter {
 	version: u8,
@@ -25,6 +26,7 @@ pub struct SegmentFooter {
 	object_header_offsets: HashMap<u64, u64>, //<object number, offset>,
 	object_footer_offsets: HashMap<u64, u64>, //<object number, offset>,
 	chunk_offsets: HashMap<u64, u64>, //<chunk number, offset>
+	merkle_leaves: HashMap<u64, Vec<u8>>, //<chunk number, leaf hash>
 	/// The offset where the footer starts.
 	footer_offset: u64,
 
@@ -39,6 +41,7 @@ impl SegmentFooter {
 			object_header_offsets: HashMap::new(),
 			object_footer_offsets: HashMap::new(),
 			chunk_offsets: HashMap::new(),
+			merkle_leaves: HashMap::new(),
 			footer_offset: 0,
 		}
 	}
@@ -51,10 +54,21 @@ impl SegmentFooter {
 			object_header_offsets,
 			object_footer_offsets,
 			chunk_offsets,
+			merkle_leaves: HashMap::new(),
 			footer_offset,
 		}
 	}
 
+	/// adds a merkle tree leaf (the hash of the appropriate chunk data) to the SegmentFooter.
+	pub fn add_merkle_leaf(&mut self, chunk_number: u64, leaf_hash: Vec<u8>) {
+		self.merkle_leaves.insert(chunk_number, leaf_hash);
+	}
+
+	/// returns a reference of the merkle tree leaves (chunk number, leaf hash) of the chunks in this segment.
+	pub fn merkle_leaves(&self) -> &HashMap<u64, Vec<u8>> {
+		&self.merkle_leaves
+	}
+
 	/// returns the length of the segment in bytes.
 	pub fn length_of_segment(&self) -> u64 {
 		self.length_of_segment
@@ -120,6 +134,10 @@ impl HeaderCoding for SegmentFooter {
 		vec.append(&mut self.object_header_offsets.encode_directly());
 		vec.append(&mut self.object_footer_offsets.encode_directly());
 		vec.append(&mut self.chunk_offsets.encode_directly());
+		if self.version >= 3 {
+			vec.append(&mut self.merkle_leaves.encode_directly());
+		}
+		// the footer offset has to be the last value, because the footer will be located by the last bytes of the segment.
 		vec.append(&mut self.footer_offset.encode_directly());
 		vec
 	}
@@ -132,7 +150,14 @@ impl HeaderCoding for SegmentFooter {
 		let object_header_offsets = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
 		let object_footer_offsets = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
 		let chunk_offsets = HashMap::<u64, u64>::decode_directly(&mut cursor)?;
+		let merkle_leaves = if footer_version >= 3 {
+			HashMap::<u64, Vec<u8>>::decode_directly(&mut cursor)?
+		} else {
+			HashMap::new()
+		};
 		let footer_offset = u64::decode_directly(&mut cursor)?;
-		Ok(SegmentFooter::new(footer_version, length_of_segment, object_header_offsets, object_footer_offsets, chunk_offsets, footer_offset))
+		let mut footer = SegmentFooter::new(footer_version, length_of_segment, object_header_offsets, object_footer_offsets, chunk_offsets, footer_offset);
+		footer.merkle_leaves = merkle_leaves;
+		Ok(footer)
 	}
 }
\ No newline at end of file
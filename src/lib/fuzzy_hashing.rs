@@ -0,0 +1,291 @@
+// - STD
+use std::fmt;
+
+// - internal
+use crate::{
+	SSDEEP_ROLLING_WINDOW,
+	SSDEEP_MIN_BLOCKSIZE,
+	SSDEEP_SPAMSUM_LENGTH,
+	SSDEEP_NUM_BLOCKHASHES,
+	SSDEEP_HASH_INIT,
+	SSDEEP_HASH_PRIME,
+	SSDEEP_BASE64_ALPHABET,
+};
+
+/// Defines all fuzzy (similarity) hashing algorithms, which are implemented in zff.
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Debug,Clone,Eq,PartialEq,Hash)]
+pub enum FuzzyHashType {
+	/// The context triggered piecewise hashing algorithm of ssdeep (spamsum) with the encoding value 0.
+	Ssdeep = 0,
+}
+
+impl fmt::Display for FuzzyHashType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let msg = match self {
+			FuzzyHashType::Ssdeep => "ssdeep",
+		};
+		write!(f, "{}", msg)
+	}
+}
+
+/// This trait is implemented by all fuzzy hashers. A fuzzy hasher consumes the data in a single pass (like the cryptographic hashers)
+/// and returns the appropriate similarity hash.
+pub trait FuzzyHasher: Send {
+	/// returns the appropriate [FuzzyHashType].
+	fn hash_type(&self) -> FuzzyHashType;
+	/// processes the given data.
+	fn update(&mut self, data: &[u8]);
+	/// returns the fuzzy hash of the processed data.
+	fn finalize(&self) -> String;
+}
+
+/// structure contains serveral methods to handle fuzzy hashing
+#[derive(Debug,Clone)]
+pub struct FuzzyHash;
+
+impl FuzzyHash {
+	/// returns a new [FuzzyHasher] for the given [FuzzyHashType].
+	pub fn new_hasher(hash_type: &FuzzyHashType) -> Box<dyn FuzzyHasher> {
+		match hash_type {
+			FuzzyHashType::Ssdeep => Box::new(SsdeepHasher::new()),
+		}
+	}
+}
+
+// the rolling hash, which determines the trigger points (see the spamsum algorithm).
+#[derive(Debug,Clone,Default)]
+struct RollingHash {
+	window: [u8; SSDEEP_ROLLING_WINDOW],
+	h1: u32,
+	h2: u32,
+	h3: u32,
+	n: u32,
+}
+
+impl RollingHash {
+	fn update(&mut self, c: u8) {
+		self.h2 = self.h2.wrapping_sub(self.h1).wrapping_add((SSDEEP_ROLLING_WINDOW as u32).wrapping_mul(c as u32));
+		// the window position wraps like the 32-bit counter of libfuzzy (this matters for inputs greater than 4 GiB).
+		let position = self.n as usize % SSDEEP_ROLLING_WINDOW;
+		self.h1 = self.h1.wrapping_add(c as u32).wrapping_sub(self.window[position] as u32);
+		self.window[position] = c;
+		self.n = self.n.wrapping_add(1);
+		self.h3 = (self.h3 << 5) ^ c as u32;
+	}
+
+	fn sum(&self) -> u32 {
+		self.h1.wrapping_add(self.h2).wrapping_add(self.h3)
+	}
+}
+
+// the state of a single block size.
+#[derive(Debug,Clone)]
+struct BlockHash {
+	h: u32,
+	halfh: u32,
+	digest: Vec<u8>, // a full digest contains SSDEEP_SPAMSUM_LENGTH - 1 characters and the pending character of the last trigger point.
+	halfdigest: Option<u8>,
+}
+
+impl BlockHash {
+	fn new(h: u32, halfh: u32) -> BlockHash {
+		Self {
+			h,
+			halfh,
+			digest: Vec::with_capacity(SSDEEP_SPAMSUM_LENGTH),
+			halfdigest: None,
+		}
+	}
+}
+
+/// A [FuzzyHasher] which calculates ssdeep compatible hashes (context triggered piecewise hashes, "blocksize:hash:hash").
+/// ```
+/// use zff::{FuzzyHash, FuzzyHashType};
+///
+/// let mut hasher = FuzzyHash::new_hasher(&FuzzyHashType::Ssdeep);
+/// hasher.update(b"The quick brown fox ");
+/// hasher.update(b"jumps over the lazy dog");
+/// assert_eq!(hasher.finalize(), "3:FJKKIUKact:FHIGi");
+/// assert_eq!(FuzzyHash::new_hasher(&FuzzyHashType::Ssdeep).finalize(), "3::");
+///
+/// // a multi-KB input and the same input zero-padded to the same length (the rolling hash is 0 at the end of the
+/// // zero-padded input, so the last character of the full digest is the pending character of the last trigger point).
+/// let mut state = 1u32;
+/// let data: Vec<u8> = (0..12288).map(|_| {
+///     state = state.wrapping_mul(1103515245).wrapping_add(12345);
+///     (state >> 16) as u8
+/// }).collect();
+/// let mut hasher = FuzzyHash::new_hasher(&FuzzyHashType::Ssdeep);
+/// hasher.update(&data);
+/// assert_eq!(hasher.finalize(), "192:xD/uceMkIkJ/jb4ACeXCQ7diBlG6apx/CMu4tx73U1L/VujBh+wH+ADnRnBO83kg:xD/5kIQXbCQ7d2AxNL73U3WhNnB6/+mm");
+///
+/// let mut hasher = FuzzyHash::new_hasher(&FuzzyHashType::Ssdeep);
+/// hasher.update(&data[..12000]);
+/// hasher.update(&[0; 288]);
+/// assert_eq!(hasher.finalize(), "192:xD/uceMkIkJ/jb4ACeXCQ7diBlG6apx/CMu4tx73U1L/VujBh+wH+ADnRnBO83kI:xD/5kIQXbCQ7d2AxNL73U3WhNnB6/+my");
+/// ```
+#[derive(Debug,Clone)]
+pub struct SsdeepHasher {
+	roll: RollingHash,
+	block_hashes: Vec<BlockHash>,
+	bhstart: usize,
+	total_size: u64,
+	lasth: Option<u32>,
+}
+
+impl SsdeepHasher {
+	/// creates a new [SsdeepHasher].
+	pub fn new() -> SsdeepHasher {
+		Self {
+			roll: RollingHash::default(),
+			block_hashes: vec![BlockHash::new(SSDEEP_HASH_INIT, SSDEEP_HASH_INIT)],
+			bhstart: 0,
+			total_size: 0,
+			lasth: None,
+		}
+	}
+
+	fn block_size(index: usize) -> u64 {
+		(SSDEEP_MIN_BLOCKSIZE as u64) << index
+	}
+
+	fn sum_hash(c: u8, h: u32) -> u32 {
+		h.wrapping_mul(SSDEEP_HASH_PRIME) ^ c as u32
+	}
+
+	fn step(&mut self, c: u8) {
+		self.roll.update(c);
+		let h = self.roll.sum() as u64;
+		for block_hash in &mut self.block_hashes[self.bhstart..] {
+			block_hash.h = Self::sum_hash(c, block_hash.h);
+			block_hash.halfh = Self::sum_hash(c, block_hash.halfh);
+		}
+		if let Some(lasth) = self.lasth {
+			self.lasth = Some(Self::sum_hash(c, lasth));
+		}
+
+		let mut index = self.bhstart;
+		while index < self.block_hashes.len() {
+			// if this is not a trigger point for this block size, it could not be a trigger point for any greater block size.
+			if h % Self::block_size(index) != Self::block_size(index) - 1 {
+				break;
+			}
+			if self.block_hashes[index].digest.is_empty() {
+				self.try_fork_block_hash();
+			}
+			let block_hash = &mut self.block_hashes[index];
+			let character = SSDEEP_BASE64_ALPHABET[(block_hash.h % 64) as usize];
+			// the pending character of a full digest will be overwritten by the next trigger points.
+			match block_hash.digest.get_mut(SSDEEP_SPAMSUM_LENGTH - 1) {
+				Some(pending_character) => *pending_character = character,
+				None => block_hash.digest.push(character),
+			}
+			block_hash.halfdigest = Some(SSDEEP_BASE64_ALPHABET[(block_hash.halfh % 64) as usize]);
+			if block_hash.digest.len() < SSDEEP_SPAMSUM_LENGTH {
+				block_hash.h = SSDEEP_HASH_INIT;
+				if block_hash.digest.len() < SSDEEP_SPAMSUM_LENGTH / 2 {
+					block_hash.halfh = SSDEEP_HASH_INIT;
+					block_hash.halfdigest = None;
+				}
+			} else {
+				self.try_reduce_block_hash();
+			}
+			index += 1;
+		}
+	}
+
+	// adds the next (greater) block size.
+	fn try_fork_block_hash(&mut self) {
+		let last = &self.block_hashes[self.block_hashes.len() - 1];
+		if self.block_hashes.len() >= SSDEEP_NUM_BLOCKHASHES {
+			if self.lasth.is_none() {
+				self.lasth = Some(last.h);
+			}
+			return;
+		}
+		let block_hash = BlockHash::new(last.h, last.halfh);
+		self.block_hashes.push(block_hash);
+	}
+
+	// drops the smallest block size, if this block size could never be used for the final hash.
+	fn try_reduce_block_hash(&mut self) {
+		if self.block_hashes.len() - self.bhstart < 2 {
+			return;
+		}
+		if Self::block_size(self.bhstart) * SSDEEP_SPAMSUM_LENGTH as u64 >= self.total_size {
+			return;
+		}
+		if self.block_hashes[self.bhstart + 1].digest.len() < SSDEEP_SPAMSUM_LENGTH / 2 {
+			return;
+		}
+		self.bhstart += 1;
+	}
+}
+
+impl Default for SsdeepHasher {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl FuzzyHasher for SsdeepHasher {
+	fn hash_type(&self) -> FuzzyHashType {
+		FuzzyHashType::Ssdeep
+	}
+
+	fn update(&mut self, data: &[u8]) {
+		self.total_size += data.len() as u64;
+		for c in data {
+			self.step(*c);
+		}
+	}
+
+	fn finalize(&self) -> String {
+		let h = self.roll.sum();
+		let mut index = self.bhstart;
+		// initial block size guess (by the total size) and the adaption to the actual digest length.
+		while Self::block_size(index) * (SSDEEP_SPAMSUM_LENGTH as u64) < self.total_size && index < SSDEEP_NUM_BLOCKHASHES - 1 {
+			index += 1;
+		}
+		while index >= self.block_hashes.len() {
+			index -= 1;
+		}
+		while index > self.bhstart && self.block_hashes[index].digest.len() < SSDEEP_SPAMSUM_LENGTH / 2 {
+			index -= 1;
+		}
+
+		let block_hash = &self.block_hashes[index];
+		let length = std::cmp::min(block_hash.digest.len(), SSDEEP_SPAMSUM_LENGTH - 1);
+		let mut first_digest = block_hash.digest[..length].to_vec();
+		if h != 0 {
+			first_digest.push(SSDEEP_BASE64_ALPHABET[(block_hash.h % 64) as usize]);
+		} else if let Some(pending_character) = block_hash.digest.get(SSDEEP_SPAMSUM_LENGTH - 1) {
+			first_digest.push(*pending_character);
+		}
+
+		let mut second_digest = Vec::new();
+		if index < self.block_hashes.len() - 1 {
+			let block_hash = &self.block_hashes[index + 1];
+			let length = std::cmp::min(block_hash.digest.len(), SSDEEP_SPAMSUM_LENGTH / 2 - 1);
+			second_digest.extend_from_slice(&block_hash.digest[..length]);
+			if h != 0 {
+				second_digest.push(SSDEEP_BASE64_ALPHABET[(block_hash.halfh % 64) as usize]);
+			} else if let Some(halfdigest) = block_hash.halfdigest {
+				second_digest.push(halfdigest);
+			}
+		} else if h != 0 {
+			let lasth = match self.lasth {
+				Some(lasth) if index != 0 => lasth,
+				_ => block_hash.h,
+			};
+			second_digest.push(SSDEEP_BASE64_ALPHABET[(lasth % 64) as usize]);
+		}
+
+		format!("{}:{}:{}",
+			Self::block_size(index),
+			String::from_utf8_lossy(&first_digest),
+			String::from_utf8_lossy(&second_digest))
+	}
+}
@@ -1,11 +1,19 @@
 // - STD
 use std::fmt;
 
+// - internal
+use crate::{
+	MERKLE_TREE_LEAF_PREFIX,
+	MERKLE_TREE_NODE_PREFIX,
+};
+
 // - external
 use blake3::{Hasher as Blake3};
 use blake2::{Blake2b512};
 use sha2::{Sha256, Sha512};
 use sha3::{Sha3_256};
+use md5::{Md5};
+use sha1::{Sha1};
 use digest::{DynDigest, Digest};
 
 /// Defines all hashing algorithms, which are implemented in zff.
@@ -23,6 +31,10 @@ pub enum HashType {
 	SHA3_256 = 3,
 	/// The blake3 algorithm with the encoding value 4.
 	Blake3 = 4,
+	/// The MD5 algorithm with the encoding value 5 (only for interoperability reasons, MD5 is not collision resistant).
+	MD5 = 5,
+	/// The SHA1 algorithm with the encoding value 6 (only for interoperability reasons, SHA1 is not collision resistant).
+	SHA1 = 6,
 }
 
 impl HashType {
@@ -34,8 +46,15 @@ impl HashType {
 			HashType::SHA512 => 512,
 			HashType::SHA3_256 => 256,
 			HashType::Blake3 => 256,
+			HashType::MD5 => 128,
+			HashType::SHA1 => 160,
 		}
 	}
+
+	/// returns true, if the appropriate algorithm is considered collision resistant (MD5 and SHA1 are not).
+	pub fn is_collision_resistant(&self) -> bool {
+		!matches!(self, HashType::MD5 | HashType::SHA1)
+	}
 }
 
 impl fmt::Display for HashType {
@@ -45,7 +64,9 @@ impl fmt::Display for HashType {
 			HashType::SHA256 => "SHA256",
 			HashType::SHA512 => "SHA512",
 			HashType::SHA3_256 => "Sha3_256",
-			HashType::Blake3 => "Blake3"
+			HashType::Blake3 => "Blake3",
+			HashType::MD5 => "MD5",
+			HashType::SHA1 => "SHA1",
 		};
 		write!(f, "{}", msg)
 	}
@@ -64,6 +85,8 @@ impl Hash {
 			HashType::SHA512 => Box::new(Sha512::new()),
 			HashType::SHA3_256 => Box::new(Sha3_256::new()),
 			HashType::Blake3 => Box::new(Blake3::new()),
+			HashType::MD5 => Box::new(Md5::new()),
+			HashType::SHA1 => Box::new(Sha1::new()),
 		}
 	}
 
@@ -71,4 +94,41 @@ impl Hash {
 	pub fn default_hashtype() -> HashType {
 		HashType::Blake3
 	}
-}
\ No newline at end of file
+}
+
+/// structure contains several methods to build a merkle tree (hash tree) over the chunks of an object.
+/// The leaves are the hashes of the (uncompressed and unencrypted) chunk data, leaves and inner nodes are hashed with different prefixes
+/// (see RFC 6962). If a level contains an odd number of nodes, the last node will be promoted to the next level.
+#[derive(Debug,Clone)]
+pub struct MerkleTree;
+
+impl MerkleTree {
+	/// returns the leaf hash of the given chunk data.
+	pub fn leaf_hash(hash_type: &HashType, data: &[u8]) -> Vec<u8> {
+		let mut hasher = Hash::new_hasher(hash_type);
+		hasher.update(&[MERKLE_TREE_LEAF_PREFIX]);
+		hasher.update(data);
+		hasher.finalize().to_vec()
+	}
+
+	/// returns the hash of the inner node with the given child nodes.
+	pub fn node_hash(hash_type: &HashType, left: &[u8], right: &[u8]) -> Vec<u8> {
+		let mut hasher = Hash::new_hasher(hash_type);
+		hasher.update(&[MERKLE_TREE_NODE_PREFIX]);
+		hasher.update(left);
+		hasher.update(right);
+		hasher.finalize().to_vec()
+	}
+
+	/// returns the root hash of the tree over the given (ordered) nodes or None, if no nodes are given.
+	pub fn root<N: AsRef<[u8]>>(hash_type: &HashType, nodes: &[N]) -> Option<Vec<u8>> {
+		let mut level: Vec<Vec<u8>> = nodes.iter().map(|node| node.as_ref().to_vec()).collect();
+		while level.len() > 1 {
+			level = level.chunks(2).map(|pair| match pair {
+				[left, right] => Self::node_hash(hash_type, left, right),
+				_ => pair[0].clone(),
+			}).collect();
+		}
+		level.pop()
+	}
+}
@@ -12,7 +12,8 @@ use crate::{
 	ValueDecoder,
 	HEADER_IDENTIFIER_CHUNK_HEADER,
 	ERROR_FLAG_VALUE,
-	COMPRESSION_FLAG_VALUE
+	COMPRESSION_FLAG_VALUE,
+	SPARSE_FLAG_VALUE,
 };
 
 /// Header for chunk data.\
@@ -25,6 +26,7 @@ pub struct ChunkHeader {
 	crc32: u32,
 	error_flag: bool,
 	compression_flag: bool,
+	sparse_flag: bool,
 	ed25519_signature: Option<[u8; SIGNATURE_LENGTH]>,
 }
 
@@ -38,6 +40,7 @@ impl ChunkHeader {
 			crc32: 0,
 			error_flag: false,
 			compression_flag: false,
+			sparse_flag: false,
 			ed25519_signature: None,
 		}
 	}
@@ -51,6 +54,7 @@ impl ChunkHeader {
 			crc32,
 			error_flag,
 			compression_flag,
+			sparse_flag: false,
 			ed25519_signature
 		}
 	}
@@ -92,6 +96,18 @@ impl ChunkHeader {
 		self.compression_flag
 	}
 
+	/// sets the sparse flag. A sparse chunk contains no data: the chunk size is the length of the appropriate hole
+	/// (the data of this chunk consists only of zeros). The sparse flag will only be encoded since header version 2
+	/// (older readers would interpret a sparse chunk as a corrupt data chunk).
+	pub fn set_sparse_flag(&mut self) {
+		self.sparse_flag = true;
+	}
+
+	/// returns the sparse flag value
+	pub fn sparse_flag(&self) -> bool {
+		self.sparse_flag
+	}
+
 	/// overwrites the signature in the header with the given value. This can be useful, if you create an 'empty'
 	/// header (with signature=None) and want to set the signature after reading the data from source to buffer.
 	/// Note: The Ed25519 signature per chunk is **optional**, so you have to set the signature as an ```Option<[u8; 64]>```.
@@ -134,6 +150,9 @@ impl HeaderCoding for ChunkHeader {
 		if self.compression_flag {
 			flags += COMPRESSION_FLAG_VALUE;
 		};
+		if self.version >= 2 && self.sparse_flag {
+			flags += SPARSE_FLAG_VALUE;
+		};
 		vec.append(&mut flags.encode_directly());
 		match self.ed25519_signature {
 			None => (),
@@ -152,6 +171,7 @@ impl HeaderCoding for ChunkHeader {
 		let flags = u8::decode_directly(&mut cursor)?;
 		let compression_flag = flags & COMPRESSION_FLAG_VALUE != 0;
 		let error_flag = flags & ERROR_FLAG_VALUE != 0;
+		let sparse_flag = version >= 2 && flags & SPARSE_FLAG_VALUE != 0;
 		let mut ed25519_signature = None;
 		if cursor.position() < (data.len() as u64 - 1) {
 			let mut buffer = [0; SIGNATURE_LENGTH];
@@ -159,6 +179,8 @@ impl HeaderCoding for ChunkHeader {
 			ed25519_signature = Some(buffer);
 		}
 
-		Ok(ChunkHeader::new(version, chunk_number, chunk_size, crc32, error_flag, compression_flag, ed25519_signature))
+		let mut chunk_header = ChunkHeader::new(version, chunk_number, chunk_size, crc32, error_flag, compression_flag, ed25519_signature);
+		chunk_header.sparse_flag = sparse_flag;
+		Ok(chunk_header)
 	}
 }
\ No newline at end of file
@@ -17,13 +17,16 @@ use crate::version1::{
 };
 
 /// Header for the data compression parameters.\
-/// This header is part of the main header.
+/// This header is part of the main header.\
+/// Since header version 2, the header can contain an (optional) trained zstd dictionary, which will be used to compress and decompress every chunk
+/// of the appropriate object (see [train_zstd_dictionary](crate::train_zstd_dictionary)).
 #[derive(Debug,Clone)]
 pub struct CompressionHeader {
 	version: u8,
 	algorithm: CompressionAlgorithm,
 	level: u8,
 	threshold: f32,
+	dictionary: Option<Vec<u8>>,
 }
 
 impl CompressionHeader {
@@ -34,9 +37,25 @@ impl CompressionHeader {
 			algorithm: compression_algo,
 			level,
 			threshold,
+			dictionary: None,
 		}
 	}
 
+	/// sets the zstd dictionary, which should be used to compress (and decompress) the chunks.
+	/// The dictionary will only be used, if the compression algorithm is [CompressionAlgorithm::Zstd].
+	/// The header version will be set to 2, if the current header version is lower (the dictionary can not be stored in version 1 headers).
+	pub fn set_dictionary(&mut self, dictionary: Vec<u8>) {
+		if self.version < 2 {
+			self.version = 2;
+		}
+		self.dictionary = Some(dictionary);
+	}
+
+	/// returns the zstd dictionary, if available.
+	pub fn dictionary(&self) -> Option<&[u8]> {
+		self.dictionary.as_deref()
+	}
+
 	/// Returns the compression algorithm. The appropriate algorithms/values
 	/// could be found at [CompressionAlgorithm](enum.CompressionAlgorithm.html).
 	pub fn algorithm(&self) -> &CompressionAlgorithm {
@@ -48,6 +67,12 @@ impl CompressionHeader {
 		&self.level
 	}
 
+	/// returns the compression level, interpreted as i8 (two's complement).
+	/// This is used by the zstd compression to support negative (fast) compression levels (e.g. a level of 251 means -5).
+	pub fn signed_level(&self) -> i8 {
+		self.level as i8
+	}
+
 	/// returns the compression threshold
 	pub fn threshold(&self) -> f32 {
 		self.threshold
@@ -68,6 +93,11 @@ impl HeaderCoding for CompressionHeader {
 	fn encode_header(&self) -> Vec<u8> {
 		let mut vec = vec![self.version, self.algorithm.clone() as u8, self.level];
 		vec.append(&mut self.threshold.encode_directly());
+		if self.version >= 2 {
+			// an empty dictionary is encoded, if no dictionary is set.
+			let dictionary = self.dictionary.clone().unwrap_or_default();
+			vec.append(&mut dictionary.encode_directly());
+		}
 		
 		vec
 	}
@@ -79,10 +109,19 @@ impl HeaderCoding for CompressionHeader {
 			Ok(0) => CompressionAlgorithm::None,
 			Ok(1) => CompressionAlgorithm::Zstd,
 			Ok(2) => CompressionAlgorithm::Lz4,
+			Ok(3) => CompressionAlgorithm::Xz,
+			Ok(4) => CompressionAlgorithm::Brotli,
 			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_COMPRESSION_ALGORITHM))
 		};
 		let level = u8::decode_directly(&mut cursor)?;
 		let threshold = f32::decode_directly(&mut cursor)?;
-		Ok(CompressionHeader::new(version, algorithm, level, threshold))
+		let mut compression_header = CompressionHeader::new(version, algorithm, level, threshold);
+		if version >= 2 {
+			let dictionary = Vec::<u8>::decode_directly(&mut cursor)?;
+			if !dictionary.is_empty() {
+				compression_header.set_dictionary(dictionary);
+			}
+		}
+		Ok(compression_header)
 	}
 }
\ No newline at end of file
@@ -25,6 +25,9 @@ use crate::header::{
 	EncryptionHeader,
 };
 
+// - external
+use time::{OffsetDateTime};
+
 /// Defines all file types, which are implemented for zff files.
 #[repr(u8)]
 #[non_exhaustive]
@@ -38,6 +41,27 @@ pub enum FileType {
 	Symlink = 3,
 	/// Represents a hard link (mostly used at unix like operating systems).
 	Hardlink = 4,
+	/// Represents a named pipe (FIFO).
+	Fifo = 5,
+	/// Represents a unix domain socket.
+	Socket = 6,
+	/// Represents a block device (the major and minor numbers are stored in the [FileHeader]).
+	BlockDevice = 7,
+	/// Represents a character device (the major and minor numbers are stored in the [FileHeader]).
+	CharDevice = 8,
+}
+
+impl FileType {
+	/// returns true, if the file type is a special file (a fifo, socket, block device or character device).
+	/// The content of special files will never be read or stored.
+	pub fn is_special_file(&self) -> bool {
+		matches!(self, FileType::Fifo | FileType::Socket | FileType::BlockDevice | FileType::CharDevice)
+	}
+
+	/// returns true, if the file type is a block device or a character device.
+	pub fn is_device(&self) -> bool {
+		matches!(self, FileType::BlockDevice | FileType::CharDevice)
+	}
 }
 
 impl fmt::Display for FileType {
@@ -47,6 +71,10 @@ impl fmt::Display for FileType {
 			FileType::Directory => "Directory",
 			FileType::Symlink => "Symlink",
 			FileType::Hardlink => "Hardlink",
+			FileType::Fifo => "Fifo",
+			FileType::Socket => "Socket",
+			FileType::BlockDevice => "BlockDevice",
+			FileType::CharDevice => "CharDevice",
 		};
 		write!(f, "{}", msg)
 	}
@@ -58,8 +86,11 @@ impl fmt::Display for FileType {
 /// - the [FileType] of the appropriate file.
 /// - the original filename of the appropriate file **without** the full path (just the filename, e.g. "my_texfile.txt" or "my_directory")
 /// - the file number of the parent directory of this file (if the file lies into the root directory, this is 0 because the first valid file number in zff is 1).
-/// - the atime, mtime, ctime and btime.
+/// - the atime, mtime, ctime and btime (since header version 3 with signed seconds, nanoseconds and an explicit marker for unavailable timestamps).
 /// - A HashMap to extend the metadata based on the operating system/filesystem. Some fields are predefined, see [the full list in the wiki](https://github.com/ph0llux/zff/wiki/zff-header-layout#file-metadata-extended-information)
+/// - the extended attributes of the file (e.g. "user.\*", "security.selinux", "security.capability" or the POSIX ACLs "system.posix_acl_access"),
+///   stored binary-safe as name-value pairs (since header version 2).
+/// - the major and minor device numbers (only for block devices and character devices, since header version 4).
 #[derive(Debug,Clone,Eq,PartialEq)]
 pub struct FileHeader {
 	version: u8,
@@ -67,26 +98,44 @@ pub struct FileHeader {
 	file_type: FileType,
 	filename: String,
 	parent_file_number: u64,
-	atime: u64,
-	mtime: u64,
-	ctime: u64,
-	btime: u64,
+	atime: Option<OffsetDateTime>,
+	mtime: Option<OffsetDateTime>,
+	ctime: Option<OffsetDateTime>,
+	btime: Option<OffsetDateTime>,
 	metadata_ext: HashMap<String, String>,
+	xattrs: HashMap<Vec<u8>, Vec<u8>>,
+	device_number: Option<(u32, u32)>, //major, minor
 }
 
+// the decoded inner content of a [FileHeader].
+type FileHeaderInnerContent = (
+	FileType,
+	String, //Filename
+	u64, //parent_file_number
+	Option<OffsetDateTime>, //atime,
+	Option<OffsetDateTime>, //mtime
+	Option<OffsetDateTime>, //ctime,
+	Option<OffsetDateTime>, //btime,
+	HashMap<String, String>,
+	HashMap<Vec<u8>, Vec<u8>>, //xattrs
+	Option<(u32, u32)>, //device number
+	);
+
 impl FileHeader {
 	/// creates a new [FileHeader] with the given values.
+	#[allow(clippy::too_many_arguments)]
 	pub fn new<F: Into<String>>(
 		version: u8,
 		file_number: u64,
 		file_type: FileType,
 		filename: F,
 		parent_file_number: u64,
-		atime: u64,
-		mtime: u64,
-		ctime: u64,
-		btime: u64,
-		metadata_ext: HashMap<String, String>) -> FileHeader {
+		atime: Option<OffsetDateTime>,
+		mtime: Option<OffsetDateTime>,
+		ctime: Option<OffsetDateTime>,
+		btime: Option<OffsetDateTime>,
+		metadata_ext: HashMap<String, String>,
+		xattrs: HashMap<Vec<u8>, Vec<u8>>) -> FileHeader {
 		Self {
 			version,
 			file_number,
@@ -97,7 +146,9 @@ impl FileHeader {
 			mtime,
 			ctime,
 			btime,
-			metadata_ext
+			metadata_ext,
+			xattrs,
+			device_number: None,
 		}
 	}
 	/// returns the file number
@@ -116,26 +167,53 @@ impl FileHeader {
 	pub fn parent_file_number(&self) -> u64 {
 		self.parent_file_number
 	}
-	/// returns the atime
-	pub fn atime(&self) -> u64 {
+	/// returns the atime (None, if the timestamp is unavailable)
+	pub fn atime(&self) -> Option<OffsetDateTime> {
 		self.atime
 	}
-	/// returns the mtime 
-	pub fn mtime(&self) -> u64 {
+	/// returns the mtime (None, if the timestamp is unavailable)
+	pub fn mtime(&self) -> Option<OffsetDateTime> {
 		self.mtime
 	}
-	/// returns the ctime
-	pub fn ctime(&self) -> u64 {
+	/// returns the ctime (None, if the timestamp is unavailable)
+	pub fn ctime(&self) -> Option<OffsetDateTime> {
 		self.ctime
 	}
-	/// returns the btime
-	pub fn btime(&self) -> u64 {
+	/// returns the btime (None, if the timestamp is unavailable)
+	pub fn btime(&self) -> Option<OffsetDateTime> {
 		self.btime
 	}
 	/// returns the extended metadata [HashMap] as a reference.
 	pub fn metadata_ext(&self) -> &HashMap<String, String> {
 		&self.metadata_ext
 	}
+	/// returns the extended attributes (name, value) of the file.
+	pub fn xattrs(&self) -> &HashMap<Vec<u8>, Vec<u8>> {
+		&self.xattrs
+	}
+	/// returns the value of the extended attribute with the given name, if available.
+	pub fn xattr<N: AsRef<[u8]>>(&self, name: N) -> Option<&[u8]> {
+		self.xattrs.get(name.as_ref()).map(|value| value.as_slice())
+	}
+
+	/// returns the major and minor device numbers, if this is a block device or a character device.
+	pub fn device_number(&self) -> Option<(u32, u32)> {
+		self.device_number
+	}
+	/// returns the major device number, if this is a block device or a character device.
+	pub fn major(&self) -> Option<u32> {
+		self.device_number.map(|(major, _)| major)
+	}
+	/// returns the minor device number, if this is a block device or a character device.
+	pub fn minor(&self) -> Option<u32> {
+		self.device_number.map(|(_, minor)| minor)
+	}
+	/// sets the major and minor device numbers. This does only work with a [FileType::BlockDevice] or [FileType::CharDevice]!
+	pub fn set_device_number(&mut self, major: u32, minor: u32) {
+		if self.file_type.is_device() {
+			self.device_number = Some((major, minor));
+		}
+	}
 
 	/// transforms the inner [FileType] to a [FileType::Hardlink]. This does not work with a [FileType::Symlink]!
 	pub fn transform_to_hardlink(&mut self) {
@@ -187,11 +265,18 @@ impl FileHeader {
 		vec.append(&mut (self.file_type.clone() as u8).encode_directly());
 		vec.append(&mut self.filename().encode_directly());
 		vec.append(&mut self.parent_file_number.encode_directly());
-		vec.append(&mut self.atime.encode_directly());
-		vec.append(&mut self.mtime.encode_directly());
-		vec.append(&mut self.ctime.encode_directly());
-		vec.append(&mut self.btime.encode_directly());
+		for timestamp in [self.atime, self.mtime, self.ctime, self.btime] {
+			vec.append(&mut encode_timestamp(timestamp, self.version));
+		}
 		vec.append(&mut self.metadata_ext.encode_directly());
+		if self.version >= 2 {
+			vec.append(&mut self.xattrs.encode_directly());
+		}
+		if self.version >= 4 && self.file_type.is_device() {
+			let (major, minor) = self.device_number.unwrap_or((0, 0));
+			vec.append(&mut major.encode_directly());
+			vec.append(&mut minor.encode_directly());
+		}
 		vec
 	}
 
@@ -224,8 +309,10 @@ impl FileHeader {
 			mtime,
 			ctime,
 			btime,
-			metadata_ext) = Self::decode_inner_content(&mut cursor)?;
-		let file_header = Self::new(
+			metadata_ext,
+			xattrs,
+			device_number) = Self::decode_inner_content(&mut cursor, header_version)?;
+		let mut file_header = Self::new(
 			header_version,
 			file_number,
 			file_type,
@@ -235,34 +322,43 @@ impl FileHeader {
 			mtime,
 			ctime,
 			btime,
-			metadata_ext);
+			metadata_ext,
+			xattrs);
+		file_header.device_number = device_number;
 		Ok(file_header)
 	}
 
-	fn decode_inner_content<R: Read>(inner_content: &mut R) -> Result<(
-		FileType,
-		String, //Filename
-		u64, //parent_file_number
-		u64, //atime,
-		u64, //mtime
-		u64, //ctime,
-		u64, //btime,
-		HashMap<String, String>,
-		)> {
+	fn decode_inner_content<R: Read>(inner_content: &mut R, header_version: u8) -> Result<FileHeaderInnerContent> {
 		let file_type = match u8::decode_directly(inner_content)? {
 			1 => FileType::File,
 			2 => FileType::Directory,
 			3 => FileType::Symlink,
 			4 => FileType::Hardlink,
+			5 => FileType::Fifo,
+			6 => FileType::Socket,
+			7 => FileType::BlockDevice,
+			8 => FileType::CharDevice,
 			val => return Err(ZffError::new(ZffErrorKind::UnknownFileType, val.to_string()))
 		};
 		let filename = String::decode_directly(inner_content)?;
 		let parent_file_number = u64::decode_directly(inner_content)?;
-		let atime = u64::decode_directly(inner_content)?;
-		let mtime = u64::decode_directly(inner_content)?;
-		let ctime = u64::decode_directly(inner_content)?;
-		let btime = u64::decode_directly(inner_content)?;
+		let atime = decode_timestamp(inner_content, header_version)?;
+		let mtime = decode_timestamp(inner_content, header_version)?;
+		let ctime = decode_timestamp(inner_content, header_version)?;
+		let btime = decode_timestamp(inner_content, header_version)?;
 		let metadata_ext = HashMap::<String, String>::decode_directly(inner_content)?;
+		let xattrs = if header_version >= 2 {
+			HashMap::<Vec<u8>, Vec<u8>>::decode_directly(inner_content)?
+		} else {
+			HashMap::new()
+		};
+		let device_number = if header_version >= 4 && file_type.is_device() {
+			let major = u32::decode_directly(inner_content)?;
+			let minor = u32::decode_directly(inner_content)?;
+			Some((major, minor))
+		} else {
+			None
+		};
 
 		let inner_content = (
 			file_type,
@@ -272,7 +368,9 @@ impl FileHeader {
 			mtime,
 			ctime,
 			btime,
-			metadata_ext);
+			metadata_ext,
+			xattrs,
+			device_number);
 		Ok(inner_content)
 	}
 }
@@ -301,7 +399,56 @@ impl HeaderCoding for FileHeader {
 		let mut cursor = Cursor::new(data);
 		let header_version = u8::decode_directly(&mut cursor)?;
 		let file_number = u64::decode_directly(&mut cursor)?;
-		let (file_type, filename, parent_file_number, atime, mtime, ctime, btime, metadata_ext) = Self::decode_inner_content(&mut cursor)?;
-		Ok(FileHeader::new(header_version, file_number, file_type, filename, parent_file_number, atime, mtime, ctime, btime, metadata_ext))
+		let (file_type, filename, parent_file_number, atime, mtime, ctime, btime, metadata_ext, xattrs, device_number) = Self::decode_inner_content(&mut cursor, header_version)?;
+		let mut file_header = FileHeader::new(header_version, file_number, file_type, filename, parent_file_number, atime, mtime, ctime, btime, metadata_ext, xattrs);
+		file_header.device_number = device_number;
+		Ok(file_header)
+	}
+}
+
+// encodes the timestamp. Since header version 3, the timestamp is encoded as availability flag, signed seconds and nanoseconds.
+// Older header versions only contain the unsigned seconds (0, if the timestamp is unavailable). Timestamps before 1970 could not be
+// represented by these versions and will be clamped to 0 (and therefore decoded as unavailable).
+fn encode_timestamp(timestamp: Option<OffsetDateTime>, header_version: u8) -> Vec<u8> {
+	let mut vec = Vec::new();
+	if header_version < 3 {
+		let seconds = timestamp.map(|timestamp| timestamp.unix_timestamp().max(0) as u64).unwrap_or(0);
+		vec.append(&mut seconds.encode_directly());
+		return vec;
 	}
-}
\ No newline at end of file
+	match timestamp {
+		Some(timestamp) => {
+			vec.append(&mut 1u8.encode_directly());
+			vec.append(&mut timestamp.unix_timestamp().encode_directly());
+			vec.append(&mut timestamp.nanosecond().encode_directly());
+		},
+		None => {
+			vec.append(&mut 0u8.encode_directly());
+			vec.append(&mut 0i64.encode_directly());
+			vec.append(&mut 0u32.encode_directly());
+		},
+	}
+	vec
+}
+
+// decodes the timestamp (see encode_timestamp). Header versions < 3 used 0 for unavailable timestamps, so 0 will be decoded as None.
+fn decode_timestamp<R: Read>(data: &mut R, header_version: u8) -> Result<Option<OffsetDateTime>> {
+	if header_version < 3 {
+		let seconds = u64::decode_directly(data)?;
+		if seconds == 0 {
+			return Ok(None);
+		}
+		return Ok(OffsetDateTime::from_unix_timestamp(seconds as i64).ok());
+	}
+	let available = u8::decode_directly(data)? != 0;
+	let seconds = i64::decode_directly(data)?;
+	let nanoseconds = u32::decode_directly(data)?;
+	if !available {
+		return Ok(None);
+	}
+	let timestamp = seconds as i128 * 1_000_000_000 + nanoseconds as i128;
+	match OffsetDateTime::from_unix_timestamp_nanos(timestamp) {
+		Ok(timestamp) => Ok(Some(timestamp)),
+		Err(e) => Err(ZffError::new(ZffErrorKind::HeaderDecodeError, e.to_string())),
+	}
+}
@@ -157,6 +157,8 @@ impl HeaderCoding for HashValue {
 			2 => HashType::SHA512,
 			3 => HashType::SHA3_256,
 			4 => HashType::Blake3,
+			5 => HashType::MD5,
+			6 => HashType::SHA1,
 			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_HASH_TYPE)),
 		};
 	 	let hash = Vec::<u8>::decode_directly(&mut cursor)?;
@@ -100,6 +100,11 @@ impl ObjectHeader {
 		self.compression_header.clone()
 	}
 
+	/// sets the [crate::header::CompressionHeader]
+	pub fn set_compression_header(&mut self, compression_header: CompressionHeader) {
+		self.compression_header = compression_header
+	}
+
 	/// returns, if the chunks has a ed25519 signature or not.
 	pub fn has_per_chunk_signatures(&self) -> bool {
 		matches!(&self.signature_flag, SignatureFlag::PerChunkSignatures)
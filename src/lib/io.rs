@@ -1,5 +1,5 @@
 // - STD
-use std::io::{Read, copy as io_copy};
+use std::io::{Read};
 
 // - internal
 use crate::{
@@ -7,7 +7,12 @@ use crate::{
     ZffError,
     ZffErrorKind,
     CompressionAlgorithm,
+    Compression,
+    Compressor,
     header::{CompressionHeader},
+    DEFAULT_ADAPTIVE_COMPRESSION_ENTROPY_THRESHOLD,
+    ENTROPY_PROBE_SIZE,
+    ENTROPY_PROBE_NUMBER_OF_SAMPLES,
 
 };
 
@@ -55,36 +60,68 @@ pub fn calculate_crc32(buffer: &[u8]) -> u32 {
     crc32_hasher.finalize()
 }
 
+/// estimates the shannon entropy (in bits per byte) of the given buffer. For buffers, which are greater than 4096 bytes,
+/// the entropy will be estimated by evenly distributed samples of the buffer (so the whole buffer has not to be read).
+pub fn estimate_entropy(buf: &[u8]) -> f32 {
+    let mut byte_counts = [0u64; 256];
+    let mut number_of_bytes = 0;
+    if buf.len() <= ENTROPY_PROBE_SIZE {
+        for byte in buf {
+            byte_counts[*byte as usize] += 1;
+        }
+        number_of_bytes = buf.len();
+    } else {
+        let sample_size = ENTROPY_PROBE_SIZE / ENTROPY_PROBE_NUMBER_OF_SAMPLES;
+        let step = (buf.len() - sample_size) / (ENTROPY_PROBE_NUMBER_OF_SAMPLES - 1);
+        for sample_number in 0..ENTROPY_PROBE_NUMBER_OF_SAMPLES {
+            let offset = sample_number * step;
+            for byte in &buf[offset..offset+sample_size] {
+                byte_counts[*byte as usize] += 1;
+            }
+            number_of_bytes += sample_size;
+        }
+    }
+    if number_of_bytes == 0 {
+        return 0.0;
+    }
+    let mut entropy = 0.0;
+    for count in byte_counts.iter().filter(|count| **count > 0) {
+        let probability = *count as f32 / number_of_bytes as f32;
+        entropy -= probability * probability.log2();
+    }
+    entropy
+}
+
 /// This function takes the buffered bytes and tries to compress them. If the compression rate is greater than the threshold value of the given
 /// [CompressionHeader], the function returns a tuple of compressed bytes and the flag, if the bytes was compressed or not.
+/// This function initializes a new [Compressor] for each call, you should prefer [compress_chunk] to compress multiple chunks.
 pub fn compress_buffer(buf: Vec<u8>, chunk_size: usize, compression_header: &CompressionHeader) -> Result<(Vec<u8>, bool)> {
-    let mut compression_flag = false;
-    let compression_threshold = compression_header.threshold();
+    let mut compressor = Compression::new_compressor(compression_header, chunk_size)?;
+    compress_chunk(compressor.as_mut(), buf, compression_header.threshold())
+}
 
-    match compression_header.algorithm() {
-        CompressionAlgorithm::None => Ok((buf, compression_flag)),
-        CompressionAlgorithm::Zstd => {
-            let compression_level = *compression_header.level() as i32;
-            let mut stream = zstd::stream::read::Encoder::new(buf.as_slice(), compression_level)?;
-            let (compressed_data, _) = buffer_chunk(&mut stream, chunk_size * *compression_header.level() as usize)?;
-            if (buf.len() as f32 / compressed_data.len() as f32) < compression_threshold {
-                Ok((buf, compression_flag))
-            } else {
-                compression_flag = true;
-                Ok((compressed_data, compression_flag))
-            }
-        },
-        CompressionAlgorithm::Lz4 => {
-            let buffer = Vec::new();
-            let mut compressor = lz4_flex::frame::FrameEncoder::new(buffer);
-            io_copy(&mut buf.as_slice(), &mut compressor)?;
-            let compressed_data = compressor.finish()?;
-            if (buf.len() as f32 / compressed_data.len() as f32) < compression_threshold {
-                Ok((buf, compression_flag))
-            } else {
-                compression_flag = true;
-                Ok((compressed_data, compression_flag))
-            }
-        }
+/// compresses the buffered bytes with the given [Compressor]. If the compression rate is lower than the given threshold,
+/// the function returns the uncompressed bytes. Returns a tuple of the (compressed) bytes and the flag, if the bytes was compressed or not.
+pub fn compress_chunk(compressor: &mut dyn Compressor, buf: Vec<u8>, compression_threshold: f32) -> Result<(Vec<u8>, bool)> {
+    if compressor.algorithm() == CompressionAlgorithm::None || buf.is_empty() {
+        return Ok((buf, false));
+    }
+    let compressed_data = compressor.compress(&buf)?;
+    if (buf.len() as f32 / compressed_data.len() as f32) < compression_threshold {
+        Ok((buf, false))
+    } else {
+        Ok((compressed_data, true))
     }
-}
\ No newline at end of file
+}
+
+/// works like [compress_chunk], but estimates the entropy of the buffer first (see [estimate_entropy]).
+/// If the estimated entropy is greater than or equal to [DEFAULT_ADAPTIVE_COMPRESSION_ENTROPY_THRESHOLD], the data is likely
+/// incompressible (e.g. encrypted volumes or already compressed media files) and the compression will be skipped.
+/// Returns the (compressed) bytes, the compression flag and true, if the compression was skipped.
+pub fn compress_chunk_adaptive(compressor: &mut dyn Compressor, buf: Vec<u8>, compression_threshold: f32) -> Result<(Vec<u8>, bool, bool)> {
+    if compressor.algorithm() != CompressionAlgorithm::None && estimate_entropy(&buf) >= DEFAULT_ADAPTIVE_COMPRESSION_ENTROPY_THRESHOLD {
+        return Ok((buf, false, true));
+    }
+    let (compressed_data, compression_flag) = compress_chunk(compressor, buf, compression_threshold)?;
+    Ok((compressed_data, compression_flag, false))
+}
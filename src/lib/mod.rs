@@ -16,6 +16,7 @@ pub mod header;
 /// This module contains all footer, could be found in the zff specification (footer version 1 and footer version 2).
 pub mod footer;
 mod hashing;
+mod fuzzy_hashing;
 mod compression;
 mod encryption;
 mod traits;
@@ -26,6 +27,7 @@ mod io;
 
 // - re-exports
 pub use hashing::*;
+pub use fuzzy_hashing::*;
 pub use compression::*;
 pub use encryption::*;
 pub use error::*;
@@ -227,7 +227,8 @@ impl<R: Read> ZffWriter<R> {
 	    			compression_flag = true;
 	    			Ok((compressed_data, compression_flag))
 	    		}
-	    	}
+	    	},
+	    	_ => crate::compress_buffer(buf, chunk_size, self.main_header.compression_header()),
 	    }
 	}
 
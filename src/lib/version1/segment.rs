@@ -12,6 +12,7 @@ use crate::{
 	ZffError,
 	ZffErrorKind,
 	CompressionAlgorithm,
+	decompress_buffer,
 	Encryption,
 	EncryptionAlgorithm,
 	Signature,
@@ -103,7 +104,8 @@ impl<R: 'static +  Read + Seek> Segment<R> {
 				let mut decompressor = lz4_flex::frame::FrameDecoder::new(chunk_data.as_slice());
 				decompressor.read_to_end(&mut buffer)?;
 				Ok(buffer)
-			}
+			},
+			compression_algorithm => decompress_buffer(&chunk_data, compression_algorithm),
 		}
 	}
 
@@ -152,7 +154,8 @@ impl<R: 'static +  Read + Seek> Segment<R> {
 				let mut decompressor = lz4_flex::frame::FrameDecoder::new(decrypted_chunk_data.as_slice());
 				decompressor.read_to_end(&mut decompressed_buffer)?;
 				Ok(decompressed_buffer)
-			}
+			},
+			compression_algorithm => decompress_buffer(&decrypted_chunk_data, compression_algorithm),
 		}
 	}
 
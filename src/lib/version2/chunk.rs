@@ -26,6 +26,10 @@ impl Chunk {
 	/// Returns a new [Chunk], read from the given [Reader](std::io::Read).
 	pub fn new_from_reader<R: Read>(data: &mut R) -> Result<Chunk> {
 		let chunk_header = ChunkHeader::decode_directly(data)?;
+		// sparse chunks contain no data.
+		if chunk_header.sparse_flag() {
+			return Ok(Self::new(chunk_header, Vec::new()));
+		}
 		let mut chunk_data = Vec::with_capacity(*chunk_header.chunk_size() as usize);
 		data.read_exact(& mut chunk_data)?;
 		Ok(Self::new(chunk_header, chunk_data))
@@ -2,23 +2,30 @@
 use std::io::{Read, Seek, SeekFrom, Cursor};
 use std::path::PathBuf;
 use std::fs::{File};
-use std::collections::{HashMap};
+use std::collections::{HashMap, VecDeque};
 use std::time::{SystemTime};
 
 // - internal
 use crate::{
 	header::{FileHeader, FileType, MainHeader, ChunkHeader, HashValue, HashHeader, CompressionHeader, EncryptionHeader},
-	footer::{FileFooter},
+	footer::{FileFooter, DataStream, FuzzyHashValue},
+	VirtualFile,
 };
 use crate::{
 	Result,
 	buffer_chunk,
 	calculate_crc32,
-	compress_buffer,
+	compress_chunk,
+	compress_chunk_adaptive,
+	Compression,
+	Compressor,
 	HeaderCoding,
 	ValueEncoder,
 	HashType,
 	Hash,
+	FuzzyHashType,
+	FuzzyHash,
+	FuzzyHasher,
 	Signature,
 	Encryption,
 	ZffError,
@@ -34,14 +41,99 @@ use digest::DynDigest;
 use ed25519_dalek::{Keypair};
 use time::{OffsetDateTime};
 
+/// The content of a file, which will be encoded by the [FileEncoder].
+pub enum FileContent {
+	/// A file of the local filesystem.
+	File(File),
+	/// A [VirtualFile] (e.g. a file extracted from a disk image).
+	Virtual(Box<dyn VirtualFile>),
+}
+
+impl Read for FileContent {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		match self {
+			FileContent::File(file) => file.read(buf),
+			FileContent::Virtual(file) => file.read(buf),
+		}
+	}
+}
+
+impl From<File> for FileContent {
+	fn from(file: File) -> FileContent {
+		FileContent::File(file)
+	}
+}
+
+// An already encoded regular file, which can be referenced by following files with identical content.
+#[derive(Debug, Clone)]
+pub(crate) struct DeduplicationCandidate {
+	file_number: u64,
+	hash: Vec<u8>,
+	first_chunk_hash: Vec<u8>,
+	first_chunk_number: u64,
+	number_of_chunks: u64,
+	length_of_data: u64,
+}
+
+impl DeduplicationCandidate {
+	// returns the candidate for the given (not deduplicated) file footer, if the footer contains a hash value of the given hash type.
+	// The hash value of the first chunk has to be calculated with the same hash type (see FileEncoder::first_chunk_hash).
+	pub(crate) fn from_footer(file_number: u64, footer: &FileFooter, hash_type: &HashType, first_chunk_hash: &[u8]) -> Option<DeduplicationCandidate> {
+		if footer.is_deduplicated() || footer.length_of_data() == 0 {
+			return None;
+		}
+		let hash = footer.hash_header().hash_values().iter().find(|hash_value| hash_value.hash_type() == hash_type)?.hash().clone();
+		Some(Self {
+			file_number,
+			hash,
+			first_chunk_hash: first_chunk_hash.to_vec(),
+			first_chunk_number: footer.first_chunk_number(),
+			number_of_chunks: footer.number_of_chunks(),
+			length_of_data: footer.length_of_data(),
+		})
+	}
+
+	// returns the length of the file content.
+	pub(crate) fn length_of_data(&self) -> u64 {
+		self.length_of_data
+	}
+}
+
+// The encoder state of the current named data stream.
+struct DataStreamEncoder {
+	name: String,
+	reader: Box<dyn Read>,
+	first_chunk_number: u64,
+	read_bytes: u64,
+	hasher_map: HashMap<HashType, Box<dyn DynDigest>>,
+}
+
+impl DataStreamEncoder {
+	fn new<'a, H: Iterator<Item = &'a HashType>>(name: String, reader: Box<dyn Read>, first_chunk_number: u64, hash_types: H) -> DataStreamEncoder {
+		Self {
+			name,
+			reader,
+			first_chunk_number,
+			read_bytes: 0,
+			hasher_map: hash_types.map(|hash_type| (hash_type.clone(), Hash::new_hasher(hash_type))).collect(),
+		}
+	}
+
+	// finalizes the hashers and returns the appropriate [DataStream].
+	fn finalize(self, current_chunk_number: u64) -> DataStream {
+		let hash_header = HashHeader::new(DEFAULT_HEADER_VERSION_HASH_HEADER, finalize_hashers(self.hasher_map));
+		DataStream::new(self.name, self.first_chunk_number, current_chunk_number - self.first_chunk_number, self.read_bytes, hash_header)
+	}
+}
+
 /// The [FileEncoder] can be used to encode a [crate::file::File].
 pub struct FileEncoder {
 	/// An encoded [FileHeader].
 	encoded_header: Vec<u8>,
 	/// remaining bytes of the encoded header to read. This is only (internally) used, if you will use the [Read] implementation of [FileEncoder].
 	encoded_header_remaining_bytes: usize,
-	/// The underlying [File](std::fs::File) object to read from.
-	underlying_file: File,
+	/// The underlying [FileContent] to read from (None for special files like fifos, sockets or devices, which will never be read).
+	underlying_file: Option<FileContent>,
 	/// optinal signature key, to sign the data with the given keypair
 	signature_key: Option<Keypair>,
 	/// optinal encryption key, to encrypt the data with the given key
@@ -71,13 +163,45 @@ pub struct FileEncoder {
 	acquisition_end: u64,
 	hard_link_filenumber: Option<u64>,
 	encoded_directory_children: Vec<u8>,
+	/// The length of the underlying file (only used for sparse files).
+	underlying_file_length: u64,
+	/// The holes (offset, length) of the underlying file, which were detected when the file was opened (sorted by the offset).
+	holes: Vec<(u64, u64)>,
+	/// The index of the first hole in the holes, which ends after the current position.
+	current_hole_index: usize,
+	/// The holes (offset, length), which were actually stored as sparse chunks (these holes will be written into the footer).
+	sparse_holes: Vec<(u64, u64)>,
+	/// The reusable buffer, into which the (likely zeroed) chunks in holes will be read.
+	hole_buffer: Vec<u8>,
+	/// The file, whose chunks will be used by this file (only used for deduplicated files).
+	deduplicated_file: Option<DeduplicationCandidate>,
+	/// The hash type, which will be used for the deduplication (only set for regular files, if the deduplication is enabled).
+	deduplication_hash_type: Option<HashType>,
+	/// The hash value of the first chunk of the main data (only calculated, if the deduplication hash type is set).
+	first_chunk_hash: Option<Vec<u8>>,
+	/// The named data streams (name, reader), which will be encoded after the main data of the file.
+	data_streams: VecDeque<(String, Box<dyn Read>)>,
+	/// The current named data stream.
+	current_data_stream: Option<DataStreamEncoder>,
+	/// The already encoded named data streams.
+	encoded_data_streams: Vec<DataStream>,
+	/// The number of chunks of the main data (set, after the main data was read completely).
+	number_of_data_chunks: Option<u64>,
+	/// Skips the compression of likely incompressible chunks, if enabled.
+	adaptive_compression: bool,
+	/// The number of chunks, whose compression was skipped by the adaptive compression.
+	skipped_compression_chunks: u64,
+	/// The compressor, which will be (re)used to compress the chunks (initialized by the first chunk, if not set).
+	compressor: Option<Box<dyn Compressor>>,
+	/// The fuzzy hashers to calculate the similarity hashes of the file content (only used for regular files).
+	fuzzy_hashers: Vec<Box<dyn FuzzyHasher>>,
 }
 
 impl FileEncoder {
 	/// creates a new [FileEncoder] with the given values.
 	pub fn new(
 		file_header: FileHeader,
-		file: File,
+		mut file: Option<FileContent>,
 		hash_types: Vec<HashType>,
 		encryption_key: Option<Vec<u8>>,
 		signature_key: Option<Keypair>,
@@ -89,6 +213,10 @@ impl FileEncoder {
 		header_encryption: bool,
 		hard_link_filenumber: Option<u64>,
 		directory_children: Vec<u64>) -> Result<FileEncoder> {
+		let (underlying_file_length, holes) = match &mut file {
+			Some(FileContent::File(file)) if file_header.file_type() == FileType::File => (file.metadata()?.len(), get_holes(file)?),
+			_ => (0, Vec::new()),
+		};
 		
 		let encoded_header = if header_encryption {
 			if let Some(ref encryption_key) = encryption_key {
@@ -135,13 +263,156 @@ impl FileEncoder {
 			acquisition_end: 0,
 			hard_link_filenumber,
 			encoded_directory_children,
+			underlying_file_length,
+			holes,
+			current_hole_index: 0,
+			sparse_holes: Vec::new(),
+			hole_buffer: Vec::new(),
+			deduplicated_file: None,
+			deduplication_hash_type: None,
+			first_chunk_hash: None,
+			data_streams: VecDeque::new(),
+			current_data_stream: None,
+			encoded_data_streams: Vec::new(),
+			number_of_data_chunks: None,
+			adaptive_compression: false,
+			skipped_compression_chunks: 0,
+			compressor: None,
+			fuzzy_hashers: Vec::new(),
 		})
 	}
 
+	/// adds a named (secondary) data stream (e.g. an NTFS alternate data stream or a resource fork), which will be encoded after the main data of the file.
+	/// # Error
+	/// Fails, if the underlying file is not a regular file.
+	pub fn add_data_stream<N: Into<String>>(&mut self, name: N, reader: Box<dyn Read>) -> Result<()> {
+		if self.file_type != FileType::File {
+			return Err(ZffError::new(ZffErrorKind::NotAvailableForFileType, self.file_type.to_string()));
+		}
+		self.data_streams.push_back((name.into(), reader));
+		Ok(())
+	}
+
+	/// enables or disables the adaptive compression (the compression of likely incompressible chunks will be skipped).
+	pub fn set_adaptive_compression(&mut self, adaptive_compression: bool) {
+		self.adaptive_compression = adaptive_compression;
+	}
+
+	/// returns the number of chunks, whose compression was skipped by the adaptive compression.
+	pub fn skipped_compression_chunks(&self) -> u64 {
+		self.skipped_compression_chunks
+	}
+
+	/// sets the fuzzy hash types (e.g. [FuzzyHashType::Ssdeep]), which will be calculated for the content of the file
+	/// (in the same pass as the cryptographic hash values). Fuzzy hashes will only be calculated for regular files.
+	/// This method has to be called before the first chunk will be read.
+	pub fn set_fuzzy_hash_types(&mut self, fuzzy_hash_types: &[FuzzyHashType]) {
+		self.fuzzy_hashers = Vec::new();
+		if self.file_type != FileType::File {
+			return;
+		}
+		for fuzzy_hash_type in fuzzy_hash_types {
+			if !self.fuzzy_hashers.iter().any(|fuzzy_hasher| fuzzy_hasher.hash_type() == *fuzzy_hash_type) {
+				self.fuzzy_hashers.push(FuzzyHash::new_hasher(fuzzy_hash_type));
+			}
+		}
+	}
+
+	// sets the compressor, which should be used to compress the chunks (e.g. the compressor of the previous file of the same object).
+	pub(crate) fn set_compressor(&mut self, compressor: Box<dyn Compressor>) {
+		self.compressor = Some(compressor);
+	}
+
+	// takes the underlying compressor (if already initialized), so it can be reused by the next file encoder.
+	pub(crate) fn take_compressor(&mut self) -> Option<Box<dyn Compressor>> {
+		self.compressor.take()
+	}
+
+	/// returns the [FileType] of the underlying file.
+	pub fn file_type(&self) -> FileType {
+		self.file_type.clone()
+	}
+
+	// sets the (collision resistant) hash type, which will be used for the deduplication. The hash value of the first chunk
+	// will be calculated with this hash type while encoding (see first_chunk_hash). Only used for regular files.
+	pub(crate) fn set_deduplication_hash_type(&mut self, hash_type: Option<HashType>) {
+		if self.file_type == FileType::File {
+			self.deduplication_hash_type = hash_type;
+		}
+	}
+
+	// returns the hash value of the first chunk of the main data (calculated with the deduplication hash type), if already encoded.
+	pub(crate) fn first_chunk_hash(&self) -> Option<&[u8]> {
+		self.first_chunk_hash.as_deref()
+	}
+
+	// checks, if the content of the underlying file is identical to the content of a candidate with the same length. Only candidates with the same hash value
+	// of the first chunk will be checked by reading the whole file, so the underlying file will only be read twice, if it is likely a duplicate.
+	// If the hash value (of the deduplication hash type) matches the hash value of a candidate, the file will be encoded as a reference
+	// to the chunks of this candidate. Otherwise, the underlying file will be rewound (and hashed while encoding).
+	// Only regular files of the local filesystem can be deduplicated. Returns true, if the file was deduplicated.
+	pub(crate) fn deduplicate(&mut self, candidates: &HashMap<u64, Vec<DeduplicationCandidate>>) -> Result<bool> {
+		let hash_type = match &self.deduplication_hash_type {
+			Some(hash_type) => hash_type.clone(),
+			None => return Ok(false),
+		};
+		let underlying_file = match &mut self.underlying_file {
+			Some(FileContent::File(file)) if self.file_type == FileType::File => file,
+			_ => return Ok(false),
+		};
+		let candidates = match candidates.get(&self.underlying_file_length) {
+			Some(candidates) => candidates,
+			None => return Ok(false),
+		};
+		let chunk_size = self.main_header.chunk_size();
+		let (mut buffer, _) = buffer_chunk(underlying_file, chunk_size)?;
+		let first_chunk_hash = calculate_hash(&hash_type, &buffer);
+		let candidates: Vec<&DeduplicationCandidate> = candidates.iter().filter(|candidate| candidate.first_chunk_hash == first_chunk_hash).collect();
+		if candidates.is_empty() {
+			underlying_file.seek(SeekFrom::Start(0))?;
+			return Ok(false);
+		}
+
+		let mut hasher_map = self.hasher_map.clone();
+		let mut fuzzy_hashers: Vec<Box<dyn FuzzyHasher>> = self.fuzzy_hashers.iter().map(|fuzzy_hasher| FuzzyHash::new_hasher(&fuzzy_hasher.hash_type())).collect();
+		let mut read_bytes = 0;
+		while !buffer.is_empty() {
+			read_bytes += buffer.len() as u64;
+			for hasher in hasher_map.values_mut() {
+				hasher.update(&buffer);
+			}
+			for fuzzy_hasher in fuzzy_hashers.iter_mut() {
+				fuzzy_hasher.update(&buffer);
+			}
+			buffer = buffer_chunk(underlying_file, chunk_size)?.0;
+		}
+		let hash = hasher_map.get(&hash_type).map(|hasher| hasher.clone().finalize());
+		let candidate = candidates.into_iter().find(|candidate| candidate.length_of_data == read_bytes && Some(&candidate.hash[..]) == hash.as_deref());
+		match candidate {
+			Some(candidate) => {
+				self.hasher_map = hasher_map;
+				self.fuzzy_hashers = fuzzy_hashers;
+				self.read_bytes_underlying_data = read_bytes;
+				self.deduplicated_file = Some(candidate.clone());
+				Ok(true)
+			},
+			None => {
+				underlying_file.seek(SeekFrom::Start(0))?;
+				Ok(false)
+			},
+		}
+	}
+
 	fn update_hasher(&mut self, buffer: &[u8]) {
+		if let (None, Some(hash_type)) = (&self.first_chunk_hash, &self.deduplication_hash_type) {
+			self.first_chunk_hash = Some(calculate_hash(hash_type, buffer));
+		}
 		for hasher in self.hasher_map.values_mut() {
 			hasher.update(buffer);
 		}
+		for fuzzy_hasher in self.fuzzy_hashers.iter_mut() {
+			fuzzy_hasher.update(buffer);
+		}
 	}
 
 	/// returns the underlying encoded header
@@ -151,10 +422,33 @@ impl FileEncoder {
 	}
 
 	/// returns the encoded chunk - this method will increment the self.current_chunk_number automatically.
+	/// The chunks of the named data streams will be returned after the chunks of the main data.
 	pub fn get_next_chunk(&mut self) -> Result<Vec<u8>> {
-		let mut chunk_header = ChunkHeader::new_empty(DEFAULT_HEADER_VERSION_CHUNK_HEADER, self.current_chunk_number);
+		if self.number_of_data_chunks.is_none() {
+			match self.get_next_data_chunk() {
+				Err(e) if matches!(e.get_kind(), ZffErrorKind::ReadEOF) => {
+					self.number_of_data_chunks = Some(self.current_chunk_number - self.initial_chunk_number);
+				},
+				result => return result,
+			}
+		}
+		self.get_next_data_stream_chunk()
+	}
+
+	// returns the next encoded chunk of the main data.
+	fn get_next_data_chunk(&mut self) -> Result<Vec<u8>> {
+		if self.deduplicated_file.is_some() {
+			return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
+		}
+		let chunk_header = ChunkHeader::new_empty(DEFAULT_HEADER_VERSION_CHUNK_HEADER, self.current_chunk_number);
 		let chunk_size = self.main_header.chunk_size();
 
+		if self.file_type == FileType::File {
+			if let Some(hole_length) = self.current_hole_length(chunk_size as u64) {
+				return self.get_next_hole_chunk(chunk_header, hole_length);
+			}
+		}
+
 		let buf = match self.file_type {
 			FileType::Directory => {
 				let mut cursor = Cursor::new(&self.encoded_directory_children);
@@ -188,22 +482,72 @@ impl FileEncoder {
 					None => Vec::new(),
 				}		
 			},
-			FileType::File => {
-				let (buf, read_bytes) = buffer_chunk(&mut self.underlying_file, chunk_size as usize)?;
-				self.read_bytes_underlying_data += read_bytes;
-				buf
+			FileType::File => match &mut self.underlying_file {
+				Some(underlying_file) => {
+					let (buf, read_bytes) = buffer_chunk(underlying_file, chunk_size)?;
+					self.read_bytes_underlying_data += read_bytes;
+					buf
+				},
+				None => Vec::new(),
 			},
+			FileType::Fifo | FileType::Socket | FileType::BlockDevice | FileType::CharDevice => Vec::new(),
 		};
 		if buf.is_empty() {
 			return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
 		};
 		self.update_hasher(&buf);
+		self.encode_chunk(chunk_header, buf)
+	}
+
+	// returns the next encoded chunk of the named data streams.
+	fn get_next_data_stream_chunk(&mut self) -> Result<Vec<u8>> {
+		let chunk_size = self.main_header.chunk_size();
+		loop {
+			let data_stream = match &mut self.current_data_stream {
+				Some(data_stream) => data_stream,
+				None => match self.data_streams.pop_front() {
+					Some((name, reader)) => {
+						self.current_data_stream = Some(DataStreamEncoder::new(name, reader, self.current_chunk_number, self.hasher_map.keys()));
+						continue;
+					},
+					None => return Err(ZffError::new(ZffErrorKind::ReadEOF, "")),
+				},
+			};
+			let (buf, read_bytes) = buffer_chunk(&mut data_stream.reader, chunk_size)?;
+			if buf.is_empty() {
+				if let Some(data_stream) = self.current_data_stream.take() {
+					self.encoded_data_streams.push(data_stream.finalize(self.current_chunk_number));
+				}
+				continue;
+			}
+			data_stream.read_bytes += read_bytes;
+			for hasher in data_stream.hasher_map.values_mut() {
+				hasher.update(&buf);
+			}
+			let chunk_header = ChunkHeader::new_empty(DEFAULT_HEADER_VERSION_CHUNK_HEADER, self.current_chunk_number);
+			return self.encode_chunk(chunk_header, buf);
+		}
+	}
 
+	// compresses, encrypts and signs the given data and returns the encoded chunk.
+	fn encode_chunk(&mut self, mut chunk_header: ChunkHeader, buf: Vec<u8>) -> Result<Vec<u8>> {
 		let crc32 = calculate_crc32(&buf);
 		let signature = Signature::calculate_signature(self.signature_key.as_ref(), &buf);
 
-		let (compressed_data, inner_compression_flag) = compress_buffer(buf, self.main_header.chunk_size(), &self.compression_header)?;
-		let compression_flag = inner_compression_flag;
+		let compressor = match &mut self.compressor {
+			Some(compressor) => compressor,
+			None => self.compressor.insert(Compression::new_compressor(&self.compression_header, self.main_header.chunk_size())?),
+		};
+		let compression_threshold = self.compression_header.threshold();
+		let (compressed_data, compression_flag) = if self.adaptive_compression {
+			let (compressed_data, compression_flag, skipped_compression) = compress_chunk_adaptive(compressor.as_mut(), buf, compression_threshold)?;
+			if skipped_compression {
+				self.skipped_compression_chunks += 1;
+			}
+			(compressed_data, compression_flag)
+		} else {
+			compress_chunk(compressor.as_mut(), buf, compression_threshold)?
+		};
 
 		let mut chunk_data = match &self.encryption_key {
 			Some(encryption_key) => {
@@ -236,28 +580,94 @@ impl FileEncoder {
 	    Ok(chunk)
 	}
 
+	// returns the length of the next chunk, if the next chunk lies completely in a hole.
+	fn current_hole_length(&mut self, chunk_size: u64) -> Option<u64> {
+		let offset = self.read_bytes_underlying_data;
+		if offset >= self.underlying_file_length {
+			return None;
+		}
+		// the holes are sorted and the offset only grows, so the passed holes could be skipped.
+		while let Some((hole_offset, hole_length)) = self.holes.get(self.current_hole_index) {
+			if hole_offset + hole_length > offset {
+				break;
+			}
+			self.current_hole_index += 1;
+		}
+		let (hole_offset, hole_length) = self.holes.get(self.current_hole_index)?;
+		let length = std::cmp::min(chunk_size, self.underlying_file_length - offset);
+		if *hole_offset <= offset && offset + length <= hole_offset + hole_length {
+			Some(length)
+		} else {
+			None
+		}
+	}
+
+	// returns the next chunk, which lies in a hole of the given length. The hole will be read (which needs no disk I/O), because
+	// the file could be modified since the holes were detected: only if the read data are zeros, a sparse chunk (without data) will be returned.
+	fn get_next_hole_chunk(&mut self, mut chunk_header: ChunkHeader, hole_length: u64) -> Result<Vec<u8>> {
+		let mut buf = std::mem::take(&mut self.hole_buffer);
+		buf.clear();
+		if let Some(underlying_file) = &mut self.underlying_file {
+			underlying_file.take(hole_length).read_to_end(&mut buf)?;
+		}
+		if buf.is_empty() {
+			self.hole_buffer = buf;
+			return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
+		}
+		let offset = self.read_bytes_underlying_data;
+		self.read_bytes_underlying_data += buf.len() as u64;
+		self.update_hasher(&buf);
+		if buf.len() as u64 != hole_length || buf.iter().any(|byte| *byte != 0) {
+			let data = buf.clone();
+			self.hole_buffer = buf;
+			return self.encode_chunk(chunk_header, data);
+		}
+
+		chunk_header.set_chunk_size(hole_length);
+		chunk_header.set_crc32(calculate_crc32(&buf));
+		chunk_header.set_signature(Signature::calculate_signature(self.signature_key.as_ref(), &buf));
+		chunk_header.set_sparse_flag();
+		self.hole_buffer = buf;
+		match self.sparse_holes.last_mut() {
+			Some((last_offset, last_length)) if *last_offset + *last_length == offset => *last_length += hole_length,
+			_ => self.sparse_holes.push((offset, hole_length)),
+		}
+
+		self.current_chunk_number += 1;
+		Ok(chunk_header.encode_directly())
+	}
+
 	/// returns the appropriate encoded [FileFooter].
 	/// A call of this method finalizes the underlying hashers. You should be care.
 	pub fn get_encoded_footer(&mut self) -> Vec<u8> {
+		self.get_footer().encode_directly()
+	}
+
+	/// returns the appropriate [FileFooter].
+	/// A call of this method finalizes the underlying hashers. You should be care.
+	pub fn get_footer(&mut self) -> FileFooter {
 		self.acquisition_end = OffsetDateTime::from(SystemTime::now()).unix_timestamp() as u64;
-		let mut hash_values = Vec::new();
-		for (hash_type, hasher) in self.hasher_map.clone() {
-			let hash = hasher.finalize();
-			let mut hash_value = HashValue::new_empty(DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER, hash_type);
-			hash_value.set_hash(hash.to_vec());
-			hash_values.push(hash_value);
-		}
-		let hash_header = HashHeader::new(DEFAULT_HEADER_VERSION_HASH_HEADER, hash_values);
-		let footer = FileFooter::new(
+		let hash_header = HashHeader::new(DEFAULT_HEADER_VERSION_HASH_HEADER, finalize_hashers(self.hasher_map.clone()));
+		let (first_chunk_number, number_of_chunks) = match &self.deduplicated_file {
+			Some(deduplicated_file) => (deduplicated_file.first_chunk_number, deduplicated_file.number_of_chunks),
+			None => (self.initial_chunk_number, self.number_of_data_chunks.unwrap_or(self.current_chunk_number - self.initial_chunk_number)),
+		};
+		let mut footer = FileFooter::new(
 			DEFAULT_FOOTER_VERSION_FILE_FOOTER,
 			self.acquisition_start,
 			self.acquisition_end,
 			hash_header,
-			self.initial_chunk_number,
-			self.current_chunk_number - self.initial_chunk_number,
+			first_chunk_number,
+			number_of_chunks,
 			self.read_bytes_underlying_data as u64,
+			self.sparse_holes.clone(),
 			);
-		footer.encode_directly()
+		if let Some(deduplicated_file) = &self.deduplicated_file {
+			footer.set_deduplicated_file_number(deduplicated_file.file_number);
+		}
+		footer.set_data_streams(self.encoded_data_streams.clone());
+		footer.set_fuzzy_hashes(self.fuzzy_hashers.iter().map(|fuzzy_hasher| FuzzyHashValue::new(fuzzy_hasher.hash_type(), fuzzy_hasher.finalize())).collect());
+		footer
 	}
 }
 
@@ -320,4 +730,65 @@ impl Read for FileEncoder {
         }
         Ok(read_bytes)
 	}
-}
\ No newline at end of file
+}
+
+// returns the hash value of the given data.
+fn calculate_hash(hash_type: &HashType, data: &[u8]) -> Vec<u8> {
+	let mut hasher = Hash::new_hasher(hash_type);
+	hasher.update(data);
+	hasher.finalize().to_vec()
+}
+
+// finalizes the given hashers and returns the appropriate hash values.
+fn finalize_hashers(hasher_map: HashMap<HashType, Box<dyn DynDigest>>) -> Vec<HashValue> {
+	let mut hash_values = Vec::new();
+	for (hash_type, hasher) in hasher_map {
+		let hash = hasher.finalize();
+		let mut hash_value = HashValue::new_empty(DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER, hash_type);
+		hash_value.set_hash(hash.to_vec());
+		hash_values.push(hash_value);
+	}
+	hash_values
+}
+
+// returns the holes (offset, length) of the given file (using SEEK_DATA and SEEK_HOLE).
+// Returns an empty Vec, if the filesystem does not support these seek operations.
+#[cfg(target_os = "linux")]
+fn get_holes(file: &mut File) -> Result<Vec<(u64, u64)>> {
+	use std::os::unix::io::AsRawFd;
+	use nix::unistd::{lseek, Whence};
+	use nix::errno::Errno;
+
+	let length = file.metadata()?.len();
+	let fd = file.as_raw_fd();
+	let mut holes = Vec::new();
+	let mut offset = 0;
+	while offset < length {
+		let data_offset = match lseek(fd, offset as i64, Whence::SeekData) {
+			Ok(data_offset) => data_offset as u64,
+			// there is no more data after the offset: the rest of the file is a hole.
+			Err(Errno::ENXIO) => length,
+			Err(_) => {
+				file.seek(SeekFrom::Start(0))?;
+				return Ok(Vec::new());
+			},
+		};
+		if data_offset > offset {
+			holes.push((offset, data_offset - offset));
+		}
+		if data_offset >= length {
+			break;
+		}
+		offset = match lseek(fd, data_offset as i64, Whence::SeekHole) {
+			Ok(hole_offset) => hole_offset as u64,
+			Err(_) => length,
+		};
+	}
+	file.seek(SeekFrom::Start(0))?;
+	Ok(holes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_holes(_file: &mut File) -> Result<Vec<(u64, u64)>> {
+	Ok(Vec::new())
+}
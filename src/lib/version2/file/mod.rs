@@ -1,8 +1,10 @@
 // - modules
 mod encoder;
+mod virtual_file;
 
 // - re-exports
 pub use encoder::*;
+pub use virtual_file::*;
 
 // - internal
 use crate::{
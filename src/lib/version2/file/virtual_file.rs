@@ -0,0 +1,70 @@
+// - STD
+use std::io::{Read};
+use std::path::{PathBuf};
+use std::collections::{HashMap};
+
+// - internal
+use crate::{
+	header::{FileType},
+};
+
+// - external
+use time::{OffsetDateTime};
+
+/// The [VirtualFile] trait describes a file entry of a logical object, which does not exist on the local filesystem
+/// (e.g. a file extracted from a disk image, a mobile backup or a cloud export).
+/// The content of the file will be read by the [Read] implementation (only for regular files).
+/// A [VirtualFile] can be added to a logical object by using [LogicalTreeBuilder::add_virtual_file](crate::LogicalTreeBuilder::add_virtual_file).
+pub trait VirtualFile: Read {
+	/// returns the type of the file. Hardlinks are not supported for virtual files.
+	fn file_type(&self) -> FileType;
+
+	/// returns the name of the file (without the path of the parent directory).
+	fn filename(&self) -> String;
+
+	/// returns the last access time of the file, if available.
+	fn atime(&self) -> Option<OffsetDateTime> {
+		None
+	}
+
+	/// returns the last modification time of the file, if available.
+	fn mtime(&self) -> Option<OffsetDateTime> {
+		None
+	}
+
+	/// returns the last change time of the file (metadata), if available.
+	fn ctime(&self) -> Option<OffsetDateTime> {
+		None
+	}
+
+	/// returns the creation time of the file, if available.
+	fn btime(&self) -> Option<OffsetDateTime> {
+		None
+	}
+
+	/// returns the extended metadata of the file (e.g. uid, gid or mode; see the METADATA_EXT_* constants).
+	fn metadata_ext(&self) -> HashMap<String, String> {
+		HashMap::new()
+	}
+
+	/// returns the extended attributes of the file.
+	fn xattrs(&self) -> HashMap<Vec<u8>, Vec<u8>> {
+		HashMap::new()
+	}
+
+	/// returns the major and minor device number, if the file is a block or character device.
+	fn device_number(&self) -> Option<(u32, u32)> {
+		None
+	}
+
+	/// returns the target of the symlink, if the file is a symlink.
+	fn symlink_target(&self) -> Option<PathBuf> {
+		None
+	}
+
+	/// returns the named (secondary) data streams (name, content) of the file (e.g. alternate data streams or resource forks).
+	/// This method will only be called for regular files.
+	fn data_streams(&mut self) -> Vec<(String, Box<dyn Read>)> {
+		Vec::new()
+	}
+}
@@ -0,0 +1,320 @@
+// - STD
+use std::io::{Read, Seek, BufRead};
+use std::collections::{HashMap, BTreeMap};
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	HashType,
+	Object,
+	header::FileType,
+};
+
+use crate::{
+	ERROR_HASH_SET_INVALID_HEX,
+	ERROR_HASH_SET_MISSING_NSRL_HASH_COLUMN,
+};
+
+use super::{
+	ZffReader,
+};
+
+/// The status of a file, which was found in a [KnownHashSet].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum KnownFileStatus {
+	/// The file is known to be harmless (e.g. a file of the NSRL).
+	KnownGood,
+	/// The file is known to be relevant or malicious.
+	KnownBad,
+}
+
+/// The [KnownHashSet] contains hash values of known files (e.g. loaded from plain hash lists or from NSRL-style CSV files).
+/// If a hash value was added as [KnownGood](KnownFileStatus::KnownGood) and as [KnownBad](KnownFileStatus::KnownBad), the hash value will be treated as known bad.
+#[derive(Debug, Clone, Default)]
+pub struct KnownHashSet {
+	hashes: HashMap<HashType, HashMap<Vec<u8>, KnownFileStatus>>,
+}
+
+impl KnownHashSet {
+	/// returns a new, empty [KnownHashSet].
+	pub fn new() -> KnownHashSet {
+		Self::default()
+	}
+
+	/// adds the given hash value with the appropriate status.
+	pub fn add_hash(&mut self, hash_type: HashType, hash: Vec<u8>, status: KnownFileStatus) {
+		let status_entry = self.hashes.entry(hash_type).or_default().entry(hash).or_insert(status);
+		if status == KnownFileStatus::KnownBad {
+			*status_entry = status;
+		}
+	}
+
+	/// adds the given hexadecimal encoded hash value with the appropriate status.
+	/// # Error
+	/// Fails, if the given value is not a valid hexadecimal encoded hash value of the given [HashType].
+	pub fn add_hex_hash<H: AsRef<str>>(&mut self, hash_type: HashType, hash: H, status: KnownFileStatus) -> Result<()> {
+		let hash = decode_hex_hash(&hash_type, hash.as_ref())?;
+		self.add_hash(hash_type, hash, status);
+		Ok(())
+	}
+
+	/// loads a plain hash list (one hexadecimal encoded hash value per line) and returns the number of loaded hash values.
+	/// Empty lines and lines starting with '#' will be ignored. Additional columns (e.g. the filename in the output format of sha256sum)
+	/// will also be ignored.
+	/// # Error
+	/// Fails, if the reader could not be read or a line does not start with a valid hash value of the given [HashType].
+	pub fn load_hash_list<R: BufRead>(&mut self, reader: R, hash_type: HashType, status: KnownFileStatus) -> Result<usize> {
+		let mut loaded_hashes = 0;
+		for line in reader.lines() {
+			let line = line?;
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let hash = match line.split(|c: char| c.is_whitespace() || c == ',').next() {
+				Some(hash) => hash,
+				None => continue,
+			};
+			self.add_hex_hash(hash_type.clone(), hash, status)?;
+			loaded_hashes += 1;
+		}
+		Ok(loaded_hashes)
+	}
+
+	/// loads a NSRL-style CSV file (e.g. "NSRLFile.txt") and returns the number of loaded hash values.
+	/// The first line has to be the CSV header (e.g. "SHA-1","MD5","CRC32","FileName",...). All columns, which contain
+	/// a hash type supported by zff, will be loaded - all other columns will be ignored.
+	/// # Error
+	/// Fails, if the reader could not be read, the header does not contain a supported hash column or a hash value is invalid.
+	pub fn load_nsrl_csv<R: BufRead>(&mut self, reader: R, status: KnownFileStatus) -> Result<usize> {
+		let mut lines = reader.lines();
+		let header = match lines.next() {
+			Some(header) => header?,
+			None => return Ok(0),
+		};
+		let hash_columns: Vec<(usize, HashType)> = split_csv_line(&header).iter().enumerate()
+			.filter_map(|(index, column)| hash_type_from_column_name(column).map(|hash_type| (index, hash_type)))
+			.collect();
+		if hash_columns.is_empty() {
+			return Err(ZffError::new(ZffErrorKind::HashSetParserError, format!("{ERROR_HASH_SET_MISSING_NSRL_HASH_COLUMN}{header}")));
+		}
+
+		let mut loaded_hashes = 0;
+		for line in lines {
+			let line = line?;
+			if line.trim().is_empty() {
+				continue;
+			}
+			let fields = split_csv_line(&line);
+			for (index, hash_type) in &hash_columns {
+				match fields.get(*index) {
+					Some(hash) if !hash.is_empty() => {
+						self.add_hex_hash(hash_type.clone(), hash, status)?;
+						loaded_hashes += 1;
+					},
+					_ => (),
+				}
+			}
+		}
+		Ok(loaded_hashes)
+	}
+
+	/// returns the status of the given hash value, if the hash value is known.
+	pub fn status(&self, hash_type: &HashType, hash: &[u8]) -> Option<KnownFileStatus> {
+		self.hashes.get(hash_type)?.get(hash).copied()
+	}
+
+	/// returns the hash types of the loaded hash values.
+	pub fn hash_types(&self) -> Vec<HashType> {
+		self.hashes.keys().cloned().collect()
+	}
+
+	/// returns the number of loaded hash values.
+	pub fn len(&self) -> usize {
+		self.hashes.values().map(|hashes| hashes.len()).sum()
+	}
+
+	/// returns true, if no hash values are loaded.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+/// A file of a logical object, which was found in a [KnownHashSet].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KnownFileMatch {
+	file_number: u64,
+	hash_type: HashType,
+	status: KnownFileStatus,
+}
+
+impl KnownFileMatch {
+	/// returns the file number of the matched file.
+	pub fn file_number(&self) -> u64 {
+		self.file_number
+	}
+
+	/// returns the [HashType] of the matched hash value.
+	pub fn hash_type(&self) -> &HashType {
+		&self.hash_type
+	}
+
+	/// returns the [KnownFileStatus] of the matched file.
+	pub fn status(&self) -> KnownFileStatus {
+		self.status
+	}
+}
+
+/// The [FileHashIndex] contains the hash values of all regular files of a logical object (stored in the appropriate [FileFooter](crate::footer::FileFooter)s)
+/// and can be used to look up files by their hash values or to match the files against a [KnownHashSet].
+#[derive(Debug, Clone)]
+pub struct FileHashIndex {
+	object_number: u64,
+	file_hashes: BTreeMap<u64, Vec<(HashType, Vec<u8>)>>, //<file number, Vec<(hash type, hash value)>>
+	index: HashMap<HashType, HashMap<Vec<u8>, Vec<u64>>>, //<hash type, <hash value, Vec<file number>>>
+}
+
+impl FileHashIndex {
+	/// builds the [FileHashIndex] for the given logical object of the [ZffReader].
+	/// # Error
+	/// Fails, if the given object number does not exist or the object is not a logical object.
+	pub fn new<R: Read + Seek>(reader: &ZffReader<R>, object_number: u64) -> Result<FileHashIndex> {
+		let files = match reader.object(object_number) {
+			Some(Object::Logical(object)) => object.files(),
+			Some(Object::Physical(_)) => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, object_number.to_string())),
+			None => return Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		};
+		let mut file_hashes = BTreeMap::new();
+		let mut index: HashMap<HashType, HashMap<Vec<u8>, Vec<u64>>> = HashMap::new();
+		for (file_number, file) in files {
+			if file.filetype() != FileType::File {
+				continue;
+			}
+			let mut hashes = Vec::new();
+			for hash_value in file.footer().hash_header().hash_values() {
+				index.entry(hash_value.hash_type().clone()).or_default().entry(hash_value.hash().clone()).or_default().push(*file_number);
+				hashes.push((hash_value.hash_type().clone(), hash_value.hash().clone()));
+			}
+			file_hashes.insert(*file_number, hashes);
+		}
+		for hashes in index.values_mut() {
+			for file_numbers in hashes.values_mut() {
+				file_numbers.sort_unstable();
+			}
+		}
+		Ok(Self {
+			object_number,
+			file_hashes,
+			index,
+		})
+	}
+
+	/// returns the object number of the underlying logical object.
+	pub fn object_number(&self) -> u64 {
+		self.object_number
+	}
+
+	/// returns the hash types, which are available in this index.
+	pub fn hash_types(&self) -> Vec<HashType> {
+		self.index.keys().cloned().collect()
+	}
+
+	/// returns the hash values (hash type, hash value) of the given file number.
+	pub fn file_hashes(&self, file_number: u64) -> Option<&Vec<(HashType, Vec<u8>)>> {
+		self.file_hashes.get(&file_number)
+	}
+
+	/// returns the file numbers (sorted) of all files with the given hash value.
+	pub fn lookup(&self, hash_type: &HashType, hash: &[u8]) -> &[u64] {
+		match self.index.get(hash_type).and_then(|hashes| hashes.get(hash)) {
+			Some(file_numbers) => file_numbers,
+			None => &[],
+		}
+	}
+
+	/// returns the file numbers (sorted) of all files with the given hexadecimal encoded hash value.
+	/// # Error
+	/// Fails, if the given value is not a valid hexadecimal encoded hash value of the given [HashType].
+	pub fn lookup_hex<H: AsRef<str>>(&self, hash_type: &HashType, hash: H) -> Result<&[u64]> {
+		let hash = decode_hex_hash(hash_type, hash.as_ref())?;
+		Ok(self.lookup(hash_type, &hash))
+	}
+
+	/// matches all files against the given [KnownHashSet] and returns the matches (sorted by file number).
+	/// If a file matches with several hash types, the match with the status [KnownBad](KnownFileStatus::KnownBad) will be preferred.
+	pub fn match_known_files(&self, known_hashes: &KnownHashSet) -> Vec<KnownFileMatch> {
+		let mut matches = Vec::new();
+		for (file_number, hashes) in &self.file_hashes {
+			let mut file_match: Option<KnownFileMatch> = None;
+			for (hash_type, hash) in hashes {
+				let status = match known_hashes.status(hash_type, hash) {
+					Some(status) => status,
+					None => continue,
+				};
+				match file_match {
+					Some(ref current_match) if current_match.status == KnownFileStatus::KnownBad => (),
+					_ => file_match = Some(KnownFileMatch { file_number: *file_number, hash_type: hash_type.clone(), status }),
+				}
+			}
+			if let Some(file_match) = file_match {
+				matches.push(file_match);
+			}
+		}
+		matches
+	}
+}
+
+// returns the appropriate hash type of the given (NSRL) column name (e.g. "SHA-256" or "sha256").
+fn hash_type_from_column_name(column_name: &str) -> Option<HashType> {
+	let normalized_name: String = column_name.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_uppercase();
+	match normalized_name.as_str() {
+		"BLAKE2B512" | "BLAKE2B" => Some(HashType::Blake2b512),
+		"SHA256" => Some(HashType::SHA256),
+		"SHA512" => Some(HashType::SHA512),
+		"SHA3256" => Some(HashType::SHA3_256),
+		"BLAKE3" => Some(HashType::Blake3),
+		"MD5" => Some(HashType::MD5),
+		"SHA1" => Some(HashType::SHA1),
+		_ => None,
+	}
+}
+
+// splits a CSV line into its (unquoted) fields. Quoted fields may contain commas and escaped quotes ("").
+fn split_csv_line(line: &str) -> Vec<String> {
+	let mut fields = Vec::new();
+	let mut current_field = String::new();
+	let mut in_quotes = false;
+	let mut chars = line.trim_end_matches(&['\r', '\n'][..]).chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'"' if in_quotes && chars.peek() == Some(&'"') => {
+				current_field.push('"');
+				chars.next();
+			},
+			'"' => in_quotes = !in_quotes,
+			',' if !in_quotes => fields.push(std::mem::take(&mut current_field)),
+			_ => current_field.push(c),
+		}
+	}
+	fields.push(current_field);
+	fields
+}
+
+// decodes the given hexadecimal encoded hash value and checks the length for the given hash type.
+fn decode_hex_hash(hash_type: &HashType, hash: &str) -> Result<Vec<u8>> {
+	let hash = hash.trim();
+	let invalid_hex = || ZffError::new(ZffErrorKind::HashSetParserError, format!("{ERROR_HASH_SET_INVALID_HEX}{hash}"));
+	if hash.len() != hash_type.default_len() / 4 || !hash.is_ascii() {
+		return Err(invalid_hex());
+	}
+	let mut decoded_hash = Vec::with_capacity(hash.len() / 2);
+	for index in (0..hash.len()).step_by(2) {
+		match u8::from_str_radix(&hash[index..index + 2], 16) {
+			Ok(byte) => decoded_hash.push(byte),
+			Err(_) => return Err(invalid_hex()),
+		}
+	}
+	Ok(decoded_hash)
+}
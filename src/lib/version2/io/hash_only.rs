@@ -0,0 +1,186 @@
+// - STD
+use std::io::{Read};
+use std::ops::Range;
+
+// - internal
+use crate::{
+	Result,
+	ZffErrorKind,
+	HashType,
+	header::{MainHeader, ObjectHeader, HashHeader},
+	footer::{ObjectFooterPhysical},
+	PhysicalObjectEncoder,
+};
+
+/// The [HashOnlyAcquisition] reads a source (e.g. a device) by the chunking and hashing path of the [PhysicalObjectEncoder] and
+/// calculates all given hash values (and the piecewise hash values, if enabled) without compressing, encrypting or writing any data.
+/// This can be used to prove, that a source still matches a previously acquired image (see [ZffReader::verify_source](crate::ZffReader::verify_source)).
+pub struct HashOnlyAcquisition<R: Read> {
+	encoder: PhysicalObjectEncoder<R>,
+}
+
+impl<R: Read> HashOnlyAcquisition<R> {
+	/// Returns a new [HashOnlyAcquisition] by the given values. The chunk size of the given [MainHeader] will be used to read the source.
+	pub fn new(source: R, object_header: ObjectHeader, main_header: MainHeader, hash_types: Vec<HashType>) -> Result<HashOnlyAcquisition<R>> {
+		let encoder = PhysicalObjectEncoder::new(object_header, source, hash_types, None, None, main_header, 1, false)?;
+		Ok(Self {
+			encoder,
+		})
+	}
+
+	/// Enables the piecewise hashing with the given hash window size (in bytes). A hash window size of 0 disables the piecewise hashing.
+	pub fn set_piecewise_hash_size(&mut self, piecewise_hash_size: u64) {
+		self.encoder.set_piecewise_hash_size(piecewise_hash_size);
+	}
+
+	/// Reads the whole source and returns the appropriate [ObjectFooterPhysical], which contains the calculated hash values,
+	/// the piecewise hash values and the length of the read data.
+	pub fn acquire(mut self) -> Result<ObjectFooterPhysical> {
+		// sets the acquisition start time.
+		self.encoder.get_encoded_header();
+		loop {
+			match self.encoder.hash_next_chunk() {
+				Ok(_) => (),
+				Err(e) => match e.get_kind() {
+					ZffErrorKind::ReadEOF => break,
+					_ => return Err(e),
+				},
+			}
+		}
+		Ok(self.encoder.get_footer())
+	}
+}
+
+/// The comparison of a single hash value of an object with the appropriate hash value of the source.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HashVerificationResult {
+	hash_type: HashType,
+	expected: Vec<u8>,
+	calculated: Option<Vec<u8>>,
+}
+
+impl HashVerificationResult {
+	/// returns the appropriate [HashType].
+	pub fn hash_type(&self) -> &HashType {
+		&self.hash_type
+	}
+
+	/// returns the hash value, which is stored in the object footer.
+	pub fn expected(&self) -> &Vec<u8> {
+		&self.expected
+	}
+
+	/// returns the hash value of the source or None, if the hash value could not be calculated.
+	pub fn calculated(&self) -> Option<&Vec<u8>> {
+		self.calculated.as_ref()
+	}
+
+	/// returns true, if the calculated hash value matches the expected hash value.
+	pub fn matches(&self) -> bool {
+		self.calculated.as_ref() == Some(&self.expected)
+	}
+}
+
+/// The report of the verification of a source against a physical object (see [ZffReader::verify_source](crate::ZffReader::verify_source)).
+/// The merkle tree of the object will not be compared, as the leaves are stored in the segment footers and the tree depends on the segmentation of the container.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SourceVerificationReport {
+	object_number: u64,
+	expected_length: u64,
+	calculated_length: u64,
+	hash_results: Vec<HashVerificationResult>,
+	piecewise_hash_size: u64,
+	mismatching_regions: Vec<Range<u64>>,
+}
+
+impl SourceVerificationReport {
+	/// Returns a new [SourceVerificationReport] by comparing the expected footer (of the object) with the calculated footer (of the source).
+	pub fn new(object_number: u64, expected: &ObjectFooterPhysical, calculated: &ObjectFooterPhysical) -> SourceVerificationReport {
+		let hash_results = expected.hash_header().hash_values().iter().map(|hash_value| HashVerificationResult {
+			hash_type: hash_value.hash_type().clone(),
+			expected: hash_value.hash().clone(),
+			calculated: calculated_hash(calculated.hash_header(), hash_value.hash_type()),
+		}).collect();
+
+		let piecewise_hash_size = expected.piecewise_hash_size();
+		let mut mismatching_regions: Vec<Range<u64>> = Vec::new();
+		if piecewise_hash_size > 0 {
+			let length = std::cmp::max(expected.length_of_data(), calculated.length_of_data());
+			let number_of_windows = std::cmp::max(expected.piecewise_hashes().len(), calculated.piecewise_hashes().len()) as u64;
+			for window_number in 0..number_of_windows {
+				let matches = match (expected.piecewise_hashes().get(window_number as usize), calculated.piecewise_hashes().get(window_number as usize)) {
+					(Some(expected), Some(calculated)) => hash_headers_match(expected, calculated),
+					_ => false,
+				};
+				if matches {
+					continue;
+				}
+				let window_start = window_number * piecewise_hash_size;
+				let window_end = std::cmp::min(window_start + piecewise_hash_size, length);
+				match mismatching_regions.last_mut() {
+					Some(last_region) if last_region.end == window_start => last_region.end = window_end,
+					_ => mismatching_regions.push(window_start..window_end),
+				}
+			}
+		}
+
+		Self {
+			object_number,
+			expected_length: expected.length_of_data(),
+			calculated_length: calculated.length_of_data(),
+			hash_results,
+			piecewise_hash_size,
+			mismatching_regions,
+		}
+	}
+
+	/// returns the number of the verified object.
+	pub fn object_number(&self) -> u64 {
+		self.object_number
+	}
+
+	/// returns the length of the data of the object.
+	pub fn expected_length(&self) -> u64 {
+		self.expected_length
+	}
+
+	/// returns the length of the read data of the source.
+	pub fn calculated_length(&self) -> u64 {
+		self.calculated_length
+	}
+
+	/// returns the comparison results of all hash values of the object.
+	pub fn hash_results(&self) -> &Vec<HashVerificationResult> {
+		&self.hash_results
+	}
+
+	/// returns the hash window size of the piecewise hashes of the object (0, if the object contains no piecewise hashes).
+	pub fn piecewise_hash_size(&self) -> u64 {
+		self.piecewise_hash_size
+	}
+
+	/// returns the (merged) regions of the source, whose piecewise hash values do not match the piecewise hash values of the object.
+	pub fn mismatching_regions(&self) -> &Vec<Range<u64>> {
+		&self.mismatching_regions
+	}
+
+	/// returns true, if the lengths, all hash values and all piecewise hash values match.
+	/// Returns false, if the object contains no hash values.
+	pub fn is_match(&self) -> bool {
+		self.expected_length == self.calculated_length
+		&& !self.hash_results.is_empty()
+		&& self.hash_results.iter().all(|result| result.matches())
+		&& self.mismatching_regions.is_empty()
+	}
+}
+
+// returns the hash value of the given type, if available.
+fn calculated_hash(hash_header: &HashHeader, hash_type: &HashType) -> Option<Vec<u8>> {
+	hash_header.hash_values().iter().find(|hash_value| hash_value.hash_type() == hash_type).map(|hash_value| hash_value.hash().clone())
+}
+
+// returns true, if all hash values of the expected hash header are available in the calculated hash header and match.
+fn hash_headers_match(expected: &HashHeader, calculated: &HashHeader) -> bool {
+	!expected.hash_values().is_empty()
+	&& expected.hash_values().iter().all(|hash_value| calculated_hash(calculated, hash_value.hash_type()).as_ref() == Some(hash_value.hash()))
+}
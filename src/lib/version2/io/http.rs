@@ -0,0 +1,436 @@
+// - STD
+use std::io::{Read, Seek, SeekFrom, Cursor};
+use std::net::ToSocketAddrs;
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	Object,
+	header::HashHeader,
+	file_extension_from_segment_number,
+	FILE_EXTENSION_START,
+};
+
+use crate::{
+	HTTP_DEFAULT_READ_AHEAD_SIZE,
+	ERROR_HTTP_MISSING_CONTENT_LENGTH,
+	ERROR_HTTP_RANGE_NOT_SUPPORTED,
+	ERROR_HTTP_SERVER,
+	ERROR_HTTP_MISSING_RESOURCE,
+	ERROR_HTTP_INVALID_SEGMENT_URL,
+	ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION,
+};
+
+use super::{
+	ZffReader,
+};
+
+// - external
+use serde_json::{json, Value};
+use tiny_http::{Server, Request, Response, Header, Method, StatusCode};
+use ureq::{Agent, AgentBuilder, Error as HttpRequestError};
+use url::Url;
+use time::{OffsetDateTime};
+
+/// A HTTP server, which serves the objects and logical files of the underlying [ZffReader].
+/// The server supports (single) HTTP range requests, so the data can be read remotely (e.g. by a [HttpSegmentReader] or any other HTTP client).
+///
+/// The following routes are available:
+/// - `/` or `/objects`: a JSON listing of all objects, files and their metadata.
+/// - `/objects/<object number>`: the data of the appropriate physical object.
+/// - `/objects/<object number>/files/<file number>`: the data of the appropriate file of a logical object.
+pub struct ZffHttpServer<R: Read + Seek> {
+	reader: ZffReader<R>,
+}
+
+impl<R: Read + Seek> ZffHttpServer<R> {
+	/// creates a new [ZffHttpServer] for the given [ZffReader].
+	pub fn new(reader: ZffReader<R>) -> ZffHttpServer<R> {
+		Self {
+			reader,
+		}
+	}
+
+	/// returns the underlying [ZffReader].
+	pub fn into_inner(self) -> ZffReader<R> {
+		self.reader
+	}
+
+	/// starts a HTTP server at the given address and serves all incoming requests (one after another).
+	pub fn listen<A: ToSocketAddrs>(&mut self, addr: A) -> Result<()> {
+		let server = match Server::http(addr) {
+			Ok(server) => server,
+			Err(e) => return Err(ZffError::new(ZffErrorKind::HttpError, format!("{ERROR_HTTP_SERVER}{e}"))),
+		};
+		self.serve(&server)
+	}
+
+	/// serves all incoming requests of the given [Server](tiny_http::Server) (one after another).
+	/// An error of a single request does not stop the server.
+	pub fn serve(&mut self, server: &Server) -> Result<()> {
+		for request in server.incoming_requests() {
+			let _ = self.handle_request(request);
+		}
+		Ok(())
+	}
+
+	/// handles a single HTTP request.
+	pub fn handle_request(&mut self, request: Request) -> Result<()> {
+		if *request.method() != Method::Get && *request.method() != Method::Head {
+			request.respond(Response::from_string("").with_status_code(405))?;
+			return Ok(());
+		}
+		let path = request.url().split('?').next().unwrap_or_default().trim_end_matches('/').to_string();
+		let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+		match segments[..] {
+			[] | ["objects"] => {
+				let listing = self.listing().to_string();
+				let response = Response::from_string(listing).with_header(header("Content-Type", "application/json"));
+				request.respond(response)?;
+			},
+			["objects", object_number] => match object_number.parse::<u64>() {
+				Ok(object_number) if self.reader.set_reader_physical_object(object_number).is_ok() => self.respond_data(request)?,
+				_ => request.respond(Response::from_string("").with_status_code(404))?,
+			},
+			["objects", object_number, "files", file_number] => match (object_number.parse::<u64>(), file_number.parse::<u64>()) {
+				(Ok(object_number), Ok(file_number)) if self.reader.set_reader_logical_object_file(object_number, file_number).is_ok() => self.respond_data(request)?,
+				_ => request.respond(Response::from_string("").with_status_code(404))?,
+			},
+			_ => request.respond(Response::from_string("").with_status_code(404))?,
+		}
+		Ok(())
+	}
+
+	// responds the data of the active object/file of the reader (considers the range header of the request).
+	fn respond_data(&mut self, request: Request) -> Result<()> {
+		let length = self.reader.seek(SeekFrom::End(0))?;
+		let range = request.headers().iter().find(|header| header.field.equiv("Range")).map(|header| header.value.as_str().to_string());
+		let mut headers = vec![
+			header("Accept-Ranges", "bytes"),
+			header("Content-Type", "application/octet-stream"),
+		];
+		let (status_code, start, end) = match range.map(|range| parse_range(&range, length)) {
+			None | Some(RangeRequest::Unsupported) => (200, 0, length),
+			Some(RangeRequest::Range(start, end)) => {
+				headers.push(header("Content-Range", &format!("bytes {start}-{}/{length}", end - 1)));
+				(206, start, end)
+			},
+			Some(RangeRequest::Unsatisfiable) => {
+				headers.push(header("Content-Range", &format!("bytes */{length}")));
+				let response = Response::new(StatusCode(416), headers, Cursor::new(Vec::new()), Some(0), None);
+				request.respond(response)?;
+				return Ok(());
+			},
+		};
+		if *request.method() == Method::Head {
+			let response = Response::new(StatusCode(status_code), headers, Cursor::new(Vec::new()), Some((end - start) as usize), None)
+				.with_chunked_threshold(usize::MAX);
+			request.respond(response)?;
+			return Ok(());
+		}
+		self.reader.seek(SeekFrom::Start(start))?;
+		let data = (&mut self.reader).take(end - start);
+		// the length is known, so the response should never use the chunked transfer encoding.
+		let response = Response::new(StatusCode(status_code), headers, data, Some((end - start) as usize), None)
+			.with_chunked_threshold(usize::MAX);
+		request.respond(response)?;
+		Ok(())
+	}
+
+	/// returns a JSON listing of all objects (and the appropriate files of logical objects) and their metadata.
+	pub fn listing(&self) -> Value {
+		let mut object_numbers = self.reader.object_numbers();
+		object_numbers.sort_unstable();
+		let mut objects = Vec::new();
+		for object_number in object_numbers {
+			let object = match self.reader.object(object_number) {
+				Some(object) => object,
+				None => continue,
+			};
+			let header = object.header();
+			let description_header = header.description_header();
+			let mut object_value = json!({
+				"object_number": object_number,
+				"object_type": header.object_type().to_string(),
+				"acquisition_start": object.acquisition_start(),
+				"acquisition_end": object.acquisition_end(),
+				"compression_algorithm": header.compression_header().algorithm().to_string(),
+				"encrypted": object.encryption_header().is_some(),
+				"description": description_header.identifier_map(),
+			});
+			match object {
+				Object::Physical(object) => {
+					object_value["size"] = json!(object.footer().length_of_data());
+					object_value["hashes"] = hashes_to_json(object.footer().hash_header());
+				},
+				Object::Logical(object) => {
+					let mut file_numbers: Vec<&u64> = object.files().keys().collect();
+					file_numbers.sort_unstable();
+					let mut files = Vec::new();
+					for file_number in file_numbers {
+						let file = &object.files()[file_number];
+						let file_header = file.header();
+						files.push(json!({
+							"file_number": file_number,
+							"filename": file_header.filename(),
+							"file_type": file_header.file_type().to_string(),
+							"parent_file_number": file_header.parent_file_number(),
+							"size": file.length_of_data(),
+							"atime": timestamp_to_json(file_header.atime()),
+							"mtime": timestamp_to_json(file_header.mtime()),
+							"ctime": timestamp_to_json(file_header.ctime()),
+							"btime": timestamp_to_json(file_header.btime()),
+							"metadata_ext": file_header.metadata_ext(),
+							"hashes": hashes_to_json(file.footer().hash_header()),
+						}));
+					}
+					object_value["root_dir_filenumbers"] = json!(object.footer().root_dir_filenumbers());
+					object_value["files"] = Value::Array(files);
+				},
+			}
+			objects.push(object_value);
+		}
+		json!({
+			"description_notes": self.reader.description_notes(),
+			"undecryptable_objects": self.reader.undecryptable_objects(),
+			"objects": objects,
+		})
+	}
+}
+
+enum RangeRequest {
+	Range(u64, u64), //<start, end (exclusive)>
+	Unsatisfiable,
+	Unsupported,
+}
+
+// parses the value of a range header. Multiple ranges and other units than bytes are not supported (the complete data will be served).
+fn parse_range(range: &str, length: u64) -> RangeRequest {
+	let range = match range.trim().strip_prefix("bytes=") {
+		Some(range) if !range.contains(',') => range.trim(),
+		_ => return RangeRequest::Unsupported,
+	};
+	let (start, end) = match range.split_once('-') {
+		Some(bounds) => bounds,
+		None => return RangeRequest::Unsupported,
+	};
+	let (start, end) = match (start.trim(), end.trim()) {
+		("", suffix) => match suffix.parse::<u64>() {
+			Ok(0) => return RangeRequest::Unsatisfiable,
+			Ok(suffix) => (length.saturating_sub(suffix), length),
+			Err(_) => return RangeRequest::Unsupported,
+		},
+		(start, "") => match start.parse::<u64>() {
+			Ok(start) => (start, length),
+			Err(_) => return RangeRequest::Unsupported,
+		},
+		(start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+			(Ok(start), Ok(end)) if start <= end => (start, std::cmp::min(end.saturating_add(1), length)),
+			_ => return RangeRequest::Unsupported,
+		},
+	};
+	if start >= length {
+		return RangeRequest::Unsatisfiable;
+	}
+	RangeRequest::Range(start, end)
+}
+
+fn header(field: &str, value: &str) -> Header {
+	// the header fields and values are always valid ASCII strings here.
+	Header::from_bytes(field.as_bytes(), value.as_bytes()).unwrap()
+}
+
+// returns the timestamp as unix timestamp with nanoseconds (or null, if the timestamp is unavailable).
+fn timestamp_to_json(timestamp: Option<OffsetDateTime>) -> Value {
+	match timestamp {
+		Some(timestamp) => json!({
+			"seconds": timestamp.unix_timestamp(),
+			"nanoseconds": timestamp.nanosecond(),
+		}),
+		None => Value::Null,
+	}
+}
+
+fn hashes_to_json(hash_header: &HashHeader) -> Value {
+	let mut hashes = Vec::new();
+	for hash_value in hash_header.hash_values() {
+		let hash: String = hash_value.hash().iter().map(|byte| format!("{byte:02x}")).collect();
+		hashes.push(json!({
+			"hash_type": hash_value.hash_type().to_string(),
+			"hash": hash,
+		}));
+	}
+	Value::Array(hashes)
+}
+
+/// A [Reader](std::io::Read) for a remote file (e.g. a .zNN segment of a zff container), which is hosted on a HTTP server.
+/// The data will be fetched by using HTTP range requests, so the server has to support range requests.
+/// A [Vec] of [HttpSegmentReader]s can be used directly with the [ZffReader] (see [HttpSegmentReader::open_segments]).
+pub struct HttpSegmentReader {
+	agent: Agent,
+	url: String,
+	length: u64,
+	position: u64,
+	read_ahead_size: usize,
+	buffer: Vec<u8>,
+	buffer_offset: u64,
+}
+
+impl HttpSegmentReader {
+	/// creates a new [HttpSegmentReader] for the given url.
+	/// # Error
+	/// Fails, if the server does not send the length of the resource or the resource is not available.
+	pub fn new<U: Into<String>>(url: U) -> Result<HttpSegmentReader> {
+		Self::with_agent(AgentBuilder::new().build(), url)
+	}
+
+	/// creates a new [HttpSegmentReader] for the given url by using the given [Agent](ureq::Agent) (e.g. to use a proxy or custom timeouts).
+	pub fn with_agent<U: Into<String>>(agent: Agent, url: U) -> Result<HttpSegmentReader> {
+		let url = url.into();
+		match Self::open(agent, url.clone())? {
+			Some(segment) => Ok(segment),
+			None => Err(ZffError::new(ZffErrorKind::HttpError, format!("{ERROR_HTTP_MISSING_RESOURCE}{url}"))),
+		}
+	}
+
+	// opens the given url. Returns None, if the server replies with 404 (Not Found) or 416 (Range Not Satisfiable).
+	fn open(agent: Agent, url: String) -> Result<Option<HttpSegmentReader>> {
+		let response = match agent.head(&url).call() {
+			Ok(response) => response,
+			Err(HttpRequestError::Status(404 | 416, _)) => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+		let length = match response.header("Content-Length").map(|length| length.parse::<u64>()) {
+			Some(Ok(length)) => length,
+			// fallback, if the server does not send the length in the response of a HEAD request.
+			_ => {
+				let response = agent.get(&url).set("Range", "bytes=0-0").call()?;
+				match response.header("Content-Range").and_then(|range| range.rsplit_once('/')).map(|(_, length)| length.parse::<u64>()) {
+					Some(Ok(length)) => length,
+					_ => return Err(ZffError::new(ZffErrorKind::HttpError, format!("{ERROR_HTTP_MISSING_CONTENT_LENGTH}{url}"))),
+				}
+			},
+		};
+		Ok(Some(Self {
+			agent,
+			url,
+			length,
+			position: 0,
+			read_ahead_size: HTTP_DEFAULT_READ_AHEAD_SIZE,
+			buffer: Vec::new(),
+			buffer_offset: 0,
+		}))
+	}
+
+	/// opens all segments of the zff container, beginning with the given url of the first segment (e.g. "http://example.com/container.z01").
+	/// The following segments will be opened until the server replies with 404 (Not Found) or 416 (Range Not Satisfiable).
+	/// The query string (and the fragment) of the given url will be kept for all segments.
+	/// # Error
+	/// Fails, if the url does not point to a zff segment or if any other error occurs while opening a segment
+	/// (e.g. a timeout or a server error), so a truncated container will never be returned.
+	pub fn open_segments<U: Into<String>>(first_segment_url: U) -> Result<Vec<HttpSegmentReader>> {
+		let first_segment_url = first_segment_url.into();
+		let agent = AgentBuilder::new().build();
+		let mut segments = vec![Self::with_agent(agent.clone(), first_segment_url.clone())?];
+		let mut segment_number = 2;
+		loop {
+			let url = segment_url(&first_segment_url, segment_number)?;
+			match Self::open(agent.clone(), url)? {
+				Some(segment) => segments.push(segment),
+				None => break,
+			}
+			segment_number += 1;
+		}
+		Ok(segments)
+	}
+
+	/// sets the number of bytes, which will be fetched at least by a single request (the default value is 1 MiB).
+	pub fn set_read_ahead_size(&mut self, read_ahead_size: usize) {
+		self.read_ahead_size = std::cmp::max(read_ahead_size, 1);
+		self.buffer = Vec::new();
+	}
+
+	/// returns the url of the remote file.
+	pub fn url(&self) -> &str {
+		&self.url
+	}
+
+	/// returns the length of the remote file.
+	pub fn length(&self) -> u64 {
+		self.length
+	}
+
+	// fetches the given range (end is exclusive) of the remote file.
+	fn fetch(&self, start: u64, end: u64) -> Result<Vec<u8>> {
+		let response = self.agent.get(&self.url)
+			.set("Range", &format!("bytes={start}-{}", end - 1))
+			.call()?;
+		if response.status() != 206 {
+			return Err(ZffError::new(ZffErrorKind::HttpError, format!("{ERROR_HTTP_RANGE_NOT_SUPPORTED}{}", self.url)));
+		}
+		let mut data = Vec::with_capacity((end - start) as usize);
+		response.into_reader().take(end - start).read_to_end(&mut data)?;
+		Ok(data)
+	}
+}
+
+// returns the url of the segment with the given segment number by replacing the .zNN extension of the last path segment of the given url.
+fn segment_url(first_segment_url: &str, segment_number: u64) -> Result<String> {
+	let invalid_url = || ZffError::new(ZffErrorKind::HttpError, format!("{ERROR_HTTP_INVALID_SEGMENT_URL}{first_segment_url}"));
+	let mut url = Url::parse(first_segment_url).map_err(|_| invalid_url())?;
+	let (base_path, extension) = url.path().rsplit_once('.').ok_or_else(invalid_url)?;
+	let is_segment_extension = match extension.strip_prefix(FILE_EXTENSION_START) {
+		Some(number) => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+		None => false,
+	};
+	if !is_segment_extension || base_path.ends_with('/') {
+		return Err(invalid_url());
+	}
+	let path = format!("{base_path}.{}", file_extension_from_segment_number(segment_number));
+	url.set_path(&path);
+	Ok(url.to_string())
+}
+
+impl Read for HttpSegmentReader {
+	fn read(&mut self, buffer: &mut [u8]) -> std::result::Result<usize, std::io::Error> {
+		if self.position >= self.length || buffer.is_empty() {
+			return Ok(0);
+		}
+		let buffer_end = self.buffer_offset + self.buffer.len() as u64;
+		if self.position < self.buffer_offset || self.position >= buffer_end {
+			let end = std::cmp::min(self.position + std::cmp::max(buffer.len(), self.read_ahead_size) as u64, self.length);
+			self.buffer = match self.fetch(self.position, end) {
+				Ok(data) => data,
+				Err(e) => match e.unwrap_kind() {
+					ZffErrorKind::IoError(io_error) => return Err(io_error),
+					error => return Err(std::io::Error::new(std::io::ErrorKind::Other, error.to_string())),
+				},
+			};
+			self.buffer_offset = self.position;
+		}
+		let inner_position = (self.position - self.buffer_offset) as usize;
+		let read_bytes = (&self.buffer[inner_position..]).read(buffer)?;
+		self.position += read_bytes as u64;
+		Ok(read_bytes)
+	}
+}
+
+impl Seek for HttpSegmentReader {
+	fn seek(&mut self, seek_from: SeekFrom) -> std::result::Result<u64, std::io::Error> {
+		let (base, offset) = match seek_from {
+			SeekFrom::Start(value) => {
+				self.position = value;
+				return Ok(self.position);
+			},
+			SeekFrom::Current(value) => (self.position, value),
+			SeekFrom::End(value) => (self.length, value),
+		};
+		if base as i64 + offset < 0 {
+			return Err(std::io::Error::new(std::io::ErrorKind::Other, ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION));
+		}
+		self.position = (base as i64 + offset) as u64;
+		Ok(self.position)
+	}
+}
@@ -1,11 +1,11 @@
 // - STD
 use std::io::{Read};
-use std::fs::{Metadata};
+use std::fs::{File, Metadata};
 use std::collections::HashMap;
 use std::path::{Path};
 
 #[cfg(target_family = "unix")]
-use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::{MetadataExt, FileTypeExt};
 
 #[cfg(target_family = "windows")]
 use std::os::windows::fs::MetadataExt;
@@ -14,11 +14,27 @@ use std::os::windows::fs::MetadataExt;
 mod zffcreator;
 mod zffreader;
 mod zffextender;
+mod segment_sink;
+mod nbd;
+mod vfs;
+mod traversal;
+mod hash_index;
+mod hash_only;
+#[cfg(feature = "http")]
+mod http;
 
 // - re-exports
 pub use zffcreator::*;
 pub use zffreader::*;
 pub use zffextender::*;
+pub use segment_sink::*;
+pub use nbd::*;
+pub use vfs::*;
+pub use traversal::*;
+pub use hash_index::*;
+pub use hash_only::*;
+#[cfg(feature = "http")]
+pub use http::*;
 
 // - internal
 use crate::{
@@ -55,14 +71,16 @@ struct ObjectEncoderInformation<R: Read> {
 	pub object_encoder: ObjectEncoder<R>,
 	pub written_object_header: bool,
 	pub unaccessable_files: Vec<String>,
+	pub skipped_files: Vec<String>,
 }
 
 impl<R: Read> ObjectEncoderInformation<R> {
-	fn with_data(object_encoder: ObjectEncoder<R>, written_object_header: bool, unaccessable_files: Vec<String>) -> ObjectEncoderInformation<R> {
+	fn with_data(object_encoder: ObjectEncoder<R>, written_object_header: bool, unaccessable_files: Vec<String>, skipped_files: Vec<String>) -> ObjectEncoderInformation<R> {
 		Self {
 			object_encoder,
 			written_object_header,
-			unaccessable_files
+			unaccessable_files,
+			skipped_files,
 		}
 	}
 }
@@ -97,29 +115,39 @@ fn get_metadata_ext(metadata: &Metadata) -> HashMap<String, String> {
 	metadata_ext
 }
 
-fn get_time_from_metadata(metadata: &Metadata) -> HashMap<&str, u64> {
-	let mut timestamps = HashMap::new();
+// returns all readable extended attributes of the file (symlinks will not be followed).
+#[cfg(target_family = "unix")]
+fn get_xattrs(path: &Path) -> HashMap<Vec<u8>, Vec<u8>> {
+	use std::os::unix::ffi::OsStrExt;
 
-	let atime = match metadata.accessed() {
-		Ok(atime) => OffsetDateTime::from(atime).unix_timestamp() as u64,
-		Err(_) => 0
-	};
-	let mtime = match metadata.modified() {
-		Ok(mtime) => OffsetDateTime::from(mtime).unix_timestamp() as u64,
-		Err(_) => 0
+	let mut xattrs = HashMap::new();
+	let names = match xattr::list(path) {
+		Ok(names) => names,
+		Err(_) => return xattrs,
 	};
+	for name in names {
+		if let Ok(Some(value)) = xattr::get(path, &name) {
+			xattrs.insert(name.as_bytes().to_vec(), value);
+		}
+	}
+	xattrs
+}
+
+#[cfg(not(target_family = "unix"))]
+fn get_xattrs(_path: &Path) -> HashMap<Vec<u8>, Vec<u8>> {
+	HashMap::new()
+}
+
+fn get_time_from_metadata(metadata: &Metadata) -> HashMap<&str, Option<OffsetDateTime>> {
+	let mut timestamps = HashMap::new();
+
+	let atime = metadata.accessed().ok().map(OffsetDateTime::from);
+	let mtime = metadata.modified().ok().map(OffsetDateTime::from);
 	#[cfg(target_family = "windows")]
-	let ctime = match metadata.modified() {
-		Ok(ctime) => OffsetDateTime::from(ctime).unix_timestamp() as u64,
-		Err(_) => 0
-	};
+	let ctime = metadata.modified().ok().map(OffsetDateTime::from);
 	#[cfg(target_family = "unix")]
-	let ctime = metadata.ctime() as u64;
-
-	let btime = match metadata.created() {
-		Ok(btime) => OffsetDateTime::from(btime).unix_timestamp() as u64,
-		Err(_) => 0
-	};
+	let ctime = OffsetDateTime::from_unix_timestamp_nanos(metadata.ctime() as i128 * 1_000_000_000 + metadata.ctime_nsec() as i128).ok();
+	let btime = metadata.created().ok().map(OffsetDateTime::from);
 
 	timestamps.insert("atime", atime);
 	timestamps.insert("mtime", mtime);
@@ -129,6 +157,52 @@ fn get_time_from_metadata(metadata: &Metadata) -> HashMap<&str, u64> {
 	timestamps
 }
 
+// checks, if the file is accessible. Special files (fifos, sockets, devices) will not be opened
+// (opening a fifo would block until a writer is available).
+fn open_file_for_check(path: &Path, metadata: &Metadata) -> std::io::Result<()> {
+	if get_special_file_type(metadata).is_some() {
+		return Ok(());
+	}
+	File::open(path)?;
+	Ok(())
+}
+
+// returns the appropriate special file type (fifo, socket, block device or character device), if the file is a special file.
+#[cfg(target_family = "unix")]
+fn get_special_file_type(metadata: &Metadata) -> Option<FileType> {
+	let file_type = metadata.file_type();
+	if file_type.is_fifo() {
+		Some(FileType::Fifo)
+	} else if file_type.is_socket() {
+		Some(FileType::Socket)
+	} else if file_type.is_block_device() {
+		Some(FileType::BlockDevice)
+	} else if file_type.is_char_device() {
+		Some(FileType::CharDevice)
+	} else {
+		None
+	}
+}
+
+#[cfg(not(target_family = "unix"))]
+fn get_special_file_type(_metadata: &Metadata) -> Option<FileType> {
+	None
+}
+
+// returns the major and minor device numbers.
+#[cfg(target_os = "linux")]
+fn get_device_number(metadata: &Metadata) -> Option<(u32, u32)> {
+	use nix::sys::stat::{major, minor};
+
+	let rdev = metadata.rdev();
+	Some((major(rdev) as u32, minor(rdev) as u32))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_device_number(_metadata: &Metadata) -> Option<(u32, u32)> {
+	None
+}
+
 fn get_file_header(metadata: &Metadata, path: &Path, current_file_number: u64, parent_file_number: u64) -> Result<FileHeader> {
 	let filetype = if metadata.file_type().is_dir() {
 		FileType::Directory
@@ -136,6 +210,8 @@ fn get_file_header(metadata: &Metadata, path: &Path, current_file_number: u64, p
 		FileType::File
 	} else if metadata.file_type().is_symlink() {
 		FileType::Symlink
+	} else if let Some(special_file_type) = get_special_file_type(metadata) {
+		special_file_type
 	} else {
 		return Err(ZffError::new(ZffErrorKind::UnknownFileType, ""));
 	};
@@ -151,8 +227,9 @@ fn get_file_header(metadata: &Metadata, path: &Path, current_file_number: u64, p
 	let btime = timestamps.get("btime").unwrap();
 
 	let metadata_ext = get_metadata_ext(metadata);
+	let xattrs = get_xattrs(path);
 
-	let file_header = FileHeader::new(
+	let mut file_header = FileHeader::new(
 					DEFAULT_HEADER_VERSION_FILE_HEADER,
 					current_file_number,
 					filetype,
@@ -162,7 +239,11 @@ fn get_file_header(metadata: &Metadata, path: &Path, current_file_number: u64, p
 					*mtime,
 					*ctime,
 					*btime,
-					metadata_ext);
+					metadata_ext,
+					xattrs);
+	if let Some((major, minor)) = get_device_number(metadata) {
+		file_header.set_device_number(major, minor);
+	}
 	Ok(file_header)
 }
 
@@ -0,0 +1,650 @@
+// - STD
+use std::io::{Read, Write, Seek, SeekFrom, ErrorKind};
+use std::net::{TcpListener, ToSocketAddrs};
+use std::sync::{Arc, Mutex, Condvar};
+use std::thread;
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	Object,
+};
+
+use crate::{
+	NBD_MAGIC,
+	NBD_IHAVEOPT,
+	NBD_OPTION_REPLY_MAGIC,
+	NBD_REQUEST_MAGIC,
+	NBD_SIMPLE_REPLY_MAGIC,
+	NBD_FLAG_FIXED_NEWSTYLE,
+	NBD_FLAG_NO_ZEROES,
+	NBD_FLAG_C_FIXED_NEWSTYLE,
+	NBD_FLAG_C_NO_ZEROES,
+	NBD_FLAG_HAS_FLAGS,
+	NBD_FLAG_READ_ONLY,
+	NBD_FLAG_SEND_FLUSH,
+	NBD_FLAG_CAN_MULTI_CONN,
+	NBD_OPT_EXPORT_NAME,
+	NBD_OPT_ABORT,
+	NBD_OPT_LIST,
+	NBD_OPT_INFO,
+	NBD_OPT_GO,
+	NBD_REP_ACK,
+	NBD_REP_SERVER,
+	NBD_REP_INFO,
+	NBD_REP_FLAG_ERROR,
+	NBD_REP_ERR_UNSUP,
+	NBD_REP_ERR_INVALID,
+	NBD_REP_ERR_UNKNOWN,
+	NBD_INFO_EXPORT,
+	NBD_CMD_READ,
+	NBD_CMD_WRITE,
+	NBD_CMD_DISC,
+	NBD_CMD_FLUSH,
+	NBD_CMD_TRIM,
+	NBD_CMD_WRITE_ZEROES,
+	NBD_EPERM,
+	NBD_EIO,
+	NBD_EINVAL,
+	NBD_MAX_OPTION_LENGTH,
+	NBD_MAX_REQUEST_LENGTH,
+	ERROR_NBD_INVALID_MAGIC,
+	ERROR_NBD_UNKNOWN_EXPORT,
+	ERROR_NBD_OPTION_TOO_LONG,
+	ERROR_NBD_OPTION_REPLY,
+	ERROR_NBD_COMMAND_REPLY,
+	ERROR_NBD_CLIENT_NOT_FIXED_NEWSTYLE,
+	ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION,
+};
+
+use super::{
+	ZffReader,
+};
+
+// - external
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+const TRANSMISSION_FLAGS: u16 = NBD_FLAG_HAS_FLAGS | NBD_FLAG_READ_ONLY | NBD_FLAG_SEND_FLUSH;
+
+/// A read-only network block device (NBD) server, which exports each physical object of the underlying [ZffReader].
+/// The server speaks the fixed newstyle handshake of the NBD protocol, so the exports can be attached e.g. with `nbd-client` or `qemu-nbd`.
+/// The name of an export is the object number of the appropriate physical object (e.g. "1").
+/// An empty export name refers to the physical object with the lowest object number.
+/// ```
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+/// use std::net::{TcpListener, TcpStream};
+/// use zff::{ZffCreator, ZffCreatorMetadataParams, ZffReader, ZffNbdServer, NbdClient, MemorySegmentSink, HashType, CompressionAlgorithm, SignatureFlag};
+/// use zff::header::{MainHeader, ObjectHeader, ObjectType, CompressionHeader, DescriptionHeader};
+///
+/// let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+/// let compression_header = CompressionHeader::new(1, CompressionAlgorithm::Zstd, 3, 1.05);
+/// let object_header = ObjectHeader::new(2, 1, None, compression_header, SignatureFlag::NoSignatures, DescriptionHeader::new_empty(2), ObjectType::Physical);
+/// let params = ZffCreatorMetadataParams::with_data(None, None, MainHeader::new(2, 12, 0, 1), false, None);
+/// let mut physical_objects = HashMap::new();
+/// physical_objects.insert(object_header, Cursor::new(data.clone()));
+/// let mut creator = ZffCreator::new(physical_objects, HashMap::new(), vec![HashType::Blake3], "unused", params).unwrap();
+/// let mut sink = MemorySegmentSink::new();
+/// creator.generate_segments(&mut sink).unwrap();
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// let segments = sink.into_readers();
+/// std::thread::spawn(move || {
+///     let (stream, _) = listener.accept().unwrap();
+///     let mut server = ZffNbdServer::new(ZffReader::new(segments, HashMap::new()).unwrap());
+///     server.serve_connection(stream).unwrap();
+/// });
+///
+/// let mut client = NbdClient::connect(TcpStream::connect(address).unwrap(), "1").unwrap();
+/// assert_eq!(client.export_size(), data.len() as u64);
+/// let mut buffer = vec![0u8; 10_000];
+/// client.read_at(45_000, &mut buffer).unwrap();
+/// assert_eq!(buffer, &data[45_000..55_000]);
+/// client.disconnect().unwrap();
+/// ```
+pub struct ZffNbdServer<R: Read + Seek> {
+	reader: ZffReader<R>,
+	multi_conn: bool, // true, if other connections to the same exports are served concurrently (see [ZffNbdServer::serve_listener_concurrent]).
+}
+
+impl<R: Read + Seek> ZffNbdServer<R> {
+	/// creates a new [ZffNbdServer] for the given [ZffReader].
+	pub fn new(reader: ZffReader<R>) -> ZffNbdServer<R> {
+		Self {
+			reader,
+			multi_conn: false,
+		}
+	}
+
+	/// returns the names of all available exports (the sorted object numbers of the physical objects).
+	pub fn export_names(&self) -> Vec<String> {
+		let mut object_numbers = self.reader.physical_object_numbers();
+		object_numbers.sort_unstable();
+		object_numbers.iter().map(|object_number| object_number.to_string()).collect()
+	}
+
+	/// returns the underlying [ZffReader].
+	pub fn into_inner(self) -> ZffReader<R> {
+		self.reader
+	}
+
+	/// binds a [TcpListener] to the given address and serves all incoming connections (one after another).
+	/// Clients which open several connections at once (e.g. `nbd-client -C 4`) have to use [ZffNbdServer::listen_concurrent] instead.
+	pub fn listen<A: ToSocketAddrs>(&mut self, addr: A) -> Result<()> {
+		let listener = TcpListener::bind(addr)?;
+		self.serve_listener(&listener)
+	}
+
+	/// serves all incoming connections of the given [TcpListener] (one after another).
+	/// An error of a single connection does not stop the server.
+	pub fn serve_listener(&mut self, listener: &TcpListener) -> Result<()> {
+		for stream in listener.incoming() {
+			let stream = stream?;
+			let _ = self.serve_connection(stream);
+		}
+		Ok(())
+	}
+
+	// returns the transmission flags of the exports.
+	fn transmission_flags(&self) -> u16 {
+		if self.multi_conn {
+			TRANSMISSION_FLAGS | NBD_FLAG_CAN_MULTI_CONN
+		} else {
+			TRANSMISSION_FLAGS
+		}
+	}
+
+	/// serves a single NBD connection (handshake and transmission phase) by using the given stream.
+	/// Returns, if the client disconnects or aborts the handshake.
+	/// # Error
+	/// Fails (and closes the connection), if the client does not support the fixed newstyle negotiation or violates the protocol.
+	pub fn serve_connection<S: Read + Write>(&mut self, mut stream: S) -> Result<()> {
+		stream.write_u64::<BigEndian>(NBD_MAGIC)?;
+		stream.write_u64::<BigEndian>(NBD_IHAVEOPT)?;
+		stream.write_u16::<BigEndian>(NBD_FLAG_FIXED_NEWSTYLE | NBD_FLAG_NO_ZEROES)?;
+		stream.flush()?;
+		let client_flags = stream.read_u32::<BigEndian>()?;
+		// the option haggling is only supported by the fixed newstyle negotiation.
+		if client_flags & NBD_FLAG_C_FIXED_NEWSTYLE == 0 {
+			return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_CLIENT_NOT_FIXED_NEWSTYLE}{client_flags:#x}")));
+		}
+		let no_zeroes = client_flags & NBD_FLAG_C_NO_ZEROES != 0;
+
+		// - option haggling
+		let object_number = loop {
+			let magic = stream.read_u64::<BigEndian>()?;
+			if magic != NBD_IHAVEOPT {
+				return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+			}
+			let option = stream.read_u32::<BigEndian>()?;
+			let length = stream.read_u32::<BigEndian>()?;
+			if length > NBD_MAX_OPTION_LENGTH {
+				return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_OPTION_TOO_LONG}{length}")));
+			}
+			let mut data = vec![0u8; length as usize];
+			stream.read_exact(&mut data)?;
+
+			match option {
+				NBD_OPT_EXPORT_NAME => {
+					let name = String::from_utf8_lossy(&data).to_string();
+					let object_number = match self.resolve_export(&name) {
+						Some(object_number) => object_number,
+						None => return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_UNKNOWN_EXPORT}{name}"))),
+					};
+					stream.write_u64::<BigEndian>(self.export_size(object_number))?;
+					stream.write_u16::<BigEndian>(self.transmission_flags())?;
+					if !no_zeroes {
+						stream.write_all(&[0u8; 124])?;
+					}
+					stream.flush()?;
+					break object_number;
+				},
+				NBD_OPT_ABORT => {
+					write_option_reply(&mut stream, option, NBD_REP_ACK, &[])?;
+					return Ok(());
+				},
+				NBD_OPT_LIST => {
+					if !data.is_empty() {
+						write_option_reply(&mut stream, option, NBD_REP_ERR_INVALID, &[])?;
+						continue;
+					}
+					for name in self.export_names() {
+						let mut reply = Vec::new();
+						reply.write_u32::<BigEndian>(name.len() as u32)?;
+						reply.extend_from_slice(name.as_bytes());
+						write_option_reply(&mut stream, option, NBD_REP_SERVER, &reply)?;
+					}
+					write_option_reply(&mut stream, option, NBD_REP_ACK, &[])?;
+				},
+				NBD_OPT_INFO | NBD_OPT_GO => {
+					let name = match parse_info_request(&data) {
+						Some(name) => name,
+						None => {
+							write_option_reply(&mut stream, option, NBD_REP_ERR_INVALID, &[])?;
+							continue;
+						},
+					};
+					let object_number = match self.resolve_export(&name) {
+						Some(object_number) => object_number,
+						None => {
+							write_option_reply(&mut stream, option, NBD_REP_ERR_UNKNOWN, format!("{ERROR_NBD_UNKNOWN_EXPORT}{name}").as_bytes())?;
+							continue;
+						},
+					};
+					let mut reply = Vec::new();
+					reply.write_u16::<BigEndian>(NBD_INFO_EXPORT)?;
+					reply.write_u64::<BigEndian>(self.export_size(object_number))?;
+					reply.write_u16::<BigEndian>(self.transmission_flags())?;
+					write_option_reply(&mut stream, option, NBD_REP_INFO, &reply)?;
+					write_option_reply(&mut stream, option, NBD_REP_ACK, &[])?;
+					if option == NBD_OPT_GO {
+						break object_number;
+					}
+				},
+				_ => write_option_reply(&mut stream, option, NBD_REP_ERR_UNSUP, &[])?,
+			}
+		};
+
+		self.transmission(&mut stream, object_number)
+	}
+
+	// handles the transmission phase for the given object.
+	fn transmission<S: Read + Write>(&mut self, stream: &mut S, object_number: u64) -> Result<()> {
+		let export_size = self.export_size(object_number);
+		loop {
+			let magic = match stream.read_u32::<BigEndian>() {
+				Ok(magic) => magic,
+				Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+				Err(e) => return Err(e.into()),
+			};
+			if magic != NBD_REQUEST_MAGIC {
+				return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+			}
+			let _command_flags = stream.read_u16::<BigEndian>()?;
+			let command = stream.read_u16::<BigEndian>()?;
+			let handle = stream.read_u64::<BigEndian>()?;
+			let offset = stream.read_u64::<BigEndian>()?;
+			let length = stream.read_u32::<BigEndian>()?;
+
+			match command {
+				NBD_CMD_READ => {
+					if length > NBD_MAX_REQUEST_LENGTH || offset.checked_add(length as u64).map_or(true, |end| end > export_size) {
+						write_simple_reply(stream, NBD_EINVAL, handle, &[])?;
+						continue;
+					}
+					let mut buffer = vec![0u8; length as usize];
+					match self.read_object(object_number, offset, &mut buffer) {
+						Ok(_) => write_simple_reply(stream, 0, handle, &buffer)?,
+						Err(_) => write_simple_reply(stream, NBD_EIO, handle, &[])?,
+					}
+				},
+				NBD_CMD_WRITE => {
+					// the payload has to be consumed, before the request can be rejected.
+					std::io::copy(&mut Read::by_ref(stream).take(length as u64), &mut std::io::sink())?;
+					write_simple_reply(stream, NBD_EPERM, handle, &[])?;
+				},
+				NBD_CMD_DISC => return Ok(()),
+				NBD_CMD_FLUSH => write_simple_reply(stream, 0, handle, &[])?,
+				NBD_CMD_TRIM | NBD_CMD_WRITE_ZEROES => write_simple_reply(stream, NBD_EPERM, handle, &[])?,
+				_ => write_simple_reply(stream, NBD_EINVAL, handle, &[])?,
+			}
+		}
+	}
+
+	fn read_object(&mut self, object_number: u64, offset: u64, buffer: &mut [u8]) -> Result<()> {
+		self.reader.set_reader_physical_object(object_number)?;
+		self.reader.seek(SeekFrom::Start(offset))?;
+		self.reader.read_exact(buffer)?;
+		Ok(())
+	}
+
+	fn resolve_export(&self, name: &str) -> Option<u64> {
+		if name.is_empty() {
+			return self.reader.physical_object_numbers().into_iter().min();
+		}
+		let object_number = name.parse::<u64>().ok()?;
+		match self.reader.object(object_number) {
+			Some(Object::Physical(_)) => Some(object_number),
+			_ => None,
+		}
+	}
+
+	fn export_size(&self, object_number: u64) -> u64 {
+		match self.reader.object(object_number) {
+			Some(Object::Physical(object)) => object.footer().length_of_data(),
+			_ => 0,
+		}
+	}
+}
+
+impl<R: Read + Seek + Send + 'static> ZffNbdServer<R> {
+	/// binds a [TcpListener] to the given address and serves all incoming connections concurrently (see [ZffNbdServer::serve_listener_concurrent]).
+	pub fn listen_concurrent<A, F, E>(addr: A, max_connections: usize, open_reader: F, on_error: E) -> Result<()>
+	where
+		A: ToSocketAddrs,
+		F: Fn() -> Result<ZffReader<R>> + Send + Sync + 'static,
+		E: Fn(ZffError) + Send + Sync + 'static,
+	{
+		let listener = TcpListener::bind(addr)?;
+		Self::serve_listener_concurrent(&listener, max_connections, open_reader, on_error)
+	}
+
+	/// serves all incoming connections of the given [TcpListener] concurrently. Each connection will be served in its own thread
+	/// by its own [ZffReader], which will be opened by the given function. As all connections see the same (read-only) data,
+	/// the exports advertise multi connection support, so clients can open several connections at once (e.g. `nbd-client -C 4`).
+	/// At most the given number of connections (at least 1) will be served at the same time: further connections will be accepted,
+	/// after a served connection was closed.
+	/// An error of a single connection (or of opening its reader) does not stop the server, but will be passed to the given error handler.
+	pub fn serve_listener_concurrent<F, E>(listener: &TcpListener, max_connections: usize, open_reader: F, on_error: E) -> Result<()>
+	where
+		F: Fn() -> Result<ZffReader<R>> + Send + Sync + 'static,
+		E: Fn(ZffError) + Send + Sync + 'static,
+	{
+		let open_reader = Arc::new(open_reader);
+		let on_error = Arc::new(on_error);
+		let connections = Arc::new(ConnectionLimit::new(max_connections));
+		loop {
+			let connection_slot = ConnectionLimit::acquire(&connections);
+			let (stream, _) = listener.accept()?;
+			let open_reader = Arc::clone(&open_reader);
+			let on_error = Arc::clone(&on_error);
+			thread::spawn(move || {
+				let result = open_reader().and_then(|reader| {
+					let mut server = ZffNbdServer::new(reader);
+					server.multi_conn = true;
+					server.serve_connection(stream)
+				});
+				// the slot will be released, if the connection is closed (or the thread panics).
+				drop(connection_slot);
+				if let Err(e) = result {
+					on_error(e);
+				}
+			});
+		}
+	}
+}
+
+// limits the number of concurrently served connections.
+struct ConnectionLimit {
+	active_connections: Mutex<usize>,
+	released: Condvar,
+	max_connections: usize,
+}
+
+impl ConnectionLimit {
+	fn new(max_connections: usize) -> ConnectionLimit {
+		Self {
+			active_connections: Mutex::new(0),
+			released: Condvar::new(),
+			max_connections: std::cmp::max(max_connections, 1),
+		}
+	}
+
+	// blocks until a further connection could be served and returns the slot of this connection.
+	fn acquire(limit: &Arc<ConnectionLimit>) -> ConnectionSlot {
+		let mut active_connections = limit.active_connections.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		while *active_connections >= limit.max_connections {
+			active_connections = limit.released.wait(active_connections).unwrap_or_else(|poisoned| poisoned.into_inner());
+		}
+		*active_connections += 1;
+		ConnectionSlot(Arc::clone(limit))
+	}
+}
+
+// a served connection, which releases its slot of the [ConnectionLimit] on drop.
+struct ConnectionSlot(Arc<ConnectionLimit>);
+
+impl Drop for ConnectionSlot {
+	fn drop(&mut self) {
+		let mut active_connections = self.0.active_connections.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+		*active_connections -= 1;
+		self.0.released.notify_one();
+	}
+}
+
+// parses the data of a NBD_OPT_INFO or NBD_OPT_GO request and returns the requested export name.
+fn parse_info_request(data: &[u8]) -> Option<String> {
+	let mut cursor = data;
+	let name_length = cursor.read_u32::<BigEndian>().ok()? as usize;
+	if cursor.len() < name_length {
+		return None;
+	}
+	let name = String::from_utf8_lossy(&cursor[..name_length]).to_string();
+	cursor = &cursor[name_length..];
+	let number_of_requests = cursor.read_u16::<BigEndian>().ok()? as usize;
+	if cursor.len() != number_of_requests * 2 {
+		return None;
+	}
+	Some(name)
+}
+
+fn write_option_reply<W: Write>(stream: &mut W, option: u32, reply_type: u32, data: &[u8]) -> Result<()> {
+	stream.write_u64::<BigEndian>(NBD_OPTION_REPLY_MAGIC)?;
+	stream.write_u32::<BigEndian>(option)?;
+	stream.write_u32::<BigEndian>(reply_type)?;
+	stream.write_u32::<BigEndian>(data.len() as u32)?;
+	stream.write_all(data)?;
+	stream.flush()?;
+	Ok(())
+}
+
+fn write_simple_reply<W: Write>(stream: &mut W, error: u32, handle: u64, data: &[u8]) -> Result<()> {
+	stream.write_u32::<BigEndian>(NBD_SIMPLE_REPLY_MAGIC)?;
+	stream.write_u32::<BigEndian>(error)?;
+	stream.write_u64::<BigEndian>(handle)?;
+	stream.write_all(data)?;
+	stream.flush()?;
+	Ok(())
+}
+
+/// A minimal, read-only NBD client, which can be used to access an export of a NBD server (e.g. the [ZffNbdServer]) as a [Reader](std::io::Read).
+pub struct NbdClient<S: Read + Write> {
+	stream: S,
+	export_size: u64,
+	transmission_flags: u16,
+	position: u64,
+	next_handle: u64,
+}
+
+impl<S: Read + Write> NbdClient<S> {
+	/// connects to the export with the given name by using the given stream (performs the fixed newstyle handshake).
+	pub fn connect(mut stream: S, export_name: &str) -> Result<NbdClient<S>> {
+		handshake(&mut stream)?;
+
+		let mut data = Vec::new();
+		data.write_u32::<BigEndian>(export_name.len() as u32)?;
+		data.extend_from_slice(export_name.as_bytes());
+		data.write_u16::<BigEndian>(0)?;
+		write_option_request(&mut stream, NBD_OPT_GO, &data)?;
+
+		let mut export_info = None;
+		loop {
+			let (reply_type, data) = read_option_reply(&mut stream, NBD_OPT_GO)?;
+			match reply_type {
+				NBD_REP_ACK => break,
+				NBD_REP_INFO => {
+					let mut cursor = &data[..];
+					if cursor.read_u16::<BigEndian>()? == NBD_INFO_EXPORT {
+						let export_size = cursor.read_u64::<BigEndian>()?;
+						let transmission_flags = cursor.read_u16::<BigEndian>()?;
+						export_info = Some((export_size, transmission_flags));
+					}
+				},
+				_ => (),
+			}
+		}
+		let (export_size, transmission_flags) = match export_info {
+			Some(export_info) => export_info,
+			None => return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_UNKNOWN_EXPORT}{export_name}"))),
+		};
+
+		Ok(Self {
+			stream,
+			export_size,
+			transmission_flags,
+			position: 0,
+			next_handle: 0,
+		})
+	}
+
+	/// returns the names of all exports of the NBD server. The connection will be closed afterwards.
+	pub fn list_exports(mut stream: S) -> Result<Vec<String>> {
+		handshake(&mut stream)?;
+		write_option_request(&mut stream, NBD_OPT_LIST, &[])?;
+		let mut export_names = Vec::new();
+		loop {
+			let (reply_type, data) = read_option_reply(&mut stream, NBD_OPT_LIST)?;
+			match reply_type {
+				NBD_REP_ACK => break,
+				NBD_REP_SERVER => {
+					let mut cursor = &data[..];
+					let name_length = cursor.read_u32::<BigEndian>()? as usize;
+					let name = cursor.get(..name_length).unwrap_or(cursor);
+					export_names.push(String::from_utf8_lossy(name).to_string());
+				},
+				_ => (),
+			}
+		}
+		write_option_request(&mut stream, NBD_OPT_ABORT, &[])?;
+		Ok(export_names)
+	}
+
+	/// returns the size of the export.
+	pub fn export_size(&self) -> u64 {
+		self.export_size
+	}
+
+	/// returns the transmission flags of the export.
+	pub fn transmission_flags(&self) -> u16 {
+		self.transmission_flags
+	}
+
+	/// reads exactly buffer.len() bytes at the given offset of the export.
+	pub fn read_at(&mut self, offset: u64, buffer: &mut [u8]) -> Result<()> {
+		for (index, part) in buffer.chunks_mut(NBD_MAX_REQUEST_LENGTH as usize).enumerate() {
+			let handle = self.next_handle;
+			self.next_handle += 1;
+			self.stream.write_u32::<BigEndian>(NBD_REQUEST_MAGIC)?;
+			self.stream.write_u16::<BigEndian>(0)?;
+			self.stream.write_u16::<BigEndian>(NBD_CMD_READ)?;
+			self.stream.write_u64::<BigEndian>(handle)?;
+			self.stream.write_u64::<BigEndian>(offset + (index * NBD_MAX_REQUEST_LENGTH as usize) as u64)?;
+			self.stream.write_u32::<BigEndian>(part.len() as u32)?;
+			self.stream.flush()?;
+
+			let magic = self.stream.read_u32::<BigEndian>()?;
+			if magic != NBD_SIMPLE_REPLY_MAGIC {
+				return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+			}
+			let error = self.stream.read_u32::<BigEndian>()?;
+			let _handle = self.stream.read_u64::<BigEndian>()?;
+			if error != 0 {
+				return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_COMMAND_REPLY}{error}")));
+			}
+			self.stream.read_exact(part)?;
+		}
+		Ok(())
+	}
+
+	/// sends a disconnect request to the server and returns the underlying stream.
+	pub fn disconnect(mut self) -> Result<S> {
+		let handle = self.next_handle;
+		self.stream.write_u32::<BigEndian>(NBD_REQUEST_MAGIC)?;
+		self.stream.write_u16::<BigEndian>(0)?;
+		self.stream.write_u16::<BigEndian>(NBD_CMD_DISC)?;
+		self.stream.write_u64::<BigEndian>(handle)?;
+		self.stream.write_u64::<BigEndian>(0)?;
+		self.stream.write_u32::<BigEndian>(0)?;
+		self.stream.flush()?;
+		Ok(self.stream)
+	}
+}
+
+impl<S: Read + Write> Read for NbdClient<S> {
+	fn read(&mut self, buffer: &mut [u8]) -> std::result::Result<usize, std::io::Error> {
+		if self.position >= self.export_size {
+			return Ok(0);
+		}
+		let length = std::cmp::min(buffer.len() as u64, self.export_size - self.position) as usize;
+		if let Err(e) = self.read_at(self.position, &mut buffer[..length]) {
+			return match e.unwrap_kind() {
+				ZffErrorKind::IoError(io_error) => Err(io_error),
+				error => Err(std::io::Error::new(std::io::ErrorKind::Other, error.to_string())),
+			};
+		}
+		self.position += length as u64;
+		Ok(length)
+	}
+}
+
+impl<S: Read + Write> Seek for NbdClient<S> {
+	fn seek(&mut self, seek_from: SeekFrom) -> std::result::Result<u64, std::io::Error> {
+		let (base, offset) = match seek_from {
+			SeekFrom::Start(value) => {
+				self.position = value;
+				return Ok(self.position);
+			},
+			SeekFrom::Current(value) => (self.position, value),
+			SeekFrom::End(value) => (self.export_size, value),
+		};
+		if base as i64 + offset < 0 {
+			return Err(std::io::Error::new(std::io::ErrorKind::Other, ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION));
+		}
+		self.position = (base as i64 + offset) as u64;
+		Ok(self.position)
+	}
+}
+
+// performs the client side of the fixed newstyle handshake.
+fn handshake<S: Read + Write>(stream: &mut S) -> Result<()> {
+	let magic = stream.read_u64::<BigEndian>()?;
+	if magic != NBD_MAGIC {
+		return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+	}
+	let magic = stream.read_u64::<BigEndian>()?;
+	if magic != NBD_IHAVEOPT {
+		return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+	}
+	let handshake_flags = stream.read_u16::<BigEndian>()?;
+	let mut client_flags = 0;
+	if handshake_flags & NBD_FLAG_FIXED_NEWSTYLE != 0 {
+		client_flags |= NBD_FLAG_C_FIXED_NEWSTYLE;
+	}
+	if handshake_flags & NBD_FLAG_NO_ZEROES != 0 {
+		client_flags |= NBD_FLAG_C_NO_ZEROES;
+	}
+	stream.write_u32::<BigEndian>(client_flags)?;
+	Ok(())
+}
+
+fn write_option_request<W: Write>(stream: &mut W, option: u32, data: &[u8]) -> Result<()> {
+	stream.write_u64::<BigEndian>(NBD_IHAVEOPT)?;
+	stream.write_u32::<BigEndian>(option)?;
+	stream.write_u32::<BigEndian>(data.len() as u32)?;
+	stream.write_all(data)?;
+	stream.flush()?;
+	Ok(())
+}
+
+// reads the next option reply and returns the reply type and the reply data. Error replies will be returned as [ZffError].
+fn read_option_reply<R: Read>(stream: &mut R, option: u32) -> Result<(u32, Vec<u8>)> {
+	let magic = stream.read_u64::<BigEndian>()?;
+	if magic != NBD_OPTION_REPLY_MAGIC {
+		return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_INVALID_MAGIC}{magic:#x}")));
+	}
+	let _option = stream.read_u32::<BigEndian>()?;
+	let reply_type = stream.read_u32::<BigEndian>()?;
+	let length = stream.read_u32::<BigEndian>()?;
+	if length > NBD_MAX_OPTION_LENGTH {
+		return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_OPTION_TOO_LONG}{length}")));
+	}
+	let mut data = vec![0u8; length as usize];
+	stream.read_exact(&mut data)?;
+	if reply_type & NBD_REP_FLAG_ERROR != 0 {
+		return Err(ZffError::new(ZffErrorKind::NbdProtocolError, format!("{ERROR_NBD_OPTION_REPLY}{option} ({})", String::from_utf8_lossy(&data))));
+	}
+	Ok((reply_type, data))
+}
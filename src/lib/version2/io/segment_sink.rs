@@ -0,0 +1,149 @@
+// - STD
+use std::io::{Write, Seek, Cursor};
+use std::path::{Path, PathBuf};
+use std::fs::{File, OpenOptions, remove_file};
+use std::collections::BTreeMap;
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	ERROR_SEGMENT_SINK_MISSING_SEGMENT,
+	file_extension_from_segment_number,
+};
+
+/// A [SegmentSink] is the storage backend, which is used by the [ZffCreator](crate::ZffCreator) and the [ZffExtender](crate::ZffExtender)
+/// to store the generated segments. Each segment is identified by its segment number (the first segment always has the number 1).
+pub trait SegmentSink {
+	/// The writer type of a single segment.
+	type Segment: Write + Seek;
+
+	/// creates a new (empty) segment with the given segment number. An existing segment with the same number will be overwritten.
+	fn create_segment(&mut self, segment_number: u64) -> Result<Self::Segment>;
+
+	/// opens an existing segment with the given segment number to write additional data into it.
+	/// The position of the returned writer is the start of the segment.
+	/// # Error
+	/// Fails, if there is no segment with the given number.
+	fn open_segment(&mut self, segment_number: u64) -> Result<Self::Segment>;
+
+	/// finalizes the given segment. The [ZffCreator](crate::ZffCreator) and the [ZffExtender](crate::ZffExtender) call this method after
+	/// they have written all data of a segment (also after an existing segment was extended).
+	fn finalize_segment(&mut self, segment_number: u64, segment: Self::Segment) -> Result<()>;
+
+	/// deletes the segment with the given segment number. This is used, if a created segment was not needed.
+	fn delete_segment(&mut self, segment_number: u64) -> Result<()>;
+}
+
+/// A [SegmentSink] which stores the segments as files in the local filesystem.
+/// The segments are named by the given base path and the appropriate file extension (e.g. "my_container.z01", "my_container.z02", ...).
+#[derive(Debug, Clone)]
+pub struct FileSystemSegmentSink {
+	base_path: PathBuf,
+}
+
+impl FileSystemSegmentSink {
+	/// creates a new [FileSystemSegmentSink] for the given base path. An existing file extension of the base path will be replaced.
+	pub fn new<P: Into<PathBuf>>(base_path: P) -> FileSystemSegmentSink {
+		Self {
+			base_path: base_path.into(),
+		}
+	}
+
+	/// returns the path of the segment with the given segment number.
+	pub fn segment_path(&self, segment_number: u64) -> PathBuf {
+		let mut path = self.base_path.clone();
+		path.set_extension(file_extension_from_segment_number(segment_number));
+		path
+	}
+
+	/// returns the underlying base path.
+	pub fn base_path(&self) -> &Path {
+		&self.base_path
+	}
+}
+
+impl SegmentSink for FileSystemSegmentSink {
+	type Segment = File;
+
+	fn create_segment(&mut self, segment_number: u64) -> Result<File> {
+		Ok(File::create(self.segment_path(segment_number))?)
+	}
+
+	fn open_segment(&mut self, segment_number: u64) -> Result<File> {
+		Ok(OpenOptions::new().read(true).write(true).open(self.segment_path(segment_number))?)
+	}
+
+	fn finalize_segment(&mut self, _segment_number: u64, segment: File) -> Result<()> {
+		segment.sync_all()?;
+		Ok(())
+	}
+
+	fn delete_segment(&mut self, segment_number: u64) -> Result<()> {
+		remove_file(self.segment_path(segment_number))?;
+		Ok(())
+	}
+}
+
+/// A [SegmentSink] which holds all segments in memory.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySegmentSink {
+	segments: BTreeMap<u64, Vec<u8>>, //<segment number, segment data>
+}
+
+impl MemorySegmentSink {
+	/// creates a new, empty [MemorySegmentSink].
+	pub fn new() -> MemorySegmentSink {
+		Self::default()
+	}
+
+	/// creates a new [MemorySegmentSink] with the given segments (e.g. to extend an existing zff container in memory).
+	pub fn with_segments(segments: BTreeMap<u64, Vec<u8>>) -> MemorySegmentSink {
+		Self {
+			segments,
+		}
+	}
+
+	/// returns a reference to the data of the finalized segment with the given segment number (if available).
+	pub fn segment(&self, segment_number: u64) -> Option<&Vec<u8>> {
+		self.segments.get(&segment_number)
+	}
+
+	/// returns a reference to the map of all finalized segments.
+	pub fn segments(&self) -> &BTreeMap<u64, Vec<u8>> {
+		&self.segments
+	}
+
+	/// returns all finalized segments as readers, ordered by their segment number. The returned [Vec] can be used directly with the [ZffReader](crate::ZffReader).
+	pub fn into_readers(self) -> Vec<Cursor<Vec<u8>>> {
+		self.segments.into_values().map(Cursor::new).collect()
+	}
+}
+
+impl SegmentSink for MemorySegmentSink {
+	type Segment = Cursor<Vec<u8>>;
+
+	fn create_segment(&mut self, segment_number: u64) -> Result<Cursor<Vec<u8>>> {
+		self.segments.remove(&segment_number);
+		Ok(Cursor::new(Vec::new()))
+	}
+
+	fn open_segment(&mut self, segment_number: u64) -> Result<Cursor<Vec<u8>>> {
+		// the segment will be cloned (and not removed), so the data of the segment is not lost, if an error occurs before finalizing the segment.
+		match self.segments.get(&segment_number) {
+			Some(data) => Ok(Cursor::new(data.clone())),
+			None => Err(ZffError::new(ZffErrorKind::MissingSegment, format!("{ERROR_SEGMENT_SINK_MISSING_SEGMENT}{segment_number}"))),
+		}
+	}
+
+	fn finalize_segment(&mut self, segment_number: u64, segment: Cursor<Vec<u8>>) -> Result<()> {
+		self.segments.insert(segment_number, segment.into_inner());
+		Ok(())
+	}
+
+	fn delete_segment(&mut self, segment_number: u64) -> Result<()> {
+		self.segments.remove(&segment_number);
+		Ok(())
+	}
+}
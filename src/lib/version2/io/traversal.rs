@@ -0,0 +1,495 @@
+// - STD
+use std::path::{Path, PathBuf};
+use std::fs::{Metadata, read_link, read_dir, File};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::MetadataExt;
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	header::{FileHeader},
+	VirtualFile,
+	DEFAULT_HEADER_VERSION_FILE_HEADER,
+};
+
+use super::{
+	get_file_header,
+	open_file_for_check,
+};
+
+#[cfg(target_family = "unix")]
+use super::{
+	add_to_hardlink_map,
+};
+
+// - external
+use glob::{Pattern};
+
+/// The [TraversalOptions] can be used to control the directory walk of logical objects (see [ZffCreatorMetadataParams](crate::ZffCreatorMetadataParams)
+/// and [ZffExtender](crate::ZffExtender)). By default, all files will be dumped and symlinks will not be followed.
+#[derive(Debug, Clone, Default)]
+pub struct TraversalOptions {
+	include_patterns: Vec<Pattern>,
+	exclude_patterns: Vec<Pattern>,
+	max_depth: Option<usize>,
+	max_file_size: Option<u64>,
+	one_file_system: bool,
+	follow_symlinks: bool,
+}
+
+impl TraversalOptions {
+	/// returns new default [TraversalOptions].
+	pub fn new() -> TraversalOptions {
+		Self::default()
+	}
+
+	/// adds a glob pattern (e.g. "/home/\*/\*.txt"). If at least one include pattern is set, only files which match one of the include patterns will be dumped.
+	/// Directories will always be traversed (but can be excluded by an exclude pattern).
+	/// # Error
+	/// Fails, if the given pattern is not a valid glob pattern.
+	pub fn add_include_pattern<P: AsRef<str>>(&mut self, pattern: P) -> Result<()> {
+		self.include_patterns.push(parse_pattern(pattern.as_ref())?);
+		Ok(())
+	}
+
+	/// adds a glob pattern (e.g. "/proc" or "\*\*/.cache"). Files and directories (including their content), which match one of the exclude patterns, will be skipped.
+	/// # Error
+	/// Fails, if the given pattern is not a valid glob pattern.
+	pub fn add_exclude_pattern<P: AsRef<str>>(&mut self, pattern: P) -> Result<()> {
+		self.exclude_patterns.push(parse_pattern(pattern.as_ref())?);
+		Ok(())
+	}
+
+	/// sets the maximum depth of the traversal. The given input paths have the depth 0, their children have the depth 1, and so on.
+	pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+		self.max_depth = max_depth;
+	}
+
+	/// sets the maximum size of regular files. Larger files will be skipped.
+	pub fn set_max_file_size(&mut self, max_file_size: Option<u64>) {
+		self.max_file_size = max_file_size;
+	}
+
+	/// if set to true, files which lie on another filesystem (devid) than the appropriate input path will be skipped.
+	/// Directories on another filesystem (e.g. mount points) will be dumped, but their content will not be traversed (like find -xdev).
+	pub fn set_one_file_system(&mut self, one_file_system: bool) {
+		self.one_file_system = one_file_system;
+	}
+
+	/// if set to true, symlinks will be followed (the target will be dumped instead of the symlink).
+	pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) {
+		self.follow_symlinks = follow_symlinks;
+	}
+
+	/// returns the include patterns.
+	pub fn include_patterns(&self) -> Vec<&str> {
+		self.include_patterns.iter().map(|pattern| pattern.as_str()).collect()
+	}
+
+	/// returns the exclude patterns.
+	pub fn exclude_patterns(&self) -> Vec<&str> {
+		self.exclude_patterns.iter().map(|pattern| pattern.as_str()).collect()
+	}
+
+	/// returns the maximum depth, if set.
+	pub fn max_depth(&self) -> Option<usize> {
+		self.max_depth
+	}
+
+	/// returns the maximum file size, if set.
+	pub fn max_file_size(&self) -> Option<u64> {
+		self.max_file_size
+	}
+
+	/// returns true, if files on other filesystems will be skipped.
+	pub fn one_file_system(&self) -> bool {
+		self.one_file_system
+	}
+
+	/// returns true, if symlinks will be followed.
+	pub fn follow_symlinks(&self) -> bool {
+		self.follow_symlinks
+	}
+
+	// returns the metadata of the given path (follows symlinks, if set - dangling symlinks will be read as symlinks).
+	fn metadata(&self, path: &Path) -> std::io::Result<Metadata> {
+		if self.follow_symlinks {
+			if let Ok(metadata) = std::fs::metadata(path) {
+				return Ok(metadata);
+			}
+		}
+		std::fs::symlink_metadata(path)
+	}
+
+	// returns true, if the given entry should be skipped.
+	fn skip(&self, path: &Path, metadata: &Metadata, depth: usize, root_devid: Option<u64>) -> bool {
+		if self.exclude_patterns.iter().any(|pattern| pattern.matches_path(path)) {
+			return true;
+		}
+		if let Some(max_depth) = self.max_depth {
+			if depth > max_depth {
+				return true;
+			}
+		}
+		if metadata.is_dir() {
+			return false;
+		}
+		if !self.include_patterns.is_empty() && !self.include_patterns.iter().any(|pattern| pattern.matches_path(path)) {
+			return true;
+		}
+		if let Some(max_file_size) = self.max_file_size {
+			if metadata.is_file() && metadata.len() > max_file_size {
+				return true;
+			}
+		}
+		self.other_file_system(metadata, root_devid)
+	}
+
+	// returns true, if the given entry lies on another filesystem than the appropriate input path (and one_file_system is set).
+	fn other_file_system(&self, metadata: &Metadata, root_devid: Option<u64>) -> bool {
+		self.one_file_system && root_devid.is_some() && root_devid != devid(metadata)
+	}
+}
+
+fn parse_pattern(pattern: &str) -> Result<Pattern> {
+	match Pattern::new(pattern) {
+		Ok(pattern) => Ok(pattern),
+		Err(e) => Err(ZffError::new(ZffErrorKind::InvalidGlobPattern, e.to_string())),
+	}
+}
+
+#[cfg(target_family = "unix")]
+fn devid(metadata: &Metadata) -> Option<u64> {
+	Some(metadata.dev())
+}
+
+#[cfg(not(target_family = "unix"))]
+fn devid(_metadata: &Metadata) -> Option<u64> {
+	None
+}
+
+// returns the device id and inode of directories, which will be used to detect symlink loops.
+#[cfg(target_family = "unix")]
+fn directory_id(metadata: &Metadata) -> Option<(u64, u64)> {
+	Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(target_family = "unix"))]
+fn directory_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+	None
+}
+
+/// The [LogicalTree] contains all file informations of a logical object, which are needed by the
+/// [LogicalObjectEncoder](crate::version2::object::LogicalObjectEncoder). A [LogicalTree] can be built by using the [LogicalTreeBuilder].
+#[derive(Default)]
+pub struct LogicalTree {
+	pub(crate) files: Vec<(PathBuf, FileHeader)>,
+	pub(crate) root_dir_filenumbers: Vec<u64>,
+	pub(crate) symlink_real_paths: HashMap<u64, PathBuf>,
+	pub(crate) hardlink_map: HashMap<u64, HashMap<u64, u64>>, // <dev_id, <inode, file number>>
+	pub(crate) directory_children: HashMap<u64, Vec<u64>>, //<file number of directory, Vec<filenumber of child>>
+	pub(crate) virtual_files: HashMap<u64, Box<dyn VirtualFile>>, //<file number, virtual file>
+	pub(crate) unaccessable_files: Vec<String>,
+	pub(crate) skipped_files: Vec<String>,
+}
+
+impl LogicalTree {
+	/// returns the files (and the appropriate [FileHeader]s) of this tree.
+	pub fn files(&self) -> &Vec<(PathBuf, FileHeader)> {
+		&self.files
+	}
+
+	/// returns the file numbers of the files in the (virtual) root directory.
+	pub fn root_dir_filenumbers(&self) -> &Vec<u64> {
+		&self.root_dir_filenumbers
+	}
+
+	/// returns the real paths of the symlinks (<file number, real path>).
+	pub fn symlink_real_paths(&self) -> &HashMap<u64, PathBuf> {
+		&self.symlink_real_paths
+	}
+
+	/// returns the hardlink map (<dev_id, <inode, file number>>).
+	pub fn hardlink_map(&self) -> &HashMap<u64, HashMap<u64, u64>> {
+		&self.hardlink_map
+	}
+
+	/// returns the children of the directories (<file number of directory, Vec<file number of child>>).
+	pub fn directory_children(&self) -> &HashMap<u64, Vec<u64>> {
+		&self.directory_children
+	}
+
+	/// returns the file numbers of the virtual files (see [VirtualFile]).
+	pub fn virtual_file_numbers(&self) -> Vec<u64> {
+		self.virtual_files.keys().copied().collect()
+	}
+
+	/// returns the files, which were not accessable while building the tree.
+	pub fn unaccessable_files(&self) -> &Vec<String> {
+		&self.unaccessable_files
+	}
+
+	/// returns the files, which were skipped by the [TraversalOptions].
+	pub fn skipped_files(&self) -> &Vec<String> {
+		&self.skipped_files
+	}
+}
+
+/// The [LogicalTreeBuilder] can be used to build a [LogicalTree], which can be used to create a
+/// [LogicalObjectEncoder](crate::version2::object::LogicalObjectEncoder).
+/// The tree can be built by walking through local directories (see [add_root](LogicalTreeBuilder::add_root)) and/or
+/// by adding custom entries (e.g. a file list from a filesystem parser; see [add_entry](LogicalTreeBuilder::add_entry)) or
+/// [VirtualFile]s, which do not exist on the local filesystem (see [add_virtual_file](LogicalTreeBuilder::add_virtual_file)).
+/// The file numbers will be assigned by the builder, starting at 1 (the file number 0 is reserved for the virtual root directory).
+pub struct LogicalTreeBuilder {
+	options: TraversalOptions,
+	current_file_number: u64,
+	tree: LogicalTree,
+	directories_to_traversal: VecDeque<(PathBuf, u64, u64, usize, Option<u64>)>, // <(path, parent_file_number, current_file_number, depth, root devid)>
+	visited_directories: HashSet<(u64, u64)>,
+}
+
+impl LogicalTreeBuilder {
+	/// returns a new [LogicalTreeBuilder] with default [TraversalOptions].
+	pub fn new() -> LogicalTreeBuilder {
+		Self::with_options(TraversalOptions::default())
+	}
+
+	/// returns a new [LogicalTreeBuilder] with the given [TraversalOptions].
+	pub fn with_options(options: TraversalOptions) -> LogicalTreeBuilder {
+		Self {
+			options,
+			current_file_number: 0,
+			tree: LogicalTree::default(),
+			directories_to_traversal: VecDeque::new(),
+			visited_directories: HashSet::new(),
+		}
+	}
+
+	/// returns the next free file number. This number will be assigned to the next added file
+	/// (this could be useful to build the [FileHeader] for [add_entry](LogicalTreeBuilder::add_entry)).
+	pub fn next_file_number(&self) -> u64 {
+		self.current_file_number + 1
+	}
+
+	/// adds the given path (and, if the path is a directory, all of its content) to the virtual root directory of the tree.
+	/// The content of directories will be traversed by calling [build](LogicalTreeBuilder::build).
+	/// Unaccessable or skipped files will be noted in the appropriate lists of the [LogicalTree].
+	pub fn add_root<P: Into<PathBuf>>(&mut self, path: P) {
+		let path = path.into();
+		let metadata = match self.options.metadata(&path) {
+			Ok(metadata) => metadata,
+			Err(_) => {
+				self.current_file_number += 1;
+				self.tree.unaccessable_files.push(path.to_string_lossy().to_string());
+				return;
+			},
+		};
+		if self.options.skip(&path, &metadata, 0, None) {
+			self.tree.skipped_files.push(path.to_string_lossy().to_string());
+			return;
+		}
+		self.current_file_number += 1;
+		if open_file_for_check(&path, &metadata).is_err() {
+			if !metadata.is_symlink() {
+				self.tree.unaccessable_files.push(path.to_string_lossy().to_string());
+			};
+			return;
+		};
+		self.tree.root_dir_filenumbers.push(self.current_file_number);
+		if metadata.file_type().is_dir() {
+			if let Some(directory_id) = directory_id(&metadata) {
+				self.visited_directories.insert(directory_id);
+			}
+			self.directories_to_traversal.push_back((path, 0, self.current_file_number, 0, devid(&metadata)));
+		} else {
+			if metadata.file_type().is_symlink() {
+				self.add_symlink_real_path(&path);
+			}
+			self.add_local_file(path, &metadata, self.current_file_number, 0);
+		}
+	}
+
+	/// adds all of the given paths to the virtual root directory of the tree (see [add_root](LogicalTreeBuilder::add_root)).
+	pub fn add_roots<P: Into<PathBuf>>(&mut self, paths: Vec<P>) {
+		for path in paths {
+			self.add_root(path);
+		}
+	}
+
+	/// adds a custom entry to the tree. The given [FileHeader] must use the file number returned by [next_file_number](LogicalTreeBuilder::next_file_number)
+	/// and the file number of an already added directory (or 0, for the virtual root directory) as parent file number.
+	/// The file content will be read from the given path while encoding the object.
+	/// Returns the file number of the added entry.
+	/// # Error
+	/// Fails, if the file number of the [FileHeader] is not the next free file number.
+	pub fn add_entry<P: Into<PathBuf>>(&mut self, path: P, file_header: FileHeader, symlink_real_path: Option<PathBuf>) -> Result<u64> {
+		let file_number = file_header.file_number();
+		if file_number != self.next_file_number() {
+			return Err(ZffError::new(ZffErrorKind::InvalidFileNumber, file_number.to_string()));
+		}
+		self.push_entry(path.into(), file_header, symlink_real_path);
+		Ok(file_number)
+	}
+
+	// adds the entry with the next file number to the tree.
+	fn push_entry(&mut self, path: PathBuf, file_header: FileHeader, symlink_real_path: Option<PathBuf>) {
+		let file_number = file_header.file_number();
+		self.current_file_number = file_number;
+		let parent_file_number = file_header.parent_file_number();
+		if parent_file_number == 0 {
+			self.tree.root_dir_filenumbers.push(file_number);
+		} else {
+			self.tree.directory_children.entry(parent_file_number).or_default().push(file_number);
+		}
+		if let Some(symlink_real_path) = symlink_real_path {
+			self.tree.symlink_real_paths.insert(file_number, symlink_real_path);
+		}
+		self.tree.files.push((path, file_header));
+	}
+
+	/// adds the given [VirtualFile] to the tree. The content of the virtual file will be read by its [Read](std::io::Read) implementation
+	/// while encoding the object (the local filesystem will not be touched).
+	/// The parent file number must be the file number of an already added directory (or 0, for the virtual root directory).
+	/// Returns the file number of the added virtual file (which can be used as parent file number, if the virtual file is a directory).
+	pub fn add_virtual_file(&mut self, parent_file_number: u64, virtual_file: Box<dyn VirtualFile>) -> u64 {
+		let file_number = self.next_file_number();
+		let mut file_header = FileHeader::new(
+			DEFAULT_HEADER_VERSION_FILE_HEADER,
+			file_number,
+			virtual_file.file_type(),
+			virtual_file.filename(),
+			parent_file_number,
+			virtual_file.atime(),
+			virtual_file.mtime(),
+			virtual_file.ctime(),
+			virtual_file.btime(),
+			virtual_file.metadata_ext(),
+			virtual_file.xattrs());
+		if let Some((major, minor)) = virtual_file.device_number() {
+			file_header.set_device_number(major, minor);
+		}
+		let path = PathBuf::from(virtual_file.filename());
+		let symlink_target = virtual_file.symlink_target();
+		self.tree.virtual_files.insert(file_number, virtual_file);
+		self.push_entry(path, file_header, symlink_target);
+		file_number
+	}
+
+	/// walks through all subdirectories of the added roots and returns the appropriate [LogicalTree].
+	pub fn build(mut self) -> LogicalTree {
+		while let Some((current_dir, dir_parent_file_number, dir_current_file_number, depth, root_devid)) = self.directories_to_traversal.pop_front() {
+			self.traverse_directory(current_dir, dir_parent_file_number, dir_current_file_number, depth, root_devid);
+		}
+		self.tree
+	}
+
+	// adds the directory itself and all of its elements (subdirectories will be queued).
+	fn traverse_directory(&mut self, current_dir: PathBuf, dir_parent_file_number: u64, dir_current_file_number: u64, depth: usize, root_devid: Option<u64>) {
+		let element_iterator = match read_dir(&current_dir) {
+			Ok(iterator) => iterator,
+			Err(_) => {
+				self.tree.unaccessable_files.push(current_dir.to_string_lossy().to_string());
+				return;
+			}
+		};
+
+		let metadata = match self.options.metadata(&current_dir) {
+			Ok(metadata) => metadata,
+			Err(_) => {
+				self.tree.unaccessable_files.push(current_dir.to_string_lossy().to_string());
+				return;
+			},
+		};
+		if File::open(&current_dir).is_err() {
+			self.tree.unaccessable_files.push(current_dir.to_string_lossy().to_string());
+			return;
+		};
+		self.tree.directory_children.entry(dir_parent_file_number).or_default().push(dir_current_file_number);
+		if !self.add_local_file(current_dir.clone(), &metadata, dir_current_file_number, dir_parent_file_number) {
+			return;
+		}
+		let parent_file_number = dir_current_file_number;
+
+		// files in current folder
+		for inner_element in element_iterator {
+			let inner_element = match inner_element {
+				Ok(element) => element,
+				Err(e) => {
+					self.current_file_number += 1;
+					self.tree.unaccessable_files.push(e.to_string());
+					continue;
+				}
+			};
+			let path = inner_element.path();
+
+			let metadata = match self.options.metadata(&path) {
+				Ok(metadata) => metadata,
+				Err(_) => {
+					self.current_file_number += 1;
+					self.tree.unaccessable_files.push(current_dir.to_string_lossy().to_string());
+					continue;
+				},
+			};
+			if self.options.skip(&path, &metadata, depth + 1, root_devid) {
+				self.tree.skipped_files.push(path.to_string_lossy().to_string());
+				continue;
+			}
+			// symlink loops (only possible, if symlinks will be followed).
+			if metadata.is_dir() {
+				if let Some(directory_id) = directory_id(&metadata) {
+					if !self.visited_directories.insert(directory_id) {
+						self.tree.skipped_files.push(path.to_string_lossy().to_string());
+						continue;
+					}
+				}
+			}
+			self.current_file_number += 1;
+			if open_file_for_check(&path, &metadata).is_err() {
+				self.tree.unaccessable_files.push(path.to_string_lossy().to_string());
+				continue;
+			};
+			if metadata.file_type().is_dir() && self.options.other_file_system(&metadata, root_devid) {
+				self.tree.directory_children.entry(parent_file_number).or_default().push(self.current_file_number);
+				self.add_local_file(path, &metadata, self.current_file_number, parent_file_number);
+			} else if metadata.file_type().is_dir() {
+				self.directories_to_traversal.push_back((path, parent_file_number, self.current_file_number, depth + 1, root_devid));
+			} else {
+				self.tree.directory_children.entry(parent_file_number).or_default().push(self.current_file_number);
+				self.add_symlink_real_path(&path);
+				self.add_local_file(path, &metadata, self.current_file_number, parent_file_number);
+			}
+		}
+	}
+
+	fn add_symlink_real_path(&mut self, path: &Path) {
+		match read_link(path) {
+			Ok(symlink_real) => self.tree.symlink_real_paths.insert(self.current_file_number, symlink_real),
+			Err(_) => self.tree.symlink_real_paths.insert(self.current_file_number, PathBuf::from("")),
+		};
+	}
+
+	// adds the local file to the tree. Returns false, if the file header could not be created.
+	fn add_local_file(&mut self, path: PathBuf, metadata: &Metadata, file_number: u64, parent_file_number: u64) -> bool {
+		let file_header = match get_file_header(metadata, &path, file_number, parent_file_number) {
+			Ok(file_header) => file_header,
+			Err(_) => return false,
+		};
+
+		#[cfg(target_family = "unix")]
+		add_to_hardlink_map(&mut self.tree.hardlink_map, metadata, file_number);
+
+		self.tree.files.push((path, file_header));
+		true
+	}
+}
+
+impl Default for LogicalTreeBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,513 @@
+// - STD
+use std::io::{self, Read, Seek, SeekFrom, Cursor};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf, Component};
+use std::collections::{HashMap, HashSet};
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ZffErrorKind,
+	Object,
+	File,
+	ValueDecoder,
+	header::{FileType, FileHeader},
+	footer::{DataStream, FuzzyHashValue},
+};
+
+use crate::{
+	ERROR_VFS_PATH_NOT_FOUND,
+	ERROR_VFS_TOO_MANY_LINKS,
+	ERROR_VFS_NOT_A_DIRECTORY,
+	ERROR_VFS_NOT_A_SYMLINK,
+	ERROR_VFS_NOT_A_HARDLINK,
+	ERROR_VFS_INVALID_FILENAME,
+	ERROR_VFS_DIRECTORY_CYCLE,
+	ERROR_VFS_INVALID_HOLES,
+};
+
+#[cfg(target_os = "linux")]
+use crate::{
+	METADATA_EXT_KEY_MODE,
+};
+
+use super::{
+	ZffReader,
+};
+
+/// The file number of the virtual root directory of a logical object.
+pub const VFS_ROOT_FILE_NUMBER: u64 = 0;
+// the maximum number of links, which will be followed by a single path resolution.
+const MAX_FOLLOWED_LINKS: usize = 40;
+// the permissions of restored special files, if the mode of the file is not available.
+#[cfg(target_os = "linux")]
+const DEFAULT_SPECIAL_FILE_PERMISSIONS: u32 = 0o644;
+
+/// The [ZffVfs] provides a virtual filesystem view of a logical object of a [ZffReader].
+/// All files, which were dumped without a parent directory, lie in the virtual root directory ("/", file number 0).
+pub struct ZffVfs<'a, R: Read + Seek> {
+	reader: &'a mut ZffReader<R>,
+	object_number: u64,
+	files: HashMap<u64, File>, //<file number, file>
+	children: HashMap<u64, Vec<u64>>, //<file number of directory, file numbers of children (sorted by filename)>
+}
+
+impl<'a, R: Read + Seek> ZffVfs<'a, R> {
+	/// creates a new [ZffVfs] for the given logical object of the [ZffReader].
+	/// # Error
+	/// Fails, if the given object number does not exist or the object is not a logical object.
+	pub fn new(reader: &'a mut ZffReader<R>, object_number: u64) -> Result<ZffVfs<'a, R>> {
+		let files = match reader.object(object_number) {
+			Some(Object::Logical(object)) => object.files().clone(),
+			Some(Object::Physical(_)) => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, object_number.to_string())),
+			None => return Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		};
+		let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+		children.insert(VFS_ROOT_FILE_NUMBER, Vec::new());
+		for (file_number, file) in &files {
+			children.entry(file.parent()).or_default().push(*file_number);
+			if file.filetype() == FileType::Directory {
+				children.entry(*file_number).or_default();
+			}
+		}
+		for file_numbers in children.values_mut() {
+			file_numbers.sort_by(|a, b| files[a].header().filename().cmp(files[b].header().filename()).then(a.cmp(b)));
+		}
+		Ok(Self {
+			reader,
+			object_number,
+			files,
+			children,
+		})
+	}
+
+	/// returns the object number of the underlying logical object.
+	pub fn object_number(&self) -> u64 {
+		self.object_number
+	}
+
+	/// returns the [File] information (type, size, timestamps, extended metadata, ...) of the given file number.
+	pub fn stat(&self, file_number: u64) -> Result<&File> {
+		match self.files.get(&file_number) {
+			Some(file) => Ok(file),
+			None => Err(ZffError::new(ZffErrorKind::MissingFileNumber, file_number.to_string())),
+		}
+	}
+
+	/// returns the [File] information of the file at the given path (symlinks and hardlinks will not be followed).
+	pub fn stat_path<P: AsRef<Path>>(&mut self, path: P) -> Result<&File> {
+		let file_number = self.lookup(path)?;
+		self.stat(file_number)
+	}
+
+	/// returns true, if the given file number is a directory (the virtual root directory is also a directory).
+	pub fn is_dir(&self, file_number: u64) -> bool {
+		self.children.contains_key(&file_number)
+	}
+
+	/// returns the file numbers of all entries of the given directory, sorted by their filenames.
+	/// # Error
+	/// Fails, if the given file number is not a directory.
+	pub fn read_dir(&self, file_number: u64) -> Result<&[u64]> {
+		match self.children.get(&file_number) {
+			Some(children) => Ok(children),
+			None => Err(ZffError::new(ZffErrorKind::NotAvailableForFileType, format!("{ERROR_VFS_NOT_A_DIRECTORY}{file_number}"))),
+		}
+	}
+
+	/// returns the [File] information of all entries of the directory at the given path (symlinks to directories will be followed).
+	pub fn read_dir_path<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<&File>> {
+		let file_number = self.resolve(path)?;
+		let children = self.read_dir(file_number)?;
+		Ok(children.iter().map(|file_number| &self.files[file_number]).collect())
+	}
+
+	/// returns the path of the given file number (relative to the virtual root directory, e.g. "/dir/sub/file").
+	pub fn path(&self, file_number: u64) -> Result<PathBuf> {
+		let mut components = Vec::new();
+		let mut current_file_number = file_number;
+		while current_file_number != VFS_ROOT_FILE_NUMBER {
+			let file = self.stat(current_file_number)?;
+			components.push(file.header().filename());
+			current_file_number = file.parent();
+			if components.len() > self.files.len() {
+				return Err(ZffError::new(ZffErrorKind::PathNotFound, format!("{ERROR_VFS_PATH_NOT_FOUND}{file_number}")));
+			}
+		}
+		let mut path = PathBuf::from("/");
+		path.extend(components.iter().rev());
+		Ok(path)
+	}
+
+	/// returns the file number of the file at the given path. Relative paths are interpreted relative to the virtual root directory.
+	/// Symlinks in the middle of the path will be followed, a symlink at the end of the path will not be followed.
+	pub fn lookup<P: AsRef<Path>>(&mut self, path: P) -> Result<u64> {
+		let mut followed_links = 0;
+		self.lookup_from(VFS_ROOT_FILE_NUMBER, path.as_ref(), &mut followed_links)
+	}
+
+	/// returns the file number of the file at the given path. Symlinks and hardlinks will be followed (also at the end of the path).
+	pub fn resolve<P: AsRef<Path>>(&mut self, path: P) -> Result<u64> {
+		let mut followed_links = 0;
+		let file_number = self.lookup_from(VFS_ROOT_FILE_NUMBER, path.as_ref(), &mut followed_links)?;
+		self.follow_links(file_number, &mut followed_links)
+	}
+
+	fn lookup_from(&mut self, start_file_number: u64, path: &Path, followed_links: &mut usize) -> Result<u64> {
+		let mut current_file_number = start_file_number;
+		let mut components = path.components().peekable();
+		while let Some(component) = components.next() {
+			current_file_number = match component {
+				Component::RootDir | Component::Prefix(_) => VFS_ROOT_FILE_NUMBER,
+				Component::CurDir => current_file_number,
+				Component::ParentDir => match current_file_number {
+					VFS_ROOT_FILE_NUMBER => VFS_ROOT_FILE_NUMBER,
+					file_number => self.stat(file_number)?.parent(),
+				},
+				Component::Normal(name) => {
+					let directory = self.follow_links(current_file_number, followed_links)?;
+					let children = self.read_dir(directory)?;
+					let child = children.iter().find(|file_number| self.files[file_number].header().filename() == name.to_string_lossy());
+					match child {
+						Some(file_number) => *file_number,
+						None => return Err(ZffError::new(ZffErrorKind::PathNotFound, format!("{ERROR_VFS_PATH_NOT_FOUND}{}", path.display()))),
+					}
+				},
+			};
+			// symlinks in the middle of the path have to be followed.
+			if components.peek().is_some() {
+				current_file_number = self.follow_links(current_file_number, followed_links)?;
+			}
+		}
+		Ok(current_file_number)
+	}
+
+	// follows symlinks and hardlinks, until the file number is neither a symlink nor a hardlink.
+	fn follow_links(&mut self, file_number: u64, followed_links: &mut usize) -> Result<u64> {
+		let mut current_file_number = file_number;
+		loop {
+			if current_file_number == VFS_ROOT_FILE_NUMBER {
+				return Ok(current_file_number);
+			}
+			current_file_number = match self.stat(current_file_number)?.filetype() {
+				FileType::Symlink => {
+					let target = self.read_link(current_file_number)?;
+					let parent = self.stat(current_file_number)?.parent();
+					*followed_links += 1;
+					if *followed_links > MAX_FOLLOWED_LINKS {
+						return Err(ZffError::new(ZffErrorKind::PathNotFound, format!("{ERROR_VFS_TOO_MANY_LINKS}{}", target.display())));
+					}
+					self.lookup_from(parent, &target, followed_links)?
+				},
+				FileType::Hardlink => self.read_hardlink(current_file_number)?,
+				_ => return Ok(current_file_number),
+			};
+		}
+	}
+
+	/// returns the target of the given symlink (as it was stored, the target may lie outside of the dumped files).
+	/// # Error
+	/// Fails, if the given file number is not a symlink.
+	pub fn read_link(&mut self, file_number: u64) -> Result<PathBuf> {
+		if self.stat(file_number)?.filetype() != FileType::Symlink {
+			return Err(ZffError::new(ZffErrorKind::NotAvailableForFileType, format!("{ERROR_VFS_NOT_A_SYMLINK}{file_number}")));
+		}
+		let data = self.read_raw(file_number)?;
+		Ok(PathBuf::from(String::decode_directly(&mut Cursor::new(data))?))
+	}
+
+	/// returns the target of the given symlink (the symlink will be followed, until the target is neither a symlink nor a hardlink).
+	/// Absolute targets will be interpreted relative to the virtual root directory.
+	/// # Error
+	/// Fails, if the target is not available in this logical object.
+	pub fn resolve_symlink(&mut self, file_number: u64) -> Result<u64> {
+		if self.stat(file_number)?.filetype() != FileType::Symlink {
+			return Err(ZffError::new(ZffErrorKind::NotAvailableForFileType, format!("{ERROR_VFS_NOT_A_SYMLINK}{file_number}")));
+		}
+		let mut followed_links = 0;
+		self.follow_links(file_number, &mut followed_links)
+	}
+
+	/// returns the file number of the file, which is referenced by the given hardlink.
+	/// # Error
+	/// Fails, if the given file number is not a hardlink.
+	pub fn read_hardlink(&mut self, file_number: u64) -> Result<u64> {
+		if self.stat(file_number)?.filetype() != FileType::Hardlink {
+			return Err(ZffError::new(ZffErrorKind::NotAvailableForFileType, format!("{ERROR_VFS_NOT_A_HARDLINK}{file_number}")));
+		}
+		let data = self.read_raw(file_number)?;
+		u64::decode_directly(&mut Cursor::new(data))
+	}
+
+	/// reads the content of the given file. Hardlinks will be resolved.
+	pub fn read(&mut self, file_number: u64) -> Result<Vec<u8>> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		self.read_raw(file_number)
+	}
+
+	/// reads the content of the file at the given path. Symlinks and hardlinks will be followed.
+	pub fn read_path<P: AsRef<Path>>(&mut self, path: P) -> Result<Vec<u8>> {
+		let file_number = self.resolve(path)?;
+		self.read(file_number)
+	}
+
+	/// reads the content of the given file at the given offset into the buffer. Hardlinks will be resolved.
+	/// Returns the number of read bytes.
+	pub fn read_at(&mut self, file_number: u64, offset: u64, buffer: &mut [u8]) -> Result<usize> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		self.reader.set_reader_logical_object_file(self.object_number, file_number)?;
+		self.reader.seek(SeekFrom::Start(offset))?;
+		let mut read_bytes = 0;
+		while read_bytes < buffer.len() {
+			match self.reader.read(&mut buffer[read_bytes..])? {
+				0 => break,
+				bytes => read_bytes += bytes,
+			}
+		}
+		Ok(read_bytes)
+	}
+
+	/// returns the named (secondary) data streams of the given file. Hardlinks will be resolved.
+	pub fn data_streams(&mut self, file_number: u64) -> Result<Vec<DataStream>> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		Ok(self.reader.data_streams(self.object_number, file_number)?.clone())
+	}
+
+	/// returns the fuzzy (similarity) hashes of the given file. Hardlinks will be resolved.
+	pub fn fuzzy_hashes(&mut self, file_number: u64) -> Result<Vec<FuzzyHashValue>> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		Ok(self.reader.fuzzy_hashes(self.object_number, file_number)?.clone())
+	}
+
+	/// reads the content of the named data stream of the given file. Hardlinks will be resolved.
+	pub fn read_data_stream(&mut self, file_number: u64, stream_name: &str) -> Result<Vec<u8>> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		let mut data = Vec::new();
+		self.reader.open_data_stream(self.object_number, file_number, stream_name)?.read_to_end(&mut data)?;
+		Ok(data)
+	}
+
+	/// reads the content of the named data stream of the given file at the given offset into the buffer. Hardlinks will be resolved.
+	/// Returns the number of read bytes.
+	pub fn read_data_stream_at(&mut self, file_number: u64, stream_name: &str, offset: u64, buffer: &mut [u8]) -> Result<usize> {
+		let file_number = match self.stat(file_number)?.filetype() {
+			FileType::Hardlink => self.read_hardlink(file_number)?,
+			_ => file_number,
+		};
+		self.reader.read_data_stream_at(self.object_number, file_number, stream_name, offset, buffer)
+	}
+
+	// reads the raw data of the given file number.
+	fn read_raw(&mut self, file_number: u64) -> Result<Vec<u8>> {
+		self.reader.set_reader_logical_object_file(self.object_number, file_number)?;
+		self.reader.seek(SeekFrom::Start(0))?;
+		let mut data = Vec::new();
+		self.reader.read_to_end(&mut data)?;
+		Ok(data)
+	}
+
+	/// extracts the given file (directories recursively) into the given destination directory.
+	/// If the file number is the virtual root directory, all files of the logical object will be extracted.
+	/// Hardlinks will be restored as hardlinks, if the appropriate target was already extracted by this call (otherwise, the content will be written as a regular file).
+	/// Fifos and devices will be recreated (creating devices needs appropriate privileges), sockets will be skipped.
+	/// The extended attributes will be restored, if possible (restoring e.g. "security.\*" or "trusted.\*" attributes needs appropriate privileges;
+	/// attributes, which could not be restored, will be skipped).
+	/// # Error
+	/// Fails, if an entry already exists in the destination directory (existing entries will never be overwritten or followed),
+	/// if a filename is not a single path component (e.g. "..", an absolute path or a name containing a path separator)
+	/// or if a directory is its own (indirect) child.
+	pub fn extract<P: AsRef<Path>>(&mut self, file_number: u64, destination: P) -> Result<()> {
+		let mut extracted_files = HashMap::new();
+		let mut visited_directories = HashSet::new();
+		if file_number == VFS_ROOT_FILE_NUMBER {
+			for child in self.read_dir(VFS_ROOT_FILE_NUMBER)?.to_vec() {
+				self.extract_file(child, destination.as_ref(), &mut extracted_files, &mut visited_directories)?;
+			}
+			return Ok(());
+		}
+		self.extract_file(file_number, destination.as_ref(), &mut extracted_files, &mut visited_directories)
+	}
+
+	fn extract_file(
+		&mut self,
+		file_number: u64,
+		destination: &Path,
+		extracted_files: &mut HashMap<u64, PathBuf>,
+		visited_directories: &mut HashSet<u64>) -> Result<()> {
+		let file = self.stat(file_number)?;
+		let filename = file.header().filename();
+		// the filename is untrusted: it must not leave the destination directory.
+		match Path::new(filename).components().collect::<Vec<_>>()[..] {
+			[Component::Normal(component)] if component == filename => (),
+			_ => return Err(ZffError::new(ZffErrorKind::MalformedHeader, format!("{ERROR_VFS_INVALID_FILENAME}{filename}"))),
+		}
+		let path = destination.join(filename);
+		let xattrs = file.header().xattrs().clone();
+		match file.filetype() {
+			FileType::Directory => {
+				if !visited_directories.insert(file_number) {
+					return Err(ZffError::new(ZffErrorKind::MalformedHeader, format!("{ERROR_VFS_DIRECTORY_CYCLE}{file_number}")));
+				}
+				fs::create_dir(&path)?;
+				for child in self.read_dir(file_number)?.to_vec() {
+					self.extract_file(child, &path, extracted_files, visited_directories)?;
+				}
+			},
+			FileType::Symlink => {
+				let target = self.read_link(file_number)?;
+				#[cfg(target_family = "unix")]
+				std::os::unix::fs::symlink(target, &path)?;
+				#[cfg(target_family = "windows")]
+				std::os::windows::fs::symlink_file(target, &path)?;
+			},
+			FileType::Hardlink => {
+				let target_file_number = self.read_hardlink(file_number)?;
+				match extracted_files.get(&target_file_number) {
+					Some(target) => fs::hard_link(target, &path)?,
+					None => {
+						self.extract_content(target_file_number, &path)?;
+						extracted_files.insert(target_file_number, path.clone());
+					},
+				}
+			},
+			FileType::Socket => return Ok(()),
+			FileType::Fifo | FileType::BlockDevice | FileType::CharDevice => {
+				create_special_file(&path, file.header())?;
+			},
+			_ => {
+				self.extract_content(file_number, &path)?;
+				extracted_files.insert(file_number, path.clone());
+			},
+		}
+		restore_xattrs(&path, &xattrs);
+		Ok(())
+	}
+
+	// writes the content of the given file number to a new file at the given path (an existing file or symlink will not be overwritten).
+	// Holes of sparse files will be recreated.
+	fn extract_content(&mut self, file_number: u64, path: &Path) -> Result<()> {
+		let footer = self.stat(file_number)?.footer();
+		let holes = footer.holes().clone();
+		let length_of_data = footer.length_of_data();
+		// the holes are untrusted: they have to be sorted, non-overlapping and within the file.
+		let mut offset = 0;
+		for (hole_offset, hole_length) in &holes {
+			match hole_offset.checked_add(*hole_length) {
+				Some(hole_end) if *hole_offset >= offset && hole_end <= length_of_data => offset = hole_end,
+				_ => return Err(ZffError::new(ZffErrorKind::HeaderDecodeError, format!("{ERROR_VFS_INVALID_HOLES}{file_number}"))),
+			}
+		}
+		let mut output = OpenOptions::new().write(true).create_new(true).open(path)?;
+		self.reader.set_reader_logical_object_file(self.object_number, file_number)?;
+		let mut offset = 0;
+		for (hole_offset, hole_length) in holes {
+			self.reader.seek(SeekFrom::Start(offset))?;
+			io::copy(&mut Read::by_ref(self.reader).take(hole_offset - offset), &mut output)?;
+			output.seek(SeekFrom::Start(hole_offset + hole_length))?;
+			offset = hole_offset + hole_length;
+		}
+		self.reader.seek(SeekFrom::Start(offset))?;
+		io::copy(self.reader, &mut output)?;
+		output.set_len(length_of_data)?;
+		Ok(())
+	}
+
+	/// returns an iterator, which walks recursively (depth-first) through the whole tree, beginning at the virtual root directory.
+	pub fn walk(&self) -> VfsWalk<'_> {
+		self.walk_from(VFS_ROOT_FILE_NUMBER)
+	}
+
+	/// returns an iterator, which walks recursively (depth-first) through the tree, beginning at the given directory.
+	/// The given directory itself is not part of the iteration. Symlinks will not be followed.
+	pub fn walk_from(&self, file_number: u64) -> VfsWalk<'_> {
+		let mut stack = Vec::new();
+		if let Ok(children) = self.read_dir(file_number) {
+			let base_path = self.path(file_number).unwrap_or_else(|_| PathBuf::from("/"));
+			for child in children.iter().rev() {
+				stack.push((base_path.join(self.files[child].header().filename()), *child));
+			}
+		}
+		VfsWalk {
+			files: &self.files,
+			children: &self.children,
+			stack,
+		}
+	}
+}
+
+// creates a fifo or a device file at the given path with the permissions of the stored mode of the file.
+// If the mode is not available, the permissions will be set to 0o644 (the file is created with respect to the umask).
+#[cfg(target_os = "linux")]
+fn create_special_file(path: &Path, file_header: &FileHeader) -> Result<()> {
+	use nix::sys::stat::{mknod, makedev, SFlag, Mode};
+
+	let kind = match file_header.file_type() {
+		FileType::Fifo => SFlag::S_IFIFO,
+		FileType::BlockDevice => SFlag::S_IFBLK,
+		FileType::CharDevice => SFlag::S_IFCHR,
+		_ => return Ok(()),
+	};
+	let (major, minor) = file_header.device_number().unwrap_or((0, 0));
+	let mode = file_header.metadata_ext().get(METADATA_EXT_KEY_MODE).and_then(|mode| mode.parse::<u32>().ok());
+	let permissions = Mode::from_bits_truncate(mode.unwrap_or(DEFAULT_SPECIAL_FILE_PERMISSIONS) & 0o7777);
+	if let Err(errno) = mknod(path, kind, permissions, makedev(major as u64, minor as u64)) {
+		return Err(io::Error::from(errno).into());
+	}
+	Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_special_file(_path: &Path, _file_header: &FileHeader) -> Result<()> {
+	Ok(())
+}
+
+// restores the given extended attributes (symlinks will not be followed). Attributes, which could not be restored, will be skipped.
+#[cfg(target_family = "unix")]
+fn restore_xattrs(path: &Path, xattrs: &HashMap<Vec<u8>, Vec<u8>>) {
+	use std::ffi::OsStr;
+	use std::os::unix::ffi::OsStrExt;
+
+	for (name, value) in xattrs {
+		let _ = xattr::set(path, OsStr::from_bytes(name), value);
+	}
+}
+
+#[cfg(not(target_family = "unix"))]
+fn restore_xattrs(_path: &Path, _xattrs: &HashMap<Vec<u8>, Vec<u8>>) {}
+
+/// An iterator over all files of a [ZffVfs] (see [ZffVfs::walk]). Returns the path and the [File] information of each file.
+pub struct VfsWalk<'v> {
+	files: &'v HashMap<u64, File>,
+	children: &'v HashMap<u64, Vec<u64>>,
+	stack: Vec<(PathBuf, u64)>,
+}
+
+impl<'v> Iterator for VfsWalk<'v> {
+	type Item = (PathBuf, &'v File);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (path, file_number) = self.stack.pop()?;
+		if let Some(children) = self.children.get(&file_number) {
+			for child in children.iter().rev() {
+				self.stack.push((path.join(self.files[child].header().filename()), *child));
+			}
+		}
+		Some((path, &self.files[&file_number]))
+	}
+}
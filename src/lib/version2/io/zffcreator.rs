@@ -1,52 +1,62 @@
 // - STD
 use std::io::{Read, Write, Seek, SeekFrom, Cursor};
 use std::path::{PathBuf};
-use std::fs::{File, OpenOptions, remove_file, read_link, read_dir};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap};
 
 
 // - internal
 use crate::{
 	Result,
 	HashType,
+	FuzzyHashType,
 	HeaderCoding,
 	ZffError,
 	ZffErrorKind,
-	file_extension_next_value,
-	file_extension_previous_value,
 	DEFAULT_HEADER_VERSION_SEGMENT_HEADER,
 	DEFAULT_FOOTER_VERSION_SEGMENT_FOOTER,
 	DEFAULT_FOOTER_VERSION_MAIN_FOOTER,
-	FILE_EXTENSION_FIRST_VALUE,
+	ERROR_STREAM_SEGMENT_SIZE_TO_SMALL,
+	ERROR_STREAM_MULTIPLE_OBJECTS,
+	ERROR_STREAM_LIMITED_SEGMENT_SIZE,
+	ERROR_RECOMPRESSION_LOGICAL_OBJECT,
+	ERROR_RECOMPRESSION_MISSING_HASH_TYPE,
+	ERROR_RECOMPRESSION_HASH_MISMATCH,
 };
 use crate::{
-	header::{ObjectHeader, MainHeader, SegmentHeader, ChunkHeader},
+	header::{ObjectHeader, MainHeader, SegmentHeader, ChunkHeader, CompressionHeader, HashHeader},
 	footer::{SegmentFooter, MainFooter},
 	version2::{
-		object::{ObjectEncoder, PhysicalObjectEncoder, LogicalObjectEncoder},
+		object::{Object, ObjectEncoder, PhysicalObjectEncoder, LogicalObjectEncoder},
 	}
 };
 
 use super::{
-	get_file_header,
+	LogicalTreeBuilder,
+	LogicalTree,
+	TraversalOptions,
 	ObjectEncoderInformation,
-};
-
-#[cfg(target_family = "unix")]
-use super::{
-	add_to_hardlink_map,
+	SegmentSink,
+	FileSystemSegmentSink,
+	ZffReader,
 };
 
 // - external
 use ed25519_dalek::{Keypair};
 
 /// struct which contains the metadata of the appropriate creator (e.g. like encryption key, main header, ...).
+/// The params can also be used to extend an existing container (see [ZffExtender::with_params](crate::ZffExtender::with_params)).
 pub struct ZffCreatorMetadataParams {
-	encryption_key: Option<Vec<u8>>,
-	signature_key: Option<Keypair>,
-	main_header: MainHeader,
-	header_encryption: bool,
-	description_notes: Option<String>,
+	pub(super) encryption_key: Option<Vec<u8>>,
+	pub(super) signature_key: Option<Keypair>,
+	pub(super) main_header: MainHeader,
+	pub(super) header_encryption: bool,
+	pub(super) description_notes: Option<String>,
+	pub(super) traversal_options: TraversalOptions,
+	pub(super) deduplication: bool,
+	pub(super) adaptive_compression: bool,
+	pub(super) piecewise_hash_size: u64,
+	pub(super) merkle_tree_hash_type: Option<HashType>,
+	pub(super) fuzzy_hash_types: Vec<FuzzyHashType>,
 }
 
 impl ZffCreatorMetadataParams {
@@ -63,8 +73,44 @@ impl ZffCreatorMetadataParams {
 			main_header,
 			header_encryption,
 			description_notes,
+			traversal_options: TraversalOptions::default(),
+			deduplication: false,
+			adaptive_compression: false,
+			piecewise_hash_size: 0,
+			merkle_tree_hash_type: None,
+			fuzzy_hash_types: Vec::new(),
 		}
 	}
+
+	/// sets the [TraversalOptions], which will be used for the directory walk of the logical objects.
+	pub fn set_traversal_options(&mut self, traversal_options: TraversalOptions) {
+		self.traversal_options = traversal_options;
+	}
+
+	/// enables or disables the whole-file deduplication of the logical objects (see [LogicalObjectEncoder::set_deduplication]).
+	pub fn set_deduplication(&mut self, deduplication: bool) {
+		self.deduplication = deduplication;
+	}
+
+	/// enables or disables the adaptive compression of all objects (see [PhysicalObjectEncoder::set_adaptive_compression]).
+	pub fn set_adaptive_compression(&mut self, adaptive_compression: bool) {
+		self.adaptive_compression = adaptive_compression;
+	}
+
+	/// sets the hash window size (in bytes) of the piecewise hashing of all physical objects (see [PhysicalObjectEncoder::set_piecewise_hash_size]).
+	pub fn set_piecewise_hash_size(&mut self, piecewise_hash_size: u64) {
+		self.piecewise_hash_size = piecewise_hash_size;
+	}
+
+	/// enables (or disables) the merkle tree over the chunks of all physical objects with the given hash type (see [PhysicalObjectEncoder::set_merkle_tree]).
+	pub fn set_merkle_tree(&mut self, hash_type: Option<HashType>) {
+		self.merkle_tree_hash_type = hash_type;
+	}
+
+	/// sets the fuzzy hash types, which will be calculated for each regular file of the logical objects (see [LogicalObjectEncoder::set_fuzzy_hash_types]).
+	pub fn set_fuzzy_hash_types(&mut self, fuzzy_hash_types: Vec<FuzzyHashType>) {
+		self.fuzzy_hash_types = fuzzy_hash_types;
+	}
 }
 
 /// The ZffCreator can be used to create a new zff container by the given files/values.
@@ -73,12 +119,14 @@ pub struct ZffCreator<R: Read> {
 	object_encoder: ObjectEncoder<R>, //the current object encoder
 	written_object_header: bool,
 	unaccessable_files: Vec<String>,
+	skipped_files: Vec<String>,
 	output_filenpath: String,
 	current_segment_no: u64,
-	last_accepted_segment_filepath: PathBuf,
+	last_accepted_segment_no: u64,
 	description_notes: Option<String>,
 	object_header_segment_numbers: HashMap<u64, u64>, //<object_number, segment_no>
 	object_footer_segment_numbers: HashMap<u64, u64>, //<object_number, segment_no>
+	expected_hash_headers: HashMap<u64, HashHeader>, //<object_number, hash header of the source object> (only used for recompressed objects)
 }
 
 impl<R: Read> ZffCreator<R> {
@@ -89,13 +137,30 @@ impl<R: Read> ZffCreator<R> {
 		hash_types: Vec<HashType>,
 		output_filenpath: O,
 		params: ZffCreatorMetadataParams) -> Result<ZffCreator<R>>{
+		let mut logical_trees = HashMap::new();
+		for (object_header, input_files) in logical_objects {
+			let mut tree_builder = LogicalTreeBuilder::with_options(params.traversal_options.clone());
+			tree_builder.add_roots(input_files);
+			logical_trees.insert(object_header, tree_builder.build());
+		}
+		Self::with_logical_trees(physical_objects, logical_trees, hash_types, output_filenpath, params)
+	}
+
+	/// Creates a new [ZffCreator] instance for the given values. The logical objects will be created by the given [LogicalTree]s
+	/// (see [LogicalTreeBuilder]), which could also contain [VirtualFile](crate::VirtualFile)s.
+	pub fn with_logical_trees<O: Into<String>>(
+		physical_objects: HashMap<ObjectHeader, R>, // <ObjectHeader, input_data stream>
+		logical_trees: HashMap<ObjectHeader, LogicalTree>, //<ObjectHeader, logical tree>
+		hash_types: Vec<HashType>,
+		output_filenpath: O,
+		params: ZffCreatorMetadataParams) -> Result<ZffCreator<R>>{
 
 		let initial_chunk_number = 1;
 		let signature_key_bytes = params.signature_key.map(|keypair| keypair.to_bytes().to_vec());
 
 		let mut object_encoder_vec = Vec::new();
 		for (object_header, input_data) in physical_objects {
-			let object_encoder = PhysicalObjectEncoder::new(
+			let mut object_encoder = PhysicalObjectEncoder::new(
 				object_header,
 				input_data,
 				hash_types.clone(),
@@ -104,172 +169,32 @@ impl<R: Read> ZffCreator<R> {
 				params.main_header.clone(),
 				initial_chunk_number,
 				params.header_encryption)?;
-			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Physical(object_encoder), false, Vec::new()));
+			object_encoder.set_adaptive_compression(params.adaptive_compression);
+			object_encoder.set_piecewise_hash_size(params.piecewise_hash_size);
+			object_encoder.set_merkle_tree(params.merkle_tree_hash_type.clone());
+			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Physical(object_encoder), false, Vec::new(), Vec::new()));
 		}
-		for (object_header, input_files) in logical_objects {
-			let mut current_file_number = 0;
-			let mut parent_file_number = 0;
-			let mut hardlink_map = HashMap::new();
-			let mut unaccessable_files = Vec::new();
-			let mut directories_to_traversal = VecDeque::new(); // <(path, parent_file_number, current_file_number)>
-			let mut files = Vec::new();
-			let mut symlink_real_paths = HashMap::new();
-			let mut directory_children = HashMap::<u64, Vec<u64>>::new(); //<file number of directory, Vec<filenumber of child>>
-			let mut root_dir_filenumbers = Vec::new();
-
-			//files in virtual root folder
-			for path in input_files {
-				current_file_number += 1;
-				let metadata = match std::fs::symlink_metadata(&path) {
-					Ok(metadata) => metadata,
-					Err(_) => {
-						unaccessable_files.push(path.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				match File::open(&path) {
-					Ok(_) => (),
-					Err(_) => {
-						if !metadata.is_symlink() {
-							unaccessable_files.push(path.to_string_lossy().to_string());
-						};
-						continue;
-					},
-				};
-				root_dir_filenumbers.push(current_file_number);
-				if metadata.file_type().is_dir() {
-					directories_to_traversal.push_back((path, parent_file_number, current_file_number));
-				} else {
-					if metadata.file_type().is_symlink() {
-						match read_link(&path) {
-							Ok(symlink_real) => symlink_real_paths.insert(current_file_number, symlink_real),
-							Err(_) => symlink_real_paths.insert(current_file_number, PathBuf::from("")),
-						};
-					}
-					let file_header = match get_file_header(&metadata, &path, current_file_number, parent_file_number) {
-						Ok(file_header) => file_header,
-						Err(_) => continue,
-					};
-
-					#[cfg(target_family = "unix")]
-					add_to_hardlink_map(&mut hardlink_map, &metadata, current_file_number);
-
-					files.push((path.clone(), file_header));
-				}
-			}
+		for (object_header, mut tree) in logical_trees {
+			let unaccessable_files = std::mem::take(&mut tree.unaccessable_files);
+			let skipped_files = std::mem::take(&mut tree.skipped_files);
 
-			// - files in subfolders
-			while let Some((current_dir, dir_parent_file_number, dir_current_file_number)) = directories_to_traversal.pop_front() {
-  				let element_iterator = match read_dir(&current_dir) {
-					Ok(iterator) => iterator,
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					}
-				};
-
-				let metadata = match std::fs::symlink_metadata(&current_dir) {
-					Ok(metadata) => metadata,
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				match File::open(&current_dir) {
-					Ok(_) => (),
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				if let Some(files_vec) = directory_children.get_mut(&dir_parent_file_number) {
-					files_vec.push(dir_current_file_number);
-				} else {
-					directory_children.insert(dir_parent_file_number, Vec::new());
-					directory_children.get_mut(&dir_parent_file_number).unwrap().push(dir_current_file_number);
-				};
-
-				parent_file_number = dir_current_file_number;
-				let file_header = match get_file_header(&metadata, &current_dir, dir_current_file_number, dir_parent_file_number) {
-					Ok(file_header) => file_header,
-					Err(_) => continue,
-				};
-				#[cfg(target_family = "unix")]
-				add_to_hardlink_map(&mut hardlink_map, &metadata, dir_current_file_number);
-				
-				files.push((current_dir.clone(), file_header));
-
-				// files in current folder
-				for inner_element in element_iterator {
-					current_file_number += 1;
-					let inner_element = match inner_element {
-						Ok(element) => element,
-						Err(e) => {
-							unaccessable_files.push(e.to_string());
-							continue;
-						}
-					};
-
-					let metadata = match std::fs::symlink_metadata(&inner_element.path()) {
-						Ok(metadata) => metadata,
-						Err(_) => {
-							unaccessable_files.push(current_dir.to_string_lossy().to_string());
-							continue;
-						},
-					};
-					match File::open(&inner_element.path()) {
-						Ok(_) => (),
-						Err(_) => {
-							unaccessable_files.push(inner_element.path().to_string_lossy().to_string());
-							continue;
-						},
-					};
-					if metadata.file_type().is_dir() {
-						directories_to_traversal.push_back((inner_element.path(), parent_file_number, current_file_number));
-					} else {
-						if let Some(files_vec) = directory_children.get_mut(&parent_file_number) {
-							files_vec.push(current_file_number);
-						} else {
-							directory_children.insert(parent_file_number, Vec::new());
-							directory_children.get_mut(&parent_file_number).unwrap().push(current_file_number);
-						};
-
-						match read_link(inner_element.path()) {
-							Ok(symlink_real) => symlink_real_paths.insert(current_file_number, symlink_real),
-							Err(_) => symlink_real_paths.insert(current_file_number, PathBuf::from("")),
-						};
-						let path = inner_element.path().clone();
-						let file_header = match get_file_header(&metadata, &path, current_file_number, parent_file_number) {
-							Ok(file_header) => file_header,
-							Err(_) => continue,
-						};
-						
-						#[cfg(target_family = "unix")]
-						add_to_hardlink_map(&mut hardlink_map, &metadata, current_file_number);
-
-						files.push((inner_element.path().clone(), file_header));
-					}
-				}
-			}
-
-			let object_encoder = LogicalObjectEncoder::new(
+			let mut object_encoder = LogicalObjectEncoder::with_tree(
 				object_header,
-				files,
-				root_dir_filenumbers,
+				tree,
 				hash_types.clone(),
 				params.encryption_key.clone(),
 				signature_key_bytes.clone(),
 				params.main_header.clone(),
-				symlink_real_paths,
-				hardlink_map,
-				directory_children,
 				initial_chunk_number,
 				params.header_encryption)?;
-			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Logical(Box::new(object_encoder)), false, unaccessable_files));
+			object_encoder.set_deduplication(params.deduplication)?;
+			object_encoder.set_adaptive_compression(params.adaptive_compression);
+			object_encoder.set_fuzzy_hash_types(params.fuzzy_hash_types.clone());
+			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Logical(Box::new(object_encoder)), false, unaccessable_files, skipped_files));
 		}
 		object_encoder_vec.reverse();
-		let (object_encoder, written_object_header, unaccessable_files) = match object_encoder_vec.pop() {
-			Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files),
+		let (object_encoder, written_object_header, unaccessable_files, skipped_files) = match object_encoder_vec.pop() {
+			Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files, creator_obj_encoder.skipped_files),
 			None => return Err(ZffError::new(ZffErrorKind::NoObjectsLeft, "")),
 		};
 
@@ -278,22 +203,25 @@ impl<R: Read> ZffCreator<R> {
 			object_encoder,
 			written_object_header,
 			unaccessable_files,
+			skipped_files,
 			output_filenpath: output_filenpath.into(),
 			current_segment_no: 1, //initial segment number should always be 1.
-			last_accepted_segment_filepath: PathBuf::new(),
+			last_accepted_segment_no: 1,
 			description_notes: params.description_notes,
 			object_header_segment_numbers: HashMap::new(),
 			object_footer_segment_numbers: HashMap::new(),
+			expected_hash_headers: HashMap::new(),
 		})
 	}
 
-	fn write_next_segment<W: Write + Seek>(
+	// writes the next segment into the given output and returns the number of written bytes and true, if the
+	// current object was finished in this segment.
+	fn write_next_segment<W: Write>(
 	&mut self,
 	output: &mut W,
 	seek_value: u64, // The seek value is a value of bytes you need to skip (e.g. the main_header, the object_header, ...)
-	) -> Result<u64> {
+	) -> Result<(u64, bool)> {
 		let mut eof = false; //true, if EOF of input stream is reached.
-		output.seek(SeekFrom::Start(seek_value))?;
 		let mut written_bytes: u64 = 0;
 		let target_chunk_size = self.object_encoder.main_header().chunk_size();
 		let target_segment_size = self.object_encoder.main_header().segment_size();
@@ -303,10 +231,11 @@ impl<R: Read> ZffCreator<R> {
 			DEFAULT_HEADER_VERSION_SEGMENT_HEADER,
 			self.object_encoder.main_header().unique_identifier(),
 			self.current_segment_no);
+		let encoded_segment_header = segment_header.encode_directly();
 
 		//check if the segment size is to small
 		if (seek_value as usize +
-			segment_header.encode_directly().len() +
+			encoded_segment_header.len() +
 			self.object_encoder.get_encoded_header().len() +
 			target_chunk_size) > self.object_encoder.main_header().segment_size() as usize {
 	        
@@ -314,7 +243,8 @@ impl<R: Read> ZffCreator<R> {
 	    };
 
 		//write segment header
-		written_bytes += output.write(&segment_header.encode_directly())? as u64;
+		output.write_all(&encoded_segment_header)?;
+		written_bytes += encoded_segment_header.len() as u64;
 
 		//prepare segment footer
 		let mut segment_footer = SegmentFooter::new_empty(DEFAULT_FOOTER_VERSION_SEGMENT_FOOTER);	
@@ -323,7 +253,9 @@ impl<R: Read> ZffCreator<R> {
 		if !self.written_object_header {
 			self.object_header_segment_numbers.insert(self.object_encoder.obj_number(), self.current_segment_no);
 			segment_footer.add_object_header_offset(self.object_encoder.obj_number(), seek_value + written_bytes);
-			written_bytes += output.write(&self.object_encoder.get_encoded_header())? as u64;
+			let encoded_object_header = self.object_encoder.get_encoded_header();
+			output.write_all(&encoded_object_header)?;
+			written_bytes += encoded_object_header.len() as u64;
 			self.written_object_header = true;
 		};
 
@@ -332,13 +264,8 @@ impl<R: Read> ZffCreator<R> {
 		loop {
 			if (written_bytes +
 				segment_footer_len +
-				target_chunk_size as u64) > target_segment_size-seek_value as u64 {
-				
-				if written_bytes == segment_header.encode_directly().len() as u64 {
-					return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
-				} else {
-					break;
-				}
+				target_chunk_size as u64) > target_segment_size-seek_value {
+				break;
 			};
 			let current_offset = seek_value + written_bytes;
 			let current_chunk_number = self.object_encoder.current_chunk_number();
@@ -346,16 +273,21 @@ impl<R: Read> ZffCreator<R> {
 				Ok(data) => data,
 				Err(e) => match e.get_kind() {
 					ZffErrorKind::ReadEOF => {
-						if written_bytes == segment_header.encode_directly().len() as u64 {
-							return Err(e);
-						} else {
-							//write the appropriate object footer and break the loop
-							self.object_footer_segment_numbers.insert(self.object_encoder.obj_number(), self.current_segment_no);
-							segment_footer.add_object_footer_offset(self.object_encoder.obj_number(), seek_value + written_bytes);
-							written_bytes += output.write(&self.object_encoder.get_encoded_footer())? as u64;
-							eof = true;
-							break;
+						// the subtree of this segment has to be finalized before the merkle root will be calculated.
+						for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+							segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+						}
+						if let Some(expected_hash_header) = self.expected_hash_headers.get(&self.object_encoder.obj_number()) {
+							check_hash_header(expected_hash_header, self.object_encoder.hash_header())?;
 						}
+						//write the appropriate object footer and break the loop
+						self.object_footer_segment_numbers.insert(self.object_encoder.obj_number(), self.current_segment_no);
+						segment_footer.add_object_footer_offset(self.object_encoder.obj_number(), seek_value + written_bytes);
+						let encoded_object_footer = self.object_encoder.get_encoded_footer();
+						output.write_all(&encoded_object_footer)?;
+						written_bytes += encoded_object_footer.len() as u64;
+						eof = true;
+						break;
 					},
 					ZffErrorKind::InterruptedInputStream => {
 						break;
@@ -363,82 +295,228 @@ impl<R: Read> ZffCreator<R> {
 					_ => return Err(e),
 				},
 			};
-			written_bytes += output.write(&data)? as u64;
+			output.write_all(&data)?;
+			written_bytes += data.len() as u64;
 			let mut data_cursor = Cursor::new(&data);
 			if ChunkHeader::check_identifier(&mut data_cursor) {
 				segment_footer.add_chunk_offset(current_chunk_number, current_offset);
 				segment_footer_len += 16;
+				// the merkle leaf: chunk number, length of the hash and the hash itself.
+				if let Some(hash_type) = self.object_encoder.merkle_tree_hash_type() {
+					segment_footer_len += 16 + hash_type.default_len() as u64 / 8;
+				}
 			};
 		}
+		for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+			segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+		}
 
 		// finish the segment footer and write the encoded footer into the Writer.
 		segment_footer.set_footer_offset(seek_value + written_bytes);
-		if eof {
+		if eof && self.object_encoder_vec.is_empty() {
 			let main_footer = MainFooter::new(DEFAULT_FOOTER_VERSION_MAIN_FOOTER, self.current_segment_no, self.object_header_segment_numbers.clone(), self.object_footer_segment_numbers.clone(), self.description_notes.clone(), 0);
 			segment_footer.set_length_of_segment(seek_value + written_bytes + segment_footer.encode_directly().len() as u64 + main_footer.encode_directly().len() as u64);
 		} else {
 			segment_footer.set_length_of_segment(seek_value + written_bytes + segment_footer.encode_directly().len() as u64);
 		}
-			
-		written_bytes += output.write(&segment_footer.encode_directly())? as u64;
-		Ok(written_bytes)
+		let encoded_segment_footer = segment_footer.encode_directly();
+		output.write_all(&encoded_segment_footer)?;
+		written_bytes += encoded_segment_footer.len() as u64;
+		Ok((written_bytes, eof))
+	}
+
+	// switches to the next object encoder. Returns false, if there are no objects left.
+	fn next_object_encoder(&mut self) -> bool {
+		match self.object_encoder_vec.pop() {
+			Some(creator_obj_encoder) => {
+				self.object_encoder = creator_obj_encoder.object_encoder;
+				self.written_object_header = creator_obj_encoder.written_object_header;
+				self.unaccessable_files = creator_obj_encoder.unaccessable_files;
+				self.skipped_files = creator_obj_encoder.skipped_files;
+				true
+			},
+			None => false,
+		}
+	}
+
+	fn main_footer(&self, footer_offset: u64) -> MainFooter {
+		MainFooter::new(
+			DEFAULT_FOOTER_VERSION_MAIN_FOOTER,
+			self.current_segment_no,
+			self.object_header_segment_numbers.clone(),
+			self.object_footer_segment_numbers.clone(),
+			self.description_notes.clone(),
+			footer_offset)
 	}
 
 	/// generates the appropriate .zXX files.
+	/// Each object starts at the beginning of a new segment (the segment of the previous object will be finished by its object footer),
+	/// so no segment has to be rewritten or deleted after it was created (which allows non-seekable outputs, see [ZffCreator::generate_streams]).
 	pub fn generate_files(&mut self) -> Result<()> {
-		let mut first_segment_filename = PathBuf::from(&self.output_filenpath);
-	    let mut file_extension = String::from(FILE_EXTENSION_FIRST_VALUE);
-	    first_segment_filename.set_extension(&file_extension);
-	    self.last_accepted_segment_filepath = first_segment_filename.clone();
-	    let mut output_file = File::create(&first_segment_filename)?;
+		let mut sink = FileSystemSegmentSink::new(&self.output_filenpath);
+		self.generate_segments(&mut sink)
+	}
+
+	/// generates the segments of the zff container and stores them by using the given [SegmentSink].
+	pub fn generate_segments<S: SegmentSink>(&mut self, sink: &mut S) -> Result<()> {
+		let mut output = sink.create_segment(self.current_segment_no)?;
+		self.last_accepted_segment_no = self.current_segment_no;
 		let encoded_main_header = self.object_encoder.main_header().encode_directly();
 
-	    output_file.write_all(&encoded_main_header)?;
-	    let mut main_footer_start_offset = self.write_next_segment(&mut output_file, encoded_main_header.len() as u64)? +
-	    								   encoded_main_header.len() as u64;
-
-	    let mut seek_value = 0;
-	    loop {
-	    	self.current_segment_no += 1;
-	    	file_extension = file_extension_next_value(&file_extension)?;
-	    	let mut segment_filename = PathBuf::from(&self.output_filenpath);
-	    	segment_filename.set_extension(&file_extension);
-	    	let mut output_file = File::create(&segment_filename)?;
-	    	main_footer_start_offset = match self.write_next_segment(&mut output_file, seek_value) {
-	    		Ok(written_bytes) => {
-	    			seek_value = 0;
-	    			written_bytes
-	    		},
-	    		Err(e) => match e.get_kind() {
-	    			ZffErrorKind::ReadEOF => {
-	    				remove_file(&segment_filename)?;
-	    				let (object_encoder, written_object_header, unaccessable_files) = match self.object_encoder_vec.pop() {
-	    					Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files),
-	    					None => break,
-	    				};
-	    				self.object_encoder = object_encoder;
-	    				self.written_object_header = written_object_header;
-	    				self.unaccessable_files = unaccessable_files;
-	    				self.current_segment_no -=1;
-	    				file_extension = file_extension_previous_value(&file_extension)?;
-	    				seek_value = main_footer_start_offset;
-	    				main_footer_start_offset
-	    			},
-	    			_ => return Err(e),
-	    		},
-	    	};
-	    	self.last_accepted_segment_filepath = segment_filename.clone();
-	    }
-
-	    let main_footer = MainFooter::new(DEFAULT_FOOTER_VERSION_MAIN_FOOTER, self.current_segment_no-1, self.object_header_segment_numbers.clone(), self.object_footer_segment_numbers.clone(), self.description_notes.clone(), main_footer_start_offset);
-	    let mut output_file = OpenOptions::new().write(true).append(true).open(&self.last_accepted_segment_filepath)?;
-	    output_file.write_all(&main_footer.encode_directly())?;
-
-	    Ok(())
+		output.write_all(&encoded_main_header)?;
+		let (written_bytes, mut eof) = self.write_next_segment(&mut output, encoded_main_header.len() as u64)?;
+		let mut main_footer_start_offset = written_bytes + encoded_main_header.len() as u64;
+		sink.finalize_segment(self.current_segment_no, output)?;
+
+		loop {
+			if eof && !self.next_object_encoder() {
+				break;
+			}
+			self.current_segment_no += 1;
+			let mut output = sink.create_segment(self.current_segment_no)?;
+			let (written_bytes, object_finished) = self.write_next_segment(&mut output, 0)?;
+			main_footer_start_offset = written_bytes;
+			eof = object_finished;
+			sink.finalize_segment(self.current_segment_no, output)?;
+			self.last_accepted_segment_no = self.current_segment_no;
+		}
+
+		let main_footer = self.main_footer(main_footer_start_offset);
+		let mut output = sink.open_segment(self.last_accepted_segment_no)?;
+		output.seek(SeekFrom::End(0))?;
+		output.write_all(&main_footer.encode_directly())?;
+		sink.finalize_segment(self.last_accepted_segment_no, output)?;
+
+		Ok(())
+	}
+
+	/// generates the zff container as a single segment and writes it into the given output (e.g. stdout, a pipe or a network socket).
+	/// The output does not need to be seekable, all offsets are calculated while writing.
+	/// A single stream supports exactly one object and an unlimited segment size (a segment size of 0, see [MainHeader::segment_size]),
+	/// because each object starts a new segment. Use [ZffCreator::generate_streams] to write a multi segment container into several streams.
+	/// # Error
+	/// Fails with [ZffErrorKind::UnsupportedStreamOutput] (before anything was written into the output), if the creator contains
+	/// more than one object or if the segment size is limited.
+	pub fn generate_stream<W: Write>(&mut self, output: W) -> Result<()> {
+		if !self.object_encoder_vec.is_empty() {
+			let number_of_objects = self.object_encoder_vec.len() + 1;
+			return Err(ZffError::new(ZffErrorKind::UnsupportedStreamOutput, format!("{ERROR_STREAM_MULTIPLE_OBJECTS}{number_of_objects}")));
+		}
+		let segment_size = self.object_encoder.main_header().segment_size();
+		if segment_size != u64::MAX {
+			return Err(ZffError::new(ZffErrorKind::UnsupportedStreamOutput, format!("{ERROR_STREAM_LIMITED_SEGMENT_SIZE}{segment_size}")));
+		}
+		let mut output = Some(output);
+		self.generate_streams(|segment_number| match output.take() {
+			Some(output) => Ok(output),
+			None => Err(ZffError::new(ZffErrorKind::UnsupportedStreamOutput, format!("{ERROR_STREAM_SEGMENT_SIZE_TO_SMALL}{segment_number}"))),
+		})
+	}
+
+	/// generates the zff container and writes each segment into its own output, which will be created by the given function
+	/// (the function will be called with the appropriate segment number, starting by 1).
+	/// The outputs do not need to be seekable, all offsets are calculated while writing.
+	/// The main footer will be appended to the last output.
+	pub fn generate_streams<W, F>(&mut self, mut next_output: F) -> Result<()>
+	where
+		W: Write,
+		F: FnMut(u64) -> Result<W>,
+	{
+		let mut output = next_output(self.current_segment_no)?;
+		self.last_accepted_segment_no = self.current_segment_no;
+		let encoded_main_header = self.object_encoder.main_header().encode_directly();
+
+		output.write_all(&encoded_main_header)?;
+		let (written_bytes, mut eof) = self.write_next_segment(&mut output, encoded_main_header.len() as u64)?;
+		let mut main_footer_start_offset = written_bytes + encoded_main_header.len() as u64;
+
+		loop {
+			if eof && !self.next_object_encoder() {
+				break;
+			}
+			output.flush()?;
+			self.current_segment_no += 1;
+			output = next_output(self.current_segment_no)?;
+			let (written_bytes, object_finished) = self.write_next_segment(&mut output, 0)?;
+			main_footer_start_offset = written_bytes;
+			eof = object_finished;
+			self.last_accepted_segment_no = self.current_segment_no;
+		}
+
+		let main_footer = self.main_footer(main_footer_start_offset);
+		output.write_all(&main_footer.encode_directly())?;
+		output.flush()?;
+
+		Ok(())
 	}
 
 	/// Returns a reference of the unaccessable files.
 	pub fn unaccessable_files(&self) -> &Vec<String> {
 		&self.unaccessable_files
 	}
+
+	/// Returns a reference of the files, which were skipped by the [TraversalOptions].
+	pub fn skipped_files(&self) -> &Vec<String> {
+		&self.skipped_files
+	}
+}
+
+impl<'a, R: Read + Seek> ZffCreator<&'a mut ZffReader<R>> {
+	/// Creates a new [ZffCreator] instance, which recompresses the given physical object of the given [ZffReader] with the given [CompressionHeader]
+	/// (e.g. to create an archival copy by using [CompressionAlgorithm::Xz](crate::CompressionAlgorithm::Xz)).
+	/// Only physical objects can be recompressed: logical objects can not be rebuilt losslessly from a [ZffReader]
+	/// (e.g. [VirtualFile](crate::VirtualFile)s do not support hardlinks), so they have to be acquired again from their source.
+	/// The object header of the new object is a copy of the original object header (with the new compression header).
+	/// If the original object is encrypted and no encryption key is set in the given [ZffCreatorMetadataParams], the (decrypted) encryption key
+	/// of the original object will be used, so the recompressed object can be decrypted by the original password.
+	/// Before the object footer will be written, the calculated hash values of the new object will be compared with the appropriate hash values of the original object.
+	/// # Error
+	/// Fails, if the given object number does not exist, the object is a logical object or none of the given hash types is stored in the original object.
+	/// The generation of the segments fails with [ZffErrorKind::HashMismatch], if a hash value of the new object does not match the original hash value.
+	pub fn recompress_physical_object<O: Into<String>>(
+		reader: &'a mut ZffReader<R>,
+		object_number: u64,
+		compression_header: CompressionHeader,
+		hash_types: Vec<HashType>,
+		output_filenpath: O,
+		mut params: ZffCreatorMetadataParams) -> Result<ZffCreator<&'a mut ZffReader<R>>> {
+		let (mut object_header, encryption_key, expected_hash_header) = match reader.object(object_number) {
+			Some(object @ Object::Physical(physical_object)) => (object.header().clone(), object.encryption_key().cloned(), physical_object.footer().hash_header().clone()),
+			Some(Object::Logical(_)) => return Err(ZffError::new(ZffErrorKind::MismatchObjectType, format!("{ERROR_RECOMPRESSION_LOGICAL_OBJECT}{object_number}"))),
+			None => return Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		};
+		if !expected_hash_header.hash_values().iter().any(|hash_value| hash_types.contains(hash_value.hash_type())) {
+			return Err(ZffError::new(ZffErrorKind::MissingHashType, format!("{ERROR_RECOMPRESSION_MISSING_HASH_TYPE}{hash_types:?}")));
+		}
+		object_header.set_compression_header(compression_header);
+		if params.encryption_key.is_none() && object_header.encryption_header().is_some() {
+			params.encryption_key = encryption_key;
+		}
+		reader.set_reader_physical_object(object_number)?;
+		reader.seek(SeekFrom::Start(0))?;
+
+		let object_number = object_header.object_number();
+		let mut physical_objects = HashMap::new();
+		physical_objects.insert(object_header, reader);
+		let mut creator = Self::with_logical_trees(physical_objects, HashMap::new(), hash_types, output_filenpath, params)?;
+		creator.expected_hash_headers.insert(object_number, expected_hash_header);
+		Ok(creator)
+	}
+}
+
+// compares the calculated hash values with the expected hash values of the same hash type.
+fn check_hash_header(expected: &HashHeader, calculated: Option<HashHeader>) -> Result<()> {
+	let calculated = match calculated {
+		Some(calculated) => calculated,
+		None => return Ok(()),
+	};
+	for calculated_value in calculated.hash_values() {
+		let expected_value = expected.hash_values().iter().find(|hash_value| hash_value.hash_type() == calculated_value.hash_type());
+		if let Some(expected_value) = expected_value {
+			if expected_value.hash() != calculated_value.hash() {
+				return Err(ZffError::new(ZffErrorKind::HashMismatch, format!("{ERROR_RECOMPRESSION_HASH_MISMATCH}{}", calculated_value.hash_type())));
+			}
+		}
+	}
+	Ok(())
 }
\ No newline at end of file
@@ -1,8 +1,8 @@
 // - STD
 use std::io::{Read, Write, Seek, SeekFrom, Cursor};
 use std::path::{PathBuf};
-use std::fs::{File, OpenOptions, remove_file, read_link, read_dir};
-use std::collections::{HashMap, VecDeque};
+use std::fs::{File};
+use std::collections::{HashMap};
 
 
 // - internal
@@ -13,9 +13,8 @@ use crate::{
 	ZffError,
 	ZffErrorKind,
 	ValueDecoder,
-	file_extension_next_value,
-	file_extension_previous_value,
 	DEFAULT_HEADER_VERSION_SEGMENT_HEADER,
+	DEFAULT_HEADER_VERSION_MAIN_HEADER,
 	DEFAULT_FOOTER_VERSION_SEGMENT_FOOTER,
 	ERROR_MISSING_SEGMENT_MAIN_FOOTER,
 	ERROR_MISMATCH_ZFF_VERSION,
@@ -31,13 +30,11 @@ use crate::{
 };
 
 use super::{
-	get_file_header,
+	LogicalTreeBuilder,
+	ZffCreatorMetadataParams,
 	ObjectEncoderInformation,
-};
-
-#[cfg(target_family = "unix")]
-use super::{
-	add_to_hardlink_map,
+	SegmentSink,
+	FileSystemSegmentSink,
 };
 
 // - external
@@ -51,8 +48,9 @@ pub struct ZffExtender<R: Read> {
 	object_encoder: ObjectEncoder<R>, //the current object encoder
 	written_object_header: bool,
 	unaccessable_files: Vec<String>,
+	skipped_files: Vec<String>,
 	current_segment_no: u64,
-	last_accepted_segment_filepath: PathBuf,
+	last_accepted_segment_no: u64,
 	last_segment_footer: SegmentFooter,
 	main_footer: MainFooter,
 }
@@ -66,6 +64,20 @@ impl<R: Read> ZffExtender<R> {
 		encryption_key: Option<Vec<u8>>,
 		signature_key: Option<Keypair>,
 		header_encryption: bool) -> Result<ZffExtender<R>>{
+		// the main header of the params will be ignored by the extender.
+		let main_header = MainHeader::new(DEFAULT_HEADER_VERSION_MAIN_HEADER, 0, 0, 0);
+		let params = ZffCreatorMetadataParams::with_data(encryption_key, signature_key, main_header, header_encryption, None);
+		Self::with_params(files_to_extend, physical_objects, logical_objects, hash_types, params)
+	}
+
+	/// Creates a new [ZffExtender] instance with the given [ZffCreatorMetadataParams] (e.g. to set the [TraversalOptions](crate::version2::io::TraversalOptions)
+	/// by using [ZffCreatorMetadataParams::set_traversal_options]).
+	/// The main header and the description notes of the params will be ignored: the main header of the existing container will be used.
+	pub fn with_params(files_to_extend: Vec<PathBuf>,
+		physical_objects: HashMap<ObjectHeader, R>, // <ObjectHeader, input_data stream>
+		logical_objects: HashMap<ObjectHeader, Vec<PathBuf>>, //<ObjectHeader, input_files>
+		hash_types: Vec<HashType>,
+		params: ZffCreatorMetadataParams) -> Result<ZffExtender<R>>{
 		let mut main_footer = None;
 		let mut main_header = None;
 		let mut last_segment = PathBuf::new();
@@ -152,193 +164,52 @@ impl<R: Read> ZffExtender<R> {
 			None => return Err(ZffError::new(ZffErrorKind::MissingSegment, ERROR_MISSING_SEGMENT_MAIN_FOOTER))
 		};
 			
-		let signature_key_bytes = signature_key.map(|keypair| keypair.to_bytes().to_vec());
+		let signature_key_bytes = params.signature_key.map(|keypair| keypair.to_bytes().to_vec());
 
 		let mut object_encoder_vec = Vec::new();
 		for (mut object_header, input_data) in physical_objects {
 			object_header.set_object_number(object_number);
 			object_number += 1;
-			let object_encoder = PhysicalObjectEncoder::new(
+			let mut object_encoder = PhysicalObjectEncoder::new(
 				object_header,
 				input_data,
 				hash_types.clone(),
-				encryption_key.clone(),
+				params.encryption_key.clone(),
 				signature_key_bytes.clone(),
 				main_header.clone(),
 				initial_chunk_number,
-				header_encryption)?;
-			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Physical(object_encoder), false, Vec::new()));
+				params.header_encryption)?;
+			object_encoder.set_adaptive_compression(params.adaptive_compression);
+			object_encoder.set_piecewise_hash_size(params.piecewise_hash_size);
+			object_encoder.set_merkle_tree(params.merkle_tree_hash_type.clone());
+			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Physical(object_encoder), false, Vec::new(), Vec::new()));
 		}
 		for (mut object_header, input_files) in logical_objects {
 			object_header.set_object_number(object_number);
 			object_number += 1;
-			let mut current_file_number = 1;
-			let mut parent_file_number = 0;
-			let mut hardlink_map = HashMap::new();
-			let mut unaccessable_files = Vec::new();
-			let mut directories_to_traversal = VecDeque::new();
-			let mut files = Vec::new();
-			let mut symlink_real_paths = HashMap::new();
-			let mut directory_children = HashMap::<u64, Vec<u64>>::new(); //<file number of directory, Vec<filenumber of child>>
-			let mut root_dir_filenumbers = Vec::new();
-
-			//files in virtual root folder
-			for path in input_files {
-				current_file_number += 1;
-				let metadata = match std::fs::symlink_metadata(&path) {
-					Ok(metadata) => metadata,
-					Err(_) => {
-						unaccessable_files.push(path.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				match File::open(&path) {
-					Ok(_) => (),
-					Err(_) => {
-						if !metadata.is_symlink() {
-							unaccessable_files.push(path.to_string_lossy().to_string());
-						};
-						continue;
-					},
-				};
-				root_dir_filenumbers.push(current_file_number);
-				if metadata.file_type().is_dir() {
-					directories_to_traversal.push_back((path, parent_file_number, current_file_number));
-				} else {
-					if metadata.file_type().is_symlink() {
-						match read_link(&path) {
-							Ok(symlink_real) => symlink_real_paths.insert(current_file_number, symlink_real),
-							Err(_) => symlink_real_paths.insert(current_file_number, PathBuf::from("")),
-						};
-					}
-					let file_header = match get_file_header(&metadata, &path, current_file_number, parent_file_number) {
-						Ok(file_header) => file_header,
-						Err(_) => continue,
-					};
-
-					#[cfg(target_family = "unix")]
-					add_to_hardlink_map(&mut hardlink_map, &metadata, current_file_number);
-					
-					files.push((path.clone(), file_header));
-				}
-			}
-
-			// - files in subfolders
-			while let Some((current_dir, dir_parent_file_number, dir_current_file_number)) = directories_to_traversal.pop_front() {
-				let mut inner_dir_elements = VecDeque::new();
-  				let element_iterator = match read_dir(&current_dir) {
-					Ok(iterator) => iterator,
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					}
-				};
-
-				let metadata = match std::fs::symlink_metadata(&current_dir) {
-					Ok(metadata) => metadata,
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				match File::open(&current_dir) {
-					Ok(_) => (),
-					Err(_) => {
-						unaccessable_files.push(current_dir.to_string_lossy().to_string());
-						continue;
-					},
-				};
-				if let Some(files_vec) = directory_children.get_mut(&dir_parent_file_number) {
-					files_vec.push(dir_current_file_number);
-				} else {
-					directory_children.insert(dir_parent_file_number, Vec::new());
-					directory_children.get_mut(&dir_parent_file_number).unwrap().push(dir_current_file_number);
-				};
-
-				parent_file_number = dir_current_file_number;
-				let file_header = match get_file_header(&metadata, &current_dir, dir_current_file_number, dir_parent_file_number) {
-					Ok(file_header) => file_header,
-					Err(_) => continue,
-				};
-				#[cfg(target_family = "unix")]
-				add_to_hardlink_map(&mut hardlink_map, &metadata, dir_current_file_number);
-				
-				let element_iterator = element_iterator.peekable();
-				files.push((current_dir.clone(), file_header));
-
-				// files in current folder
-				for inner_element in element_iterator {
-					current_file_number += 1;
-					let inner_element = match inner_element {
-						Ok(element) => element,
-						Err(e) => {
-							unaccessable_files.push(e.to_string());
-							continue;
-						}
-					};
+			let mut tree_builder = LogicalTreeBuilder::with_options(params.traversal_options.clone());
+			tree_builder.add_roots(input_files);
+			let mut tree = tree_builder.build();
+			let unaccessable_files = std::mem::take(&mut tree.unaccessable_files);
+			let skipped_files = std::mem::take(&mut tree.skipped_files);
 
-					let metadata = match std::fs::symlink_metadata(&inner_element.path()) {
-						Ok(metadata) => metadata,
-						Err(_) => {
-							unaccessable_files.push(current_dir.to_string_lossy().to_string());
-							continue;
-						},
-					};
-					match File::open(&inner_element.path()) {
-						Ok(_) => (),
-						Err(_) => {
-							unaccessable_files.push(inner_element.path().to_string_lossy().to_string());
-							continue;
-						},
-					};
-					if metadata.file_type().is_dir() {
-						directories_to_traversal.push_back((inner_element.path(), parent_file_number, current_file_number));
-					} else {
-						if let Some(files_vec) = directory_children.get_mut(&parent_file_number) {
-							files_vec.push(current_file_number);
-						} else {
-							directory_children.insert(parent_file_number, Vec::new());
-							directory_children.get_mut(&parent_file_number).unwrap().push(current_file_number);
-						};
-
-						match read_link(inner_element.path()) {
-							Ok(symlink_real) => symlink_real_paths.insert(current_file_number, symlink_real),
-							Err(_) => symlink_real_paths.insert(current_file_number, PathBuf::from("")),
-						};
-						let path = inner_element.path().clone();
-						let file_header = match get_file_header(&metadata, &path, current_file_number, parent_file_number) {
-							Ok(file_header) => file_header,
-							Err(_) => continue,
-						};
-
-						#[cfg(target_family = "unix")]
-						add_to_hardlink_map(&mut hardlink_map, &metadata, current_file_number);
-						
-						files.push((inner_element.path().clone(), file_header));
-					}
-
-					directories_to_traversal.append(&mut inner_dir_elements);
-				}
-			}
-
-			let object_encoder = LogicalObjectEncoder::new(
+			let mut object_encoder = LogicalObjectEncoder::with_tree(
 				object_header,
-				files,
-				root_dir_filenumbers,
+				tree,
 				hash_types.clone(),
-				encryption_key.clone(),
+				params.encryption_key.clone(),
 				signature_key_bytes.clone(),
 				main_header.clone(),
-				symlink_real_paths,
-				hardlink_map,
-				directory_children,
 				initial_chunk_number,
-				header_encryption)?;
-			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Logical(Box::new(object_encoder)), false, unaccessable_files));
+				params.header_encryption)?;
+			object_encoder.set_deduplication(params.deduplication)?;
+			object_encoder.set_adaptive_compression(params.adaptive_compression);
+			object_encoder.set_fuzzy_hash_types(params.fuzzy_hash_types.clone());
+			object_encoder_vec.push(ObjectEncoderInformation::with_data(ObjectEncoder::Logical(Box::new(object_encoder)), false, unaccessable_files, skipped_files));
 		}
 		object_encoder_vec.reverse();
-		let (object_encoder, written_object_header, unaccessable_files) = match object_encoder_vec.pop() {
-			Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files),
+		let (object_encoder, written_object_header, unaccessable_files, skipped_files) = match object_encoder_vec.pop() {
+			Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files, creator_obj_encoder.skipped_files),
 			None => return Err(ZffError::new(ZffErrorKind::NoObjectsLeft, "")),
 		};
 		Ok(Self {
@@ -348,8 +219,9 @@ impl<R: Read> ZffExtender<R> {
 			object_encoder,
 			written_object_header,
 			unaccessable_files,
+			skipped_files,
 			current_segment_no,
-			last_accepted_segment_filepath: PathBuf::new(),
+			last_accepted_segment_no: current_segment_no,
 			main_footer,
 			last_segment_footer,
 		})
@@ -409,6 +281,10 @@ impl<R: Read> ZffExtender<R> {
 						if written_bytes == self.size_to_overwrite {
 							return Err(e);
 						} else {
+							// the subtree of this segment has to be finalized before the merkle root will be calculated.
+							for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+								self.last_segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+							}
 							//write the appropriate object footer and break the loop
 							self.main_footer.add_object_footer(self.object_encoder.obj_number(), self.current_segment_no);
 							self.last_segment_footer.add_object_footer_offset(self.object_encoder.obj_number(), written_bytes);
@@ -428,8 +304,15 @@ impl<R: Read> ZffExtender<R> {
 			if ChunkHeader::check_identifier(&mut data_cursor) {
 				self.last_segment_footer.add_chunk_offset(current_chunk_number, current_offset);
 				last_segment_footer_len += 16;
+				// the merkle leaf: chunk number, length of the hash and the hash itself.
+				if let Some(hash_type) = self.object_encoder.merkle_tree_hash_type() {
+					last_segment_footer_len += 16 + hash_type.default_len() as u64 / 8;
+				}
 			};
 		}
+		for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+			self.last_segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+		}
 
 		// finish the segment footer and write the encoded footer into the Writer.
 		self.last_segment_footer.set_footer_offset(written_bytes);
@@ -505,6 +388,10 @@ impl<R: Read> ZffExtender<R> {
 						if written_bytes == segment_header.encode_directly().len() as u64 {
 							return Err(e);
 						} else {
+							// the subtree of this segment has to be finalized before the merkle root will be calculated.
+							for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+								segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+							}
 							//write the appropriate object footer and break the loop
 							self.main_footer.add_object_footer(self.object_encoder.obj_number(), self.current_segment_no);
 							segment_footer.add_object_footer_offset(self.object_encoder.obj_number(), seek_value + written_bytes);
@@ -524,8 +411,15 @@ impl<R: Read> ZffExtender<R> {
 			if ChunkHeader::check_identifier(&mut data_cursor) {
 				segment_footer.add_chunk_offset(current_chunk_number, current_offset);
 				segment_footer_len += 16;
+				// the merkle leaf: chunk number, length of the hash and the hash itself.
+				if let Some(hash_type) = self.object_encoder.merkle_tree_hash_type() {
+					segment_footer_len += 16 + hash_type.default_len() as u64 / 8;
+				}
 			};
 		}
+		for (chunk_number, leaf_hash) in self.object_encoder.take_merkle_leaves(self.current_segment_no) {
+			segment_footer.add_merkle_leaf(chunk_number, leaf_hash);
+		}
 
 		// finish the segment footer and write the encoded footer into the Writer.
 		segment_footer.set_footer_offset(seek_value + written_bytes);
@@ -541,64 +435,62 @@ impl<R: Read> ZffExtender<R> {
 
 	/// extends the current .zXX files and generate additional .zXX, if needed.
 	pub fn extend(&mut self) -> Result<()> {
-		let mut segment_filename = PathBuf::from(&self.start_segment);
-		self.last_accepted_segment_filepath = segment_filename.clone();
-		let mut main_footer_start_offset = self.size_to_overwrite;
-		let mut file_extension = match segment_filename.extension() {
-			Some(extension) => match extension.to_str() {
-				Some(extension) => String::from(extension),
-				None => return Err(ZffError::new(ZffErrorKind::FileExtensionParserError, "")),
-			},
-			None => return Err(ZffError::new(ZffErrorKind::FileExtensionParserError, "")),
+		let mut sink = FileSystemSegmentSink::new(&self.start_segment);
+		self.extend_segments(&mut sink)
+	}
+
+	/// extends the last segment of the zff container and generates additional segments, if needed, by using the given [SegmentSink].
+	/// The given [SegmentSink] has to contain the last segment of the zff container (the segment with the main footer).
+	pub fn extend_segments<S: SegmentSink>(&mut self, sink: &mut S) -> Result<()> {
+		self.last_accepted_segment_no = self.current_segment_no;
+		let mut output = sink.open_segment(self.current_segment_no)?;
+		let mut main_footer_start_offset = match self.extend_current_segment(&mut output) {
+			Ok(offset) => offset,
+			Err(e) => match  e.get_kind() {
+				ZffErrorKind::ReadEOF => 0, //TODO: only remove the main footer?
+				_ => return Err(e),
+			}
 		};
-		let mut output_file = OpenOptions::new().write(true).open(&segment_filename)?;
-		let mut segment_extended = false;
+		sink.finalize_segment(self.current_segment_no, output)?;
+
 		let mut seek_value = 0;
 		loop {
-			if !segment_extended {
-				main_footer_start_offset = match self.extend_current_segment(&mut output_file) {
-					Ok(offset) => offset,
-					Err(e) => match  e.get_kind() {
-						ZffErrorKind::ReadEOF => 0, //TODO: only remove the main footer?
-						_ => return Err(e),
-					}
-				};
-				segment_extended = true;
-			}
 			self.current_segment_no += 1;
-			file_extension = file_extension_next_value(file_extension)?;
-	    	segment_filename.set_extension(&file_extension);
-	    	let mut output_file = File::create(&segment_filename)?;
-	    	main_footer_start_offset = match self.write_next_segment(&mut output_file, seek_value) {
-	    		Ok(written_bytes) => {
-	    			seek_value = 0;
-	    			written_bytes
-	    		},
-	    		Err(e) => match e.get_kind() {
-	    			ZffErrorKind::ReadEOF => {
-	    				remove_file(&segment_filename)?;
-	    				let (object_encoder, written_object_header, unaccessable_files) = match self.object_encoder_vec.pop() {
-	    					Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files),
-	    					None => break,
-	    				};
-	    				self.object_encoder = object_encoder;
-	    				self.written_object_header = written_object_header;
-	    				self.unaccessable_files = unaccessable_files;
-	    				self.current_segment_no -=1;
-	    				file_extension = file_extension_previous_value(file_extension)?;
-	    				seek_value = main_footer_start_offset;
-	    				main_footer_start_offset
-	    			},
-	    			_ => return Err(e),
-	    		},
-	    	};
-	    	self.last_accepted_segment_filepath = segment_filename.clone();
+			let mut output = sink.create_segment(self.current_segment_no)?;
+			main_footer_start_offset = match self.write_next_segment(&mut output, seek_value) {
+				Ok(written_bytes) => {
+					sink.finalize_segment(self.current_segment_no, output)?;
+					seek_value = 0;
+					written_bytes
+				},
+				Err(e) => match e.get_kind() {
+					ZffErrorKind::ReadEOF => {
+						drop(output);
+						sink.delete_segment(self.current_segment_no)?;
+						let (object_encoder, written_object_header, unaccessable_files, skipped_files) = match self.object_encoder_vec.pop() {
+							Some(creator_obj_encoder) => (creator_obj_encoder.object_encoder, creator_obj_encoder.written_object_header, creator_obj_encoder.unaccessable_files, creator_obj_encoder.skipped_files),
+							None => break,
+						};
+						self.object_encoder = object_encoder;
+						self.written_object_header = written_object_header;
+						self.unaccessable_files = unaccessable_files;
+						self.skipped_files = skipped_files;
+						self.current_segment_no -=1;
+						seek_value = main_footer_start_offset;
+						main_footer_start_offset
+					},
+					_ => return Err(e),
+				},
+			};
+			self.last_accepted_segment_no = self.current_segment_no;
 		}
 		self.main_footer.set_number_of_segments(self.current_segment_no-1);
 		self.main_footer.set_footer_offset(main_footer_start_offset);
-		let mut output_file = OpenOptions::new().write(true).append(true).open(&self.last_accepted_segment_filepath)?;
-	    output_file.write_all(&self.main_footer.encode_directly())?;
-	    Ok(())
+		let mut output = sink.open_segment(self.last_accepted_segment_no)?;
+		output.seek(SeekFrom::End(0))?;
+		output.write_all(&self.main_footer.encode_directly())?;
+		sink.finalize_segment(self.last_accepted_segment_no, output)?;
+		Ok(())
 	}
 
 	/// returns the unique identifier of the underlying zff container.
@@ -606,4 +498,13 @@ impl<R: Read> ZffExtender<R> {
 		self.object_encoder.main_header().unique_identifier()
 	}
 
+	/// Returns a reference of the unaccessable files.
+	pub fn unaccessable_files(&self) -> &Vec<String> {
+		&self.unaccessable_files
+	}
+
+	/// Returns a reference of the files, which were skipped by the [TraversalOptions].
+	pub fn skipped_files(&self) -> &Vec<String> {
+		&self.skipped_files
+	}
 }
\ No newline at end of file
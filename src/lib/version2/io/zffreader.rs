@@ -1,6 +1,7 @@
 // - STD
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom, Cursor};
+use std::ops::Range;
 
 // - internal
 use crate::{
@@ -10,16 +11,23 @@ use crate::{
 	ValueDecoder,
 	ZffError,
 	ZffErrorKind,
-	header::{MainHeader, FileHeader},
-	footer::{MainFooter, ObjectFooter, FileFooter},
+	header::{MainHeader, FileHeader, HashHeader},
+	footer::{MainFooter, ObjectFooter, ObjectFooterPhysical, FileFooter, SegmentFooter, DataStream, FuzzyHashValue},
 	PhysicalObjectInformation,
 	LogicalObjectInformation,
 	Object,
 	File,
 	Signature,
+	Hash,
+	MerkleTree,
 	calculate_crc32,
 };
 
+use super::{
+	HashOnlyAcquisition,
+	SourceVerificationReport,
+};
+
 use crate::{
 	ED25519_DALEK_PUBKEY_LEN,
 	ERROR_MISSING_SEGMENT_MAIN_HEADER,
@@ -28,6 +36,7 @@ use crate::{
 	ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION,
 	ERROR_ZFFREADER_MISSING_FILE,
 	ERROR_ZFFREADER_SEGMENT_NOT_FOUND,
+	ERROR_MERKLE_TREE_CHUNK_VERIFICATION_FAILED,
 	ERROR_MISMATCH_ZFF_VERSION
 };
 
@@ -40,6 +49,10 @@ pub struct ZffReader<R: Read + Seek> {
 	chunk_map: HashMap<u64, u64>, //<chunk_number, segment_number> for better runtime performance.
 	active_object: u64, // the object number of the active object
 	undecryptable_objects: Vec<u64>, // contains all numbers of objects, which could not be decoded, because the appropriate object header is not decryptable with the given password.
+	chunk_buffer: Vec<u8>, // reusable buffer, chunks will be decompressed into.
+	merkle_verification: bool, // verifies each read chunk by the merkle tree of the appropriate object.
+	merkle_publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>, // the publickey, which will be used to verify the signature of the merkle roots (if merkle_verification is enabled).
+	merkle_verified_segments: HashSet<(u64, u64, Option<[u8; ED25519_DALEK_PUBKEY_LEN]>)>, // <(object number, segment number, publickey)> of the segments, whose merkle leaves are already verified (with the given publickey).
 }
 
 impl<R: Read + Seek> ZffReader<R> {
@@ -258,6 +271,7 @@ impl<R: Read + Seek> ZffReader<R> {
 			};
 		}
 
+		let chunk_buffer = vec![0; main_header.chunk_size()];
 		Ok(Self {
 			main_header,
 			main_footer,
@@ -266,6 +280,10 @@ impl<R: Read + Seek> ZffReader<R> {
 			segments,
 			active_object: 1,
 			undecryptable_objects,
+			chunk_buffer,
+			merkle_verification: false,
+			merkle_publickey: None,
+			merkle_verified_segments: HashSet::new(),
 		})
 	}
 
@@ -363,6 +381,90 @@ impl<R: Read + Seek> ZffReader<R> {
 		}
 	}
 
+	/// Returns the named (secondary) data streams of the given file of the given logical object.
+	/// # Error
+	/// Fails if the given object number not exists, the object is a physical object or the file number not exists in the appropriate object.
+	pub fn data_streams(&self, object_number: u64, file_number: u64) -> Result<&Vec<DataStream>> {
+		match self.objects.get(&object_number) {
+			Some(Object::Logical(object)) => match object.files().get(&file_number) {
+				Some(file) => Ok(file.footer().data_streams()),
+				None => Err(ZffError::new(ZffErrorKind::MissingFileNumber, file_number.to_string())),
+			},
+			Some(Object::Physical(_)) => Err(ZffError::new(ZffErrorKind::MismatchObjectType, object_number.to_string())),
+			None => Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		}
+	}
+
+	/// Returns the fuzzy (similarity) hashes of the given file of the given logical object.
+	/// # Error
+	/// Fails if the given object number not exists, the object is a physical object or the file number not exists in the appropriate object.
+	pub fn fuzzy_hashes(&self, object_number: u64, file_number: u64) -> Result<&Vec<FuzzyHashValue>> {
+		match self.objects.get(&object_number) {
+			Some(Object::Logical(object)) => match object.files().get(&file_number) {
+				Some(file) => Ok(file.footer().fuzzy_hashes()),
+				None => Err(ZffError::new(ZffErrorKind::MissingFileNumber, file_number.to_string())),
+			},
+			Some(Object::Physical(_)) => Err(ZffError::new(ZffErrorKind::MismatchObjectType, object_number.to_string())),
+			None => Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		}
+	}
+
+	/// Reads the named data stream of the given file (of the given logical object) at the given offset into the buffer.
+	/// Returns the number of read bytes.
+	/// # Error
+	/// Fails if the given object or file number not exists or the file has no data stream with the given name.
+	pub fn read_data_stream_at(&mut self, object_number: u64, file_number: u64, stream_name: &str, offset: u64, buffer: &mut [u8]) -> Result<usize> {
+		let data_stream = match self.data_streams(object_number, file_number)?.iter().find(|data_stream| data_stream.name() == stream_name) {
+			Some(data_stream) => data_stream.clone(),
+			None => return Err(ZffError::new(ZffErrorKind::MissingDataStream, stream_name)),
+		};
+		if offset >= data_stream.length_of_data() {
+			return Ok(0);
+		}
+		// the object exists (checked by data_streams()).
+		let object = &self.objects[&object_number];
+		let chunk_size = self.main_header.chunk_size() as u64;
+		let last_chunk_number = data_stream.first_chunk_number() + data_stream.number_of_chunks();
+		let mut current_chunk_number = data_stream.first_chunk_number() + offset / chunk_size;
+		let mut inner_position = (offset % chunk_size) as usize;
+		let read_length = std::cmp::min(buffer.len() as u64, data_stream.length_of_data() - offset) as usize;
+		let mut read_bytes = 0;
+		while read_bytes < read_length && current_chunk_number < last_chunk_number {
+			let segment = match self.chunk_map.get(&current_chunk_number).and_then(|segment_no| self.segments.get_mut(segment_no)) {
+				Some(segment) => segment,
+				None => return Err(ZffError::new(ZffErrorKind::MissingSegment, ERROR_ZFFREADER_SEGMENT_NOT_FOUND)),
+			};
+			let chunk_length = segment.chunk_data_into(current_chunk_number, object, &mut self.chunk_buffer)?;
+			if inner_position >= chunk_length {
+				break;
+			}
+			let mut cursor = Cursor::new(&self.chunk_buffer[inner_position..chunk_length]);
+			read_bytes += cursor.read(&mut buffer[read_bytes..read_length])?;
+			inner_position = 0;
+			current_chunk_number += 1;
+		}
+		Ok(read_bytes)
+	}
+
+	/// Opens the named data stream of the given file (of the given logical object) and returns an appropriate [DataStreamReader].
+	/// # Error
+	/// Fails if the given object or file number not exists or the file has no data stream with the given name.
+	pub fn open_data_stream<N: Into<String>>(&mut self, object_number: u64, file_number: u64, stream_name: N) -> Result<DataStreamReader<'_, R>> {
+		let stream_name = stream_name.into();
+		let length_of_data = match self.data_streams(object_number, file_number)?.iter().find(|data_stream| data_stream.name() == stream_name) {
+			Some(data_stream) => data_stream.length_of_data(),
+			None => return Err(ZffError::new(ZffErrorKind::MissingDataStream, stream_name)),
+		};
+		Ok(DataStreamReader {
+			reader: self,
+			object_number,
+			file_number,
+			stream_name,
+			length_of_data,
+			position: 0,
+		})
+	}
+
 	/// Returns the description notes of the zff container (if available).
 	pub fn description_notes(&self) -> Option<&str> {
 		self.main_footer.description_notes()
@@ -381,10 +483,22 @@ impl<R: Read + Seek> ZffReader<R> {
 			Object::Logical(ref obj_info) => {
 				let mut corrupt_chunks = Vec::new();
 				for file in obj_info.files().values() {
+					// the chunks of deduplicated files will be verified with the appropriate original file.
+					if file.footer().is_deduplicated() {
+						continue;
+					}
 					let first_chunk_number = file.footer().first_chunk_number();
 					let last_chunk_number = file.footer().first_chunk_number() + file.footer().number_of_chunks() - 1;
 				
 					corrupt_chunks.append(&mut self.verify_chunks(publickey, first_chunk_number, last_chunk_number, &current_object)?);
+					for data_stream in file.footer().data_streams() {
+						if data_stream.number_of_chunks() == 0 {
+							continue;
+						}
+						let first_chunk_number = data_stream.first_chunk_number();
+						let last_chunk_number = first_chunk_number + data_stream.number_of_chunks() - 1;
+						corrupt_chunks.append(&mut self.verify_chunks(publickey, first_chunk_number, last_chunk_number, &current_object)?);
+					}
 				}
 				Ok(corrupt_chunks)
 			},
@@ -398,19 +512,276 @@ impl<R: Read + Seek> ZffReader<R> {
 			let segment_no = self.chunk_map.get_mut(&chunk_number).unwrap();
 			let segment = self.segments.get_mut(segment_no).unwrap();
 
-			let chunk_data = segment.chunk_data(chunk_number, current_object)?;
+			let chunk_length = segment.chunk_data_into(chunk_number, current_object, &mut self.chunk_buffer)?;
 			let signature = match segment.raw_chunk(chunk_number)?.header().signature() {
 				Some(signature) => *signature,
 				None => return Err(ZffError::new(ZffErrorKind::NoSignatureFoundAtChunk, chunk_number.to_string())),
 			};
 
-			if !Signature::verify(publickey, &chunk_data, signature)? {
+			if !Signature::verify(publickey, &self.chunk_buffer[..chunk_length], signature)? {
 				corrupt_chunks.push(chunk_number);
 			}
 		}
 
 		Ok(corrupt_chunks)
 	}
+
+	/// Verifies the piecewise hashes of the given physical object for all hash windows, which overlap the given range
+	/// (offset and length in bytes of the uncompressed and unencrypted data).
+	/// Returns the ranges of all hash windows, which could NOT be verified (an empty Vec means, the given range is intact).
+	/// Unreadable hash windows (e.g. caused by a damaged chunk) are also treated as damaged.
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object or if the object contains no piecewise hashes.
+	pub fn verify_piecewise_hashes(&mut self, object_number: u64, offset: u64, length: u64) -> Result<Vec<Range<u64>>> {
+		let footer = self.physical_object_footer(object_number)?;
+		let piecewise_hash_size = footer.piecewise_hash_size();
+		if piecewise_hash_size == 0 || footer.piecewise_hashes().is_empty() {
+			return Err(ZffError::new(ZffErrorKind::MissingPiecewiseHashes, object_number.to_string()));
+		}
+		let mut damaged_regions = Vec::new();
+		let end = std::cmp::min(offset.saturating_add(length), footer.length_of_data());
+		if offset >= end {
+			return Ok(damaged_regions);
+		}
+
+		// the position of the active object (and the active object itself) will be restored after the verification.
+		let active_object = self.active_object;
+		let position = self.set_reader_physical_object(object_number)?;
+		for window_number in offset / piecewise_hash_size..=(end - 1) / piecewise_hash_size {
+			let window_start = window_number * piecewise_hash_size;
+			let window_end = std::cmp::min(window_start + piecewise_hash_size, footer.length_of_data());
+			let verified = match footer.piecewise_hashes().get(window_number as usize) {
+				Some(hash_header) => self.verify_hash_window(window_start, window_end - window_start, hash_header),
+				None => false,
+			};
+			if !verified {
+				damaged_regions.push(window_start..window_end);
+			}
+		}
+		self.seek(SeekFrom::Start(position))?;
+		self.active_object = active_object;
+
+		Ok(damaged_regions)
+	}
+
+	/// Verifies all piecewise hashes of the given physical object and returns the (merged) ranges of the damaged regions.
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object or if the object contains no piecewise hashes.
+	pub fn locate_damaged_regions(&mut self, object_number: u64) -> Result<Vec<Range<u64>>> {
+		let mut damaged_regions: Vec<Range<u64>> = Vec::new();
+		for region in self.verify_piecewise_hashes(object_number, 0, u64::MAX)? {
+			match damaged_regions.last_mut() {
+				Some(last_region) if last_region.end == region.start => last_region.end = region.end,
+				_ => damaged_regions.push(region),
+			}
+		}
+		Ok(damaged_regions)
+	}
+
+	/// Reads the whole given source (e.g. the original device) in hash-only mode (see [HashOnlyAcquisition]) and compares the calculated
+	/// hash values and piecewise hash values with the hash values of the given physical object. No data will be written.
+	/// The source will be hashed by all hash types of the object (and with the hash window size of the object, if the object contains piecewise hashes).
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object or if the source could not be read.
+	pub fn verify_source<S: Read>(&mut self, object_number: u64, source: S) -> Result<SourceVerificationReport> {
+		let footer = self.physical_object_footer(object_number)?;
+		let object_header = match self.objects.get(&object_number) {
+			Some(object) => object.header().clone(),
+			None => return Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		};
+		let hash_types = footer.hash_header().hash_values().iter().map(|hash_value| hash_value.hash_type().clone()).collect();
+		let mut acquisition = HashOnlyAcquisition::new(source, object_header, self.main_header.clone(), hash_types)?;
+		acquisition.set_piecewise_hash_size(footer.piecewise_hash_size());
+		let calculated_footer = acquisition.acquire()?;
+		Ok(SourceVerificationReport::new(object_number, &footer, &calculated_footer))
+	}
+
+	/// Enables or disables the on-the-fly verification of all read chunks by the merkle tree of the appropriate physical object.
+	/// If enabled, the [Read] implementation returns an error of kind [InvalidData](std::io::ErrorKind::InvalidData), if a read chunk
+	/// could not be verified (e.g. a tampered chunk or a physical object without a merkle tree). Logical objects will be read unverified.
+	/// If a publickey is given, the signature of the merkle root will be verified with this publickey before the merkle tree will be trusted:
+	/// chunks of objects with an unsigned (or wrong signed) merkle root could not be verified in this case.
+	pub fn set_merkle_verification(&mut self, merkle_verification: bool, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) {
+		self.merkle_verification = merkle_verification;
+		self.merkle_publickey = publickey;
+	}
+
+	/// Verifies the given chunk of the given physical object by the merkle tree of the object: the chunk data will be checked against the
+	/// appropriate leaf in the segment footer, the leaves of the segment against the subtree root of the segment and the subtree roots
+	/// against the root in the object footer. Only the appropriate segment (and the object footer) will be read.
+	/// If a publickey is given, the signature of the merkle root will be verified first (an unsigned merkle root could not be verified in this case).
+	/// Returns false, if the chunk could NOT be verified.
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object, contains no merkle tree or if the chunk is not part of the object.
+	/// # Example
+	/// ```
+	/// use std::collections::HashMap;
+	/// use std::io::{Cursor, Read, ErrorKind};
+	/// use ed25519_dalek::Keypair;
+	/// use zff::{ZffCreator, ZffCreatorMetadataParams, ZffReader, MemorySegmentSink, MerkleTree, Signature, HashType, CompressionAlgorithm, SignatureFlag};
+	/// use zff::header::{MainHeader, ObjectHeader, ObjectType, CompressionHeader, DescriptionHeader};
+	///
+	/// // three chunks of pseudo-random data (the incompressible chunks will be stored uncompressed).
+	/// let mut state = 1u32;
+	/// let data: Vec<u8> = (0..3 * 4096).map(|_| {
+	///     state = state.wrapping_mul(1103515245).wrapping_add(12345);
+	///     (state >> 16) as u8
+	/// }).collect();
+	/// let keypair = Signature::new_keypair();
+	/// let publickey = keypair.public.to_bytes();
+	/// let wrong_publickey = Signature::new_keypair().public.to_bytes();
+	/// let create_segment = |signature_flag| {
+	///     let compression_header = CompressionHeader::new(1, CompressionAlgorithm::Zstd, 3, 1.05);
+	///     let object_header = ObjectHeader::new(2, 1, None, compression_header, signature_flag, DescriptionHeader::new_empty(2), ObjectType::Physical);
+	///     let signature_key = Keypair::from_bytes(&keypair.to_bytes()).unwrap();
+	///     let mut params = ZffCreatorMetadataParams::with_data(None, Some(signature_key), MainHeader::new(2, 12, 0, 1), false, None);
+	///     params.set_merkle_tree(Some(HashType::Blake3));
+	///     let mut physical_objects = HashMap::new();
+	///     physical_objects.insert(object_header, Cursor::new(data.clone()));
+	///     let mut creator = ZffCreator::new(physical_objects, HashMap::new(), vec![HashType::Blake3], "unused", params).unwrap();
+	///     let mut sink = MemorySegmentSink::new();
+	///     creator.generate_segments(&mut sink).unwrap();
+	///     sink.segments()[&1].clone()
+	/// };
+	/// let read_verified = |segment: &Vec<u8>, publickey| {
+	///     let mut reader = ZffReader::new(vec![Cursor::new(segment.clone())], HashMap::new()).unwrap();
+	///     reader.set_reader_physical_object(1).unwrap();
+	///     reader.set_merkle_verification(true, publickey);
+	///     reader.read_to_end(&mut Vec::new())
+	/// };
+	///
+	/// let segment = create_segment(SignatureFlag::HashValueSignatureOnly);
+	/// let mut reader = ZffReader::new(vec![Cursor::new(segment.clone())], HashMap::new()).unwrap();
+	/// assert!(reader.verify_merkle_chunk(1, 2, Some(publickey)).unwrap());
+	/// assert!(reader.verify_merkle_segment(1, 1, Some(publickey)).unwrap().is_empty());
+	/// assert_eq!(read_verified(&segment, Some(publickey)).unwrap(), data.len());
+	///
+	/// // a wrong publickey (or an unsigned merkle root) fails the verification of all chunks.
+	/// assert!(!reader.verify_merkle_chunk(1, 2, Some(wrong_publickey)).unwrap());
+	/// assert_eq!(reader.verify_merkle_tree(1, Some(wrong_publickey)).unwrap(), vec![1, 2, 3]);
+	/// assert_eq!(read_verified(&segment, Some(wrong_publickey)).unwrap_err().kind(), ErrorKind::InvalidData);
+	/// let unsigned_segment = create_segment(SignatureFlag::NoSignatures);
+	/// let mut unsigned_reader = ZffReader::new(vec![Cursor::new(unsigned_segment.clone())], HashMap::new()).unwrap();
+	/// assert!(unsigned_reader.verify_merkle_chunk(1, 2, None).unwrap());
+	/// assert!(!unsigned_reader.verify_merkle_chunk(1, 2, Some(publickey)).unwrap());
+	/// assert_eq!(read_verified(&unsigned_segment, Some(publickey)).unwrap_err().kind(), ErrorKind::InvalidData);
+	///
+	/// // a tampered chunk fails the verification of this chunk.
+	/// let find = |needle: &[u8]| segment.windows(needle.len()).position(|window| window == needle).unwrap();
+	/// let mut tampered_segment = segment.clone();
+	/// tampered_segment[find(&data[4096..8192]) + 100] ^= 0xff;
+	/// let mut tampered_reader = ZffReader::new(vec![Cursor::new(tampered_segment.clone())], HashMap::new()).unwrap();
+	/// assert!(!tampered_reader.verify_merkle_chunk(1, 2, None).unwrap());
+	/// assert_eq!(tampered_reader.verify_merkle_segment(1, 1, None).unwrap(), vec![2]);
+	/// assert_eq!(read_verified(&tampered_segment, None).unwrap_err().kind(), ErrorKind::InvalidData);
+	///
+	/// // tampered merkle leaves fail the verification of all chunks of the segment.
+	/// let mut tampered_segment = segment.clone();
+	/// tampered_segment[find(&MerkleTree::leaf_hash(&HashType::Blake3, &data[..4096]))] ^= 0xff;
+	/// let mut tampered_reader = ZffReader::new(vec![Cursor::new(tampered_segment.clone())], HashMap::new()).unwrap();
+	/// assert!(!tampered_reader.verify_merkle_chunk(1, 2, None).unwrap());
+	/// assert_eq!(tampered_reader.verify_merkle_segment(1, 1, None).unwrap(), vec![1, 2, 3]);
+	/// assert_eq!(read_verified(&tampered_segment, None).unwrap_err().kind(), ErrorKind::InvalidData);
+	/// ```
+	pub fn verify_merkle_chunk(&mut self, object_number: u64, chunk_number: u64, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) -> Result<bool> {
+		let footer = self.merkle_tree_object_footer(object_number)?;
+		if chunk_number < footer.first_chunk_number() || chunk_number >= footer.first_chunk_number() + footer.number_of_chunks() {
+			return Err(ZffError::new(ZffErrorKind::InvalidChunkNumber, chunk_number.to_string()));
+		}
+		let segment_number = match self.chunk_map.get(&chunk_number) {
+			Some(segment_number) => *segment_number,
+			None => return Err(ZffError::new(ZffErrorKind::MissingSegment, ERROR_ZFFREADER_SEGMENT_NOT_FOUND)),
+		};
+		Ok(self.verify_merkle_chunk_in_segment(object_number, segment_number, chunk_number, publickey))
+	}
+
+	/// Verifies all chunks of the given physical object in the given segment by the merkle tree of the object (see [ZffReader::verify_merkle_chunk]).
+	/// Returns a Vec of chunk numbers, which could NOT be verified. If the merkle leaves of the segment are tampered, all chunks of the object in this segment
+	/// will be returned.
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object, contains no merkle tree or if the segment not exists.
+	pub fn verify_merkle_segment(&mut self, object_number: u64, segment_number: u64, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) -> Result<Vec<u64>> {
+		let footer = self.merkle_tree_object_footer(object_number)?;
+		let first_chunk_number = footer.first_chunk_number();
+		let last_chunk_number = first_chunk_number + footer.number_of_chunks();
+		let mut chunk_numbers: Vec<u64> = match self.segments.get(&segment_number) {
+			Some(segment) => segment.footer().chunk_offsets().keys().filter(|chunk_number| (first_chunk_number..last_chunk_number).contains(chunk_number)).copied().collect(),
+			None => return Err(ZffError::new(ZffErrorKind::MissingSegment, ERROR_ZFFREADER_SEGMENT_NOT_FOUND)),
+		};
+		chunk_numbers.sort_unstable();
+		Ok(chunk_numbers.into_iter().filter(|chunk_number| !self.verify_merkle_chunk_in_segment(object_number, segment_number, *chunk_number, publickey)).collect())
+	}
+
+	/// Verifies all chunks of the given physical object by the merkle tree of the object (see [ZffReader::verify_merkle_chunk]).
+	/// Returns a Vec of chunk numbers, which could NOT be verified.
+	/// # Error
+	/// Fails if the given object number not exists, is a logical object or contains no merkle tree.
+	pub fn verify_merkle_tree(&mut self, object_number: u64, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) -> Result<Vec<u64>> {
+		let footer = self.merkle_tree_object_footer(object_number)?;
+		let mut corrupt_chunks = Vec::new();
+		for chunk_number in footer.first_chunk_number()..footer.first_chunk_number() + footer.number_of_chunks() {
+			let verified = match self.chunk_map.get(&chunk_number) {
+				Some(segment_number) => self.verify_merkle_chunk_in_segment(object_number, *segment_number, chunk_number, publickey),
+				None => false,
+			};
+			if !verified {
+				corrupt_chunks.push(chunk_number);
+			}
+		}
+		Ok(corrupt_chunks)
+	}
+
+	// reads the given chunk and verifies the chunk data by the merkle tree of the given object.
+	fn verify_merkle_chunk_in_segment(&mut self, object_number: u64, segment_number: u64, chunk_number: u64, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) -> bool {
+		let (object, segment) = match (self.objects.get(&object_number), self.segments.get_mut(&segment_number)) {
+			(Some(object), Some(segment)) => (object, segment),
+			_ => return false,
+		};
+		match segment.chunk_data_into(chunk_number, object, &mut self.chunk_buffer) {
+			Ok(chunk_length) => verify_merkle_chunk_data(object, segment_number, segment.footer(), chunk_number, &self.chunk_buffer[..chunk_length], publickey, &mut self.merkle_verified_segments),
+			Err(_) => false,
+		}
+	}
+
+	// returns a copy of the footer of the given physical object.
+	fn physical_object_footer(&self, object_number: u64) -> Result<ObjectFooterPhysical> {
+		match self.objects.get(&object_number) {
+			Some(Object::Physical(object)) => Ok(object.footer().clone()),
+			Some(Object::Logical(_)) => Err(ZffError::new(ZffErrorKind::MismatchObjectType, object_number.to_string())),
+			None => Err(ZffError::new(ZffErrorKind::MissingObjectNumber, object_number.to_string())),
+		}
+	}
+
+	// returns a copy of the footer of the given physical object, if the object contains a merkle tree.
+	fn merkle_tree_object_footer(&self, object_number: u64) -> Result<ObjectFooterPhysical> {
+		let footer = self.physical_object_footer(object_number)?;
+		match footer.merkle_root() {
+			Some(_) => Ok(footer),
+			None => Err(ZffError::new(ZffErrorKind::MissingMerkleTree, object_number.to_string())),
+		}
+	}
+
+	// reads the given hash window of the active object and compares the calculated hash values with the given hash values.
+	fn verify_hash_window(&mut self, window_start: u64, window_length: u64, hash_header: &HashHeader) -> bool {
+		let mut hashers: Vec<_> = hash_header.hash_values().iter().map(|hash_value| (hash_value, Hash::new_hasher(hash_value.hash_type()))).collect();
+		if self.seek(SeekFrom::Start(window_start)).is_err() {
+			return false;
+		}
+		let mut buffer = vec![0; self.main_header.chunk_size()];
+		let mut remaining_bytes = window_length;
+		while remaining_bytes > 0 {
+			let length = std::cmp::min(remaining_bytes, buffer.len() as u64) as usize;
+			let read_bytes = match self.read(&mut buffer[..length]) {
+				Ok(0) | Err(_) => return false,
+				Ok(read_bytes) => read_bytes,
+			};
+			for (_, hasher) in hashers.iter_mut() {
+				hasher.update(&buffer[..read_bytes]);
+			}
+			remaining_bytes -= read_bytes as u64;
+		}
+		hashers.into_iter().all(|(hash_value, hasher)| *hasher.finalize() == **hash_value.hash())
+	}
 }
 
 impl<R: Read + Seek> Read for ZffReader<R> {
@@ -441,34 +812,40 @@ impl<R: Read + Seek> Read for ZffReader<R> {
 		};
 		let mut current_chunk_number = (first_chunk_number * chunk_size as u64 + object.position()) / chunk_size as u64;
 		let mut inner_position = (object.position() % chunk_size as u64) as usize; // the inner chunk position
+		let start_position = object.position();
 		let mut read_bytes = 0; // number of bytes which are written to buffer
 
 		loop {
 			if read_bytes == buffer.len() || current_chunk_number > last_chunk_number {
 				break;
 			}
-			let segment = match self.chunk_map.get(&current_chunk_number) {
+			let (segment_no, segment) = match self.chunk_map.get(&current_chunk_number) {
 				Some(segment_no) => match self.segments.get_mut(segment_no) {
-					Some(segment) => segment,
+					Some(segment) => (*segment_no, segment),
 					None => return Err(std::io::Error::new(std::io::ErrorKind::NotFound, ERROR_ZFFREADER_SEGMENT_NOT_FOUND)),
 				},
 				None => break,
 			};
-			let chunk_data = match segment.chunk_data(current_chunk_number, object) {
-				Ok(data) => data,
+			let chunk_length = match segment.chunk_data_into(current_chunk_number, object, &mut self.chunk_buffer) {
+				Ok(length) => length,
 				Err(e) => match e.unwrap_kind() {
 					ZffErrorKind::IoError(io_error) => return Err(io_error),
 					error => return Err(std::io::Error::new(std::io::ErrorKind::Other, error.to_string())) 
 				},
 			};
-			let mut cursor = Cursor::new(&chunk_data[inner_position..]);
+			if self.merkle_verification && !verify_merkle_chunk_data(object, segment_no, segment.footer(), current_chunk_number, &self.chunk_buffer[..chunk_length], self.merkle_publickey, &mut self.merkle_verified_segments) {
+				return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{ERROR_MERKLE_TREE_CHUNK_VERIFICATION_FAILED}{current_chunk_number}")));
+			}
+			if inner_position >= chunk_length {
+				break;
+			}
+			let mut cursor = Cursor::new(&self.chunk_buffer[inner_position..chunk_length]);
 			read_bytes += cursor.read(&mut buffer[read_bytes..])?;
 			inner_position = 0;
 			current_chunk_number += 1;
-			object.set_position(read_bytes as u64);
 		}
 
-		object.set_position(read_bytes as u64);
+		object.set_position(start_position + read_bytes as u64);
 		Ok(read_bytes)
 	}
 }
@@ -489,11 +866,9 @@ impl<R: Read + Seek> Seek for ZffReader<R> {
 			} else if value >= 0 {
    					object.set_position(object.position() + value as u64);
 			} else {
-				object.set_position(object.position() - value as u64);
+				object.set_position(object.position() - value.unsigned_abs());
 			},
-			SeekFrom::End(value) => if object.position() as i64 + value < 0 {
-				return Err(std::io::Error::new(std::io::ErrorKind::Other, ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION))
-			} else {
+			SeekFrom::End(value) => {
 				let end = {
 					match object {
 						Object::Physical(object) => object.footer().length_of_data(),
@@ -505,13 +880,128 @@ impl<R: Read + Seek> Seek for ZffReader<R> {
 						},
 					}
 				};
-				if value >= 0 {
+				if end as i64 + value < 0 {
+					return Err(std::io::Error::new(std::io::ErrorKind::Other, ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION))
+				} else if value >= 0 {
 					object.set_position(end + value as u64);
 				} else {
-					object.set_position(end - value as u64);
+					object.set_position(end - value.unsigned_abs());
 				}
 			},
 		}
 		Ok(object.position())
 	}
-}
\ No newline at end of file
+}
+
+/// The [DataStreamReader] can be used to [Read](std::io::Read) a named (secondary) data stream of a file (see [ZffReader::open_data_stream]).
+pub struct DataStreamReader<'a, R: Read + Seek> {
+	reader: &'a mut ZffReader<R>,
+	object_number: u64,
+	file_number: u64,
+	stream_name: String,
+	length_of_data: u64,
+	position: u64,
+}
+
+impl<'a, R: Read + Seek> DataStreamReader<'a, R> {
+	/// returns the name of the underlying data stream.
+	pub fn stream_name(&self) -> &str {
+		&self.stream_name
+	}
+
+	/// returns the (uncompressed, unencrypted) length of the underlying data stream.
+	pub fn length_of_data(&self) -> u64 {
+		self.length_of_data
+	}
+}
+
+impl<'a, R: Read + Seek> Read for DataStreamReader<'a, R> {
+	fn read(&mut self, buffer: &mut [u8]) -> std::result::Result<usize, std::io::Error> {
+		let read_bytes = match self.reader.read_data_stream_at(self.object_number, self.file_number, &self.stream_name, self.position, buffer) {
+			Ok(read_bytes) => read_bytes,
+			Err(e) => match e.unwrap_kind() {
+				ZffErrorKind::IoError(io_error) => return Err(io_error),
+				error => return Err(std::io::Error::new(std::io::ErrorKind::Other, error.to_string())),
+			},
+		};
+		self.position += read_bytes as u64;
+		Ok(read_bytes)
+	}
+}
+
+impl<'a, R: Read + Seek> Seek for DataStreamReader<'a, R> {
+	fn seek(&mut self, seek_from: SeekFrom) -> std::result::Result<u64, std::io::Error> {
+		let position = match seek_from {
+			SeekFrom::Start(value) => value as i128,
+			SeekFrom::Current(value) => self.position as i128 + value as i128,
+			SeekFrom::End(value) => self.length_of_data as i128 + value as i128,
+		};
+		if position < 0 {
+			return Err(std::io::Error::new(std::io::ErrorKind::Other, ERROR_IO_NOT_SEEKABLE_NEGATIVE_POSITION));
+		}
+		self.position = position as u64;
+		Ok(self.position)
+	}
+}
+
+// verifies the given chunk data by the merkle tree of the given object. The merkle leaves of each segment will be verified once
+// (against the merkle root of the object and its signature, if a publickey is given) and cached in the given set.
+// Logical objects will not be verified, chunks of physical objects without a merkle tree could not be verified.
+fn verify_merkle_chunk_data(
+	object: &Object,
+	segment_number: u64,
+	segment_footer: &SegmentFooter,
+	chunk_number: u64,
+	chunk_data: &[u8],
+	publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>,
+	verified_segments: &mut HashSet<(u64, u64, Option<[u8; ED25519_DALEK_PUBKEY_LEN]>)>) -> bool {
+	let footer = match object {
+		Object::Physical(object) => object.footer(),
+		Object::Logical(_) => return true,
+	};
+	let merkle_root = match footer.merkle_root() {
+		Some(merkle_root) => merkle_root,
+		None => return false,
+	};
+	let verified_segment = (object.header().object_number(), segment_number, publickey);
+	if !verified_segments.contains(&verified_segment) {
+		if !verify_merkle_segment_leaves(footer, segment_number, segment_footer, publickey) {
+			return false;
+		}
+		verified_segments.insert(verified_segment);
+	}
+	match segment_footer.merkle_leaves().get(&chunk_number) {
+		Some(leaf_hash) => MerkleTree::leaf_hash(merkle_root.hash_type(), chunk_data) == *leaf_hash,
+		None => false,
+	}
+}
+
+// verifies the merkle leaves of the given object in the given segment against the subtree root of the segment and
+// the subtree roots of all segments against the merkle root of the object. If a publickey is given, the signature of the
+// merkle root will be verified first.
+fn verify_merkle_segment_leaves(footer: &ObjectFooterPhysical, segment_number: u64, segment_footer: &SegmentFooter, publickey: Option<[u8; ED25519_DALEK_PUBKEY_LEN]>) -> bool {
+	let merkle_root = match footer.merkle_root() {
+		Some(merkle_root) => merkle_root,
+		None => return false,
+	};
+	if let Some(publickey) = publickey {
+		match merkle_root.ed25519_signature() {
+			Some(signature) if matches!(Signature::verify(publickey, merkle_root.hash(), signature), Ok(true)) => (),
+			_ => return false,
+		}
+	}
+	let hash_type = merkle_root.hash_type();
+	let first_chunk_number = footer.first_chunk_number();
+	let last_chunk_number = first_chunk_number + footer.number_of_chunks();
+	let mut leaves: Vec<(&u64, &Vec<u8>)> = segment_footer.merkle_leaves().iter().filter(|(chunk_number, _)| (first_chunk_number..last_chunk_number).contains(*chunk_number)).collect();
+	leaves.sort_by_key(|(chunk_number, _)| **chunk_number);
+	let leaves: Vec<&Vec<u8>> = leaves.into_iter().map(|(_, leaf_hash)| leaf_hash).collect();
+	match (MerkleTree::root(hash_type, &leaves), footer.merkle_segment_roots().get(&segment_number)) {
+		(Some(segment_root), Some(expected_segment_root)) if segment_root == *expected_segment_root => (),
+		_ => return false,
+	}
+	let mut segment_numbers: Vec<&u64> = footer.merkle_segment_roots().keys().collect();
+	segment_numbers.sort();
+	let segment_roots: Vec<&Vec<u8>> = segment_numbers.iter().map(|segment_number| &footer.merkle_segment_roots()[segment_number]).collect();
+	MerkleTree::root(hash_type, &segment_roots).as_ref() == Some(merkle_root.hash())
+}
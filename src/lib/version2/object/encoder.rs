@@ -3,8 +3,8 @@
 use std::os::unix::fs::MetadataExt;
 
 use std::io::{Read, Cursor, Seek, SeekFrom};
-use std::fs::{File};
-use std::path::{PathBuf};
+use std::fs::{File, Metadata};
+use std::path::{Path, PathBuf};
 use std::collections::{HashMap};
 use std::time::{SystemTime};
 
@@ -13,10 +13,15 @@ use crate::{
 	Result,
 	buffer_chunk,
 	calculate_crc32,
-	compress_buffer,
+	compress_chunk,
+	compress_chunk_adaptive,
+	Compression,
+	Compressor,
 	HeaderCoding,
 	HashType,
 	Hash,
+	FuzzyHashType,
+	MerkleTree,
 	Signature,
 	ZffError,
 	ZffErrorKind,
@@ -26,12 +31,22 @@ use crate::{
 	DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER,
 	DEFAULT_HEADER_VERSION_HASH_HEADER,
 	DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_LOGICAL,
+	ERROR_DEDUPLICATION_MISSING_HASH_TYPE,
 };
 
+#[cfg(target_os = "linux")]
 use crate::{
-	header::{ObjectHeader, MainHeader, ChunkHeader, HashValue, HashHeader, FileHeader, CompressionHeader, EncryptionHeader},
+	NTFS_STREAMS_LIST_XATTR,
+};
+
+use crate::{
+	header::{ObjectHeader, MainHeader, ChunkHeader, HashValue, HashHeader, FileHeader, FileType, CompressionHeader, EncryptionHeader},
 	footer::{ObjectFooterPhysical, ObjectFooterLogical},
 	FileEncoder, 
+	FileContent,
+	DeduplicationCandidate,
+	VirtualFile,
+	LogicalTree,
 };
 
 // - external
@@ -96,6 +111,23 @@ impl<R: Read> ObjectEncoder<R> {
 		}
 	}
 
+	/// returns the hash type of the merkle tree (if the merkle tree is enabled).
+	pub fn merkle_tree_hash_type(&self) -> Option<&HashType> {
+		match self {
+			ObjectEncoder::Physical(obj) => obj.merkle_tree_hash_type.as_ref(),
+			ObjectEncoder::Logical(_) => None,
+		}
+	}
+
+	/// returns the merkle tree leaves of the chunks, which were created since the last call of this method, and
+	/// finalizes the appropriate subtree of the given segment (see [PhysicalObjectEncoder::take_merkle_leaves]).
+	pub fn take_merkle_leaves(&mut self, segment_number: u64) -> HashMap<u64, Vec<u8>> {
+		match self {
+			ObjectEncoder::Physical(obj) => obj.take_merkle_leaves(segment_number),
+			ObjectEncoder::Logical(_) => HashMap::new(),
+		}
+	}
+
 	/// returns the next data.
 	pub fn get_next_data(&mut self, current_offset: u64, current_segment_no: u64) -> Result<Vec<u8>> {
 		match self {
@@ -104,6 +136,14 @@ impl<R: Read> ObjectEncoder<R> {
 		}
 	}
 
+	/// returns the hash header of the data, which was read so far (only available for physical objects).
+	pub fn hash_header(&self) -> Option<HashHeader> {
+		match self {
+			ObjectEncoder::Physical(obj) => Some(obj.hash_header(&obj.hasher_map)),
+			ObjectEncoder::Logical(_) => None,
+		}
+	}
+
 }
 
 /// The [PhysicalObjectEncoder] can be used to encode a physical object.
@@ -132,6 +172,16 @@ pub struct PhysicalObjectEncoder<R: Read> {
 	has_hash_signatures: bool,
 	acquisition_start: u64,
 	acquisition_end: u64,
+	adaptive_compression: bool,
+	skipped_compression_chunks: u64,
+	compressor: Box<dyn Compressor>,
+	piecewise_hash_size: u64,
+	piecewise_hasher_map: HashMap<HashType, Box<dyn DynDigest>>,
+	piecewise_hash_window_bytes: u64, // the number of bytes, which are already hashed in the current hash window.
+	piecewise_hashes: Vec<HashHeader>,
+	merkle_tree_hash_type: Option<HashType>,
+	merkle_leaves: HashMap<u64, Vec<u8>>, //<chunk number, leaf hash> of the chunks of the current segment.
+	merkle_segment_roots: HashMap<u64, Vec<u8>>, //<segment number, subtree root>
 }
 
 impl<R: Read> PhysicalObjectEncoder<R> {
@@ -165,6 +215,7 @@ impl<R: Read> PhysicalObjectEncoder<R> {
 	        let hasher = Hash::new_hasher(&h_type);
 	        hasher_map.insert(h_type.clone(), hasher);
 	    };
+	    let compressor = Compression::new_compressor(&obj_header.compression_header(), main_header.chunk_size())?;
 		Ok(Self {
 			obj_number: obj_header.object_number(),
 			encoded_header_remaining_bytes: encoded_header.len(),
@@ -186,13 +237,126 @@ impl<R: Read> PhysicalObjectEncoder<R> {
 			has_hash_signatures: obj_header.has_hash_signatures(),
 			acquisition_start: 0,
 			acquisition_end: 0,
+			adaptive_compression: false,
+			skipped_compression_chunks: 0,
+			compressor,
+			piecewise_hash_size: 0,
+			piecewise_hasher_map: HashMap::new(),
+			piecewise_hash_window_bytes: 0,
+			piecewise_hashes: Vec::new(),
+			merkle_tree_hash_type: None,
+			merkle_leaves: HashMap::new(),
+			merkle_segment_roots: HashMap::new(),
 		})
 	}
 
+	/// Enables or disables the adaptive compression. If enabled, the entropy of each chunk will be estimated before compression
+	/// and the compression of likely incompressible chunks (e.g. of encrypted volumes) will be skipped.
+	/// The number of skipped chunks will be stored in the object footer.
+	pub fn set_adaptive_compression(&mut self, adaptive_compression: bool) {
+		self.adaptive_compression = adaptive_compression;
+	}
+
+	/// Enables the piecewise hashing with the given hash window size (in bytes) - like the hashwindow of dcfldd.
+	/// For each hash window, the data will be hashed separately by all given hash types and the hash values will be stored
+	/// in the object footer. This allows to locate damaged regions and to verify only parts of the object.
+	/// A hash window size of 0 disables the piecewise hashing.
+	pub fn set_piecewise_hash_size(&mut self, piecewise_hash_size: u64) {
+		self.piecewise_hash_size = piecewise_hash_size;
+		self.piecewise_hasher_map = HashMap::new();
+		if piecewise_hash_size > 0 {
+			for hash_type in self.hasher_map.keys() {
+				self.piecewise_hasher_map.insert(hash_type.clone(), Hash::new_hasher(hash_type));
+			}
+		}
+	}
+
+	/// Enables the merkle tree over the chunks of this object with the given hash type (e.g. [HashType::Blake3] or [HashType::SHA256]) or disables it (by None).
+	/// The leaves (the hashes of the chunk data) will be stored in the appropriate segment footers, the roots of each segment subtree
+	/// and the (signed) root of the tree over these subtree roots will be stored in the object footer. This allows to verify
+	/// a single chunk or a single segment without reading the whole object (see [ZffReader::verify_merkle_chunk](crate::ZffReader::verify_merkle_chunk)).
+	pub fn set_merkle_tree(&mut self, hash_type: Option<HashType>) {
+		self.merkle_tree_hash_type = hash_type;
+	}
+
+	/// Returns the merkle tree leaves of the chunks, which were created since the last call of this method, and
+	/// finalizes the subtree of the given segment. This method should be called by the segment writer at the end of each segment
+	/// (and before writing the object footer). Returns an empty map, if the merkle tree is disabled.
+	pub fn take_merkle_leaves(&mut self, segment_number: u64) -> HashMap<u64, Vec<u8>> {
+		let merkle_leaves = std::mem::take(&mut self.merkle_leaves);
+		if let Some(hash_type) = &self.merkle_tree_hash_type {
+			let mut chunk_numbers: Vec<&u64> = merkle_leaves.keys().collect();
+			chunk_numbers.sort();
+			let leaves: Vec<&Vec<u8>> = chunk_numbers.iter().map(|chunk_number| &merkle_leaves[chunk_number]).collect();
+			if let Some(segment_root) = MerkleTree::root(hash_type, &leaves) {
+				self.merkle_segment_roots.insert(segment_number, segment_root);
+			}
+		}
+		merkle_leaves
+	}
+
 	fn update_hasher(&mut self, buffer: &[u8]) {
 		for hasher in self.hasher_map.values_mut() {
 			hasher.update(buffer);
 		}
+		if self.piecewise_hash_size == 0 {
+			return;
+		}
+		let mut buffer = buffer;
+		while !buffer.is_empty() {
+			let remaining_window_bytes = self.piecewise_hash_size - self.piecewise_hash_window_bytes;
+			let length = std::cmp::min(remaining_window_bytes, buffer.len() as u64) as usize;
+			for hasher in self.piecewise_hasher_map.values_mut() {
+				hasher.update(&buffer[..length]);
+			}
+			self.piecewise_hash_window_bytes += length as u64;
+			buffer = &buffer[length..];
+			if self.piecewise_hash_window_bytes == self.piecewise_hash_size {
+				self.finalize_piecewise_hash_window();
+			}
+		}
+	}
+
+	fn finalize_piecewise_hash_window(&mut self) {
+		let hash_header = self.hash_header(&self.piecewise_hasher_map);
+		self.piecewise_hashes.push(hash_header);
+		for hasher in self.piecewise_hasher_map.values_mut() {
+			hasher.reset();
+		}
+		self.piecewise_hash_window_bytes = 0;
+	}
+
+	// returns the (signed) root of the merkle tree over the subtree roots of all segments (ordered by the segment number).
+	fn merkle_root(&self) -> Option<HashValue> {
+		let hash_type = self.merkle_tree_hash_type.as_ref()?;
+		let mut segment_numbers: Vec<&u64> = self.merkle_segment_roots.keys().collect();
+		segment_numbers.sort();
+		let segment_roots: Vec<&Vec<u8>> = segment_numbers.iter().map(|segment_number| &self.merkle_segment_roots[segment_number]).collect();
+		let root = MerkleTree::root(hash_type, &segment_roots)?;
+		let mut merkle_root = HashValue::new_empty(DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER, hash_type.clone());
+		if self.has_hash_signatures {
+			if let Some(signature) = Signature::calculate_signature(self.signature_key.as_ref(), &root) {
+				merkle_root.set_ed25519_signature(signature);
+			}
+		}
+		merkle_root.set_hash(root);
+		Some(merkle_root)
+	}
+
+	// finalizes (clones of) the given hashers and returns the appropriate (signed) hash header.
+	fn hash_header(&self, hasher_map: &HashMap<HashType, Box<dyn DynDigest>>) -> HashHeader {
+		let mut hash_values = Vec::new();
+	    for (hash_type, hasher) in hasher_map.clone() {
+	        let hash = hasher.finalize();
+	        let mut hash_value = HashValue::new_empty(DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER, hash_type);
+	        hash_value.set_hash(hash.to_vec());
+	        if self.has_hash_signatures {
+	        	let signature = Signature::calculate_signature(self.signature_key.as_ref(), &hash);
+	        	if let Some(sig) = signature { hash_value.set_ed25519_signature(sig) };
+	        };
+	        hash_values.push(hash_value);
+	    }
+	    HashHeader::new(DEFAULT_HEADER_VERSION_HASH_HEADER, hash_values)
 	}
 
 	/// Returns the appropriate object number.
@@ -217,17 +381,20 @@ impl<R: Read> PhysicalObjectEncoder<R> {
 		let mut chunk = Vec::new();
 
 		// prepare chunked data:
-	    let chunk_size = self.main_header.chunk_size();
-	    let (buf, read_bytes) = buffer_chunk(&mut self.underlying_data, chunk_size as usize)?;
-	    self.read_bytes_underlying_data += read_bytes;
-	    if buf.is_empty() {
-	    	return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
-	    };
-	    self.update_hasher(&buf);
+	    let buf = self.read_next_chunk()?;
 	    let crc32 = calculate_crc32(&buf);
 	    let signature = Signature::calculate_signature(self.signature_key.as_ref(), &buf);
 
-	    let (chunked_data, compression_flag) = compress_buffer(buf, self.main_header.chunk_size(), &self.compression_header)?;
+	    let compression_threshold = self.compression_header.threshold();
+	    let (chunked_data, compression_flag) = if self.adaptive_compression {
+	    	let (chunked_data, compression_flag, skipped_compression) = compress_chunk_adaptive(self.compressor.as_mut(), buf, compression_threshold)?;
+	    	if skipped_compression {
+	    		self.skipped_compression_chunks += 1;
+	    	}
+	    	(chunked_data, compression_flag)
+	    } else {
+	    	compress_chunk(self.compressor.as_mut(), buf, compression_threshold)?
+	    };
 
 	    // prepare chunk header:
 	    let mut chunk_header = ChunkHeader::new_empty(DEFAULT_HEADER_VERSION_CHUNK_HEADER, self.current_chunk_number);  
@@ -259,24 +426,48 @@ impl<R: Read> PhysicalObjectEncoder<R> {
 	    Ok(chunk)
 	}
 
-	/// Generates a appropriate footer. Attention: A call of this method ...
+	/// Reads and hashes the next chunk of the underlying data without compressing, encrypting or encoding the chunk
+	/// (e.g. for a hash-only acquisition). Returns the number of read bytes.
+	/// This method will increment the self.current_chunk_number automatically.
+	pub fn hash_next_chunk(&mut self) -> Result<u64> {
+		let buf = self.read_next_chunk()?;
+		self.current_chunk_number += 1;
+		Ok(buf.len() as u64)
+	}
+
+	// reads the next chunk of the underlying data and updates the hashers (and the merkle tree).
+	fn read_next_chunk(&mut self) -> Result<Vec<u8>> {
+	    let chunk_size = self.main_header.chunk_size();
+	    let (buf, read_bytes) = buffer_chunk(&mut self.underlying_data, chunk_size)?;
+	    self.read_bytes_underlying_data += read_bytes;
+	    if buf.is_empty() {
+	    	return Err(ZffError::new(ZffErrorKind::ReadEOF, ""));
+	    };
+	    self.update_hasher(&buf);
+	    if let Some(hash_type) = &self.merkle_tree_hash_type {
+	    	self.merkle_leaves.insert(self.current_chunk_number, MerkleTree::leaf_hash(hash_type, &buf));
+	    }
+	    Ok(buf)
+	}
+
+	/// Generates a appropriate encoded footer. Attention: A call of this method ...
 	/// - sets the acquisition end time to the current time
 	/// - finalizes the underlying hashers
 	pub fn get_encoded_footer(&mut self) -> Vec<u8> {
+		self.get_footer().encode_directly()
+	}
+
+	/// Generates a appropriate [ObjectFooterPhysical]. Attention: A call of this method ...
+	/// - sets the acquisition end time to the current time
+	/// - finalizes the underlying hashers
+	pub fn get_footer(&mut self) -> ObjectFooterPhysical {
 		self.acquisition_end = OffsetDateTime::from(SystemTime::now()).unix_timestamp() as u64;
-		let mut hash_values = Vec::new();
-	    for (hash_type, hasher) in self.hasher_map.clone() {
-	        let hash = hasher.finalize();
-	        let mut hash_value = HashValue::new_empty(DEFAULT_HEADER_VERSION_HASH_VALUE_HEADER, hash_type);
-	        hash_value.set_hash(hash.to_vec());
-	        if self.has_hash_signatures {
-	        	let signature = Signature::calculate_signature(self.signature_key.as_ref(), &hash.to_vec());
-	        	if let Some(sig) = signature { hash_value.set_ed25519_signature(sig) };
-	        };
-	        hash_values.push(hash_value);
+	    let hash_header = self.hash_header(&self.hasher_map);
+	    // the last hash window could be smaller than the hash window size.
+	    if self.piecewise_hash_window_bytes > 0 {
+	    	self.finalize_piecewise_hash_window();
 	    }
-	    let hash_header = HashHeader::new(DEFAULT_HEADER_VERSION_HASH_HEADER, hash_values);
-		let footer = ObjectFooterPhysical::new(
+		let mut footer = ObjectFooterPhysical::new(
 			DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_PHYSICAL,
 			self.acquisition_start,
 			self.acquisition_end,
@@ -284,7 +475,12 @@ impl<R: Read> PhysicalObjectEncoder<R> {
 			self.initial_chunk_number,
 			self.current_chunk_number - self.initial_chunk_number,
 			hash_header);
-		footer.encode_directly()
+		footer.set_skipped_compression_chunks(self.skipped_compression_chunks);
+		footer.set_piecewise_hashes(self.piecewise_hash_size, self.piecewise_hashes.clone());
+		if let Some(merkle_root) = self.merkle_root() {
+			footer.set_merkle_tree(merkle_root, self.merkle_segment_roots.clone());
+		}
+		footer
 	}
 
 	/// Returns the underlying [MainHeader].
@@ -377,8 +573,13 @@ pub struct LogicalObjectEncoder {
 	symlink_real_paths: HashMap<u64, PathBuf>,
 	hardlink_map: HashMap<u64, HashMap<u64, u64>>, // <dev_id, <inode, file number>>
 	directory_children: HashMap<u64, Vec<u64>>, //<directory file number, Vec<child filenumber>>
+	virtual_files: HashMap<u64, Box<dyn VirtualFile>>, //<file number, virtual file>
 	object_footer: ObjectFooterLogical,
 	header_encryption: bool,
+	deduplication_hash_type: Option<HashType>,
+	deduplication_candidates: HashMap<u64, Vec<DeduplicationCandidate>>, //<length of data, Vec<candidate>>
+	adaptive_compression: bool,
+	fuzzy_hash_types: Vec<FuzzyHashType>,
 }
 
 impl LogicalObjectEncoder {
@@ -400,6 +601,37 @@ impl LogicalObjectEncoder {
 		hardlink_map: HashMap<u64, HashMap<u64, u64>>, // <dev_id, <inode, file number>>
 		directory_children: HashMap<u64, Vec<u64>>,
 		current_chunk_number: u64,
+		header_encryption: bool) -> Result<LogicalObjectEncoder> {
+		Self::with_virtual_files(
+			obj_header,
+			files,
+			root_dir_filenumbers,
+			hash_types,
+			encryption_key,
+			signature_key_bytes,
+			main_header,
+			symlink_real_paths,
+			hardlink_map,
+			directory_children,
+			HashMap::new(),
+			current_chunk_number,
+			header_encryption)
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn with_virtual_files(
+		obj_header: ObjectHeader,
+		files: Vec<(PathBuf, FileHeader)>,
+		root_dir_filenumbers: Vec<u64>,
+		hash_types: Vec<HashType>,
+		encryption_key: Option<Vec<u8>>,
+		signature_key_bytes: Option<Vec<u8>>,
+		main_header: MainHeader,
+		symlink_real_paths: HashMap<u64, PathBuf>,
+		hardlink_map: HashMap<u64, HashMap<u64, u64>>,
+		directory_children: HashMap<u64, Vec<u64>>,
+		mut virtual_files: HashMap<u64, Box<dyn VirtualFile>>,
+		current_chunk_number: u64,
 		header_encryption: bool) -> Result<LogicalObjectEncoder> {		
 
 		let encoded_header = if header_encryption {
@@ -417,7 +649,7 @@ impl LogicalObjectEncoder {
 			Some((file, header)) => (file, header),
 			None => return Err(ZffError::new(ZffErrorKind::NoFilesLeft, "There is no input file"))
 		};
-		let current_file = File::open(&current_path)?;
+		let opened_file = open_file(&current_path, &mut current_file_header, &mut virtual_files, &hardlink_map)?;
 		let current_file_number = current_file_header.file_number();
 		let symlink_real_path = symlink_real_paths.get(&current_file_number).cloned();
 		let current_directory_children = match directory_children.get(&current_file_number) {
@@ -428,21 +660,12 @@ impl LogicalObjectEncoder {
 	    	Some(bytes) => Some(Keypair::from_bytes(bytes)?),
 	    	None => None
 	    };
-	    let metadata = current_file.metadata()?;
 	    let encryption_header = obj_header.encryption_header().map(ToOwned::to_owned);
 
-	    let mut hardlink_filenumber = None;
-	    #[cfg(target_family = "unix")]
-	    if let Some(inner_map) = hardlink_map.get(&metadata.dev()) {
-    		if let Some(fno) = inner_map.get(&metadata.ino()) {
-				if *fno != current_file_header.file_number() {
-					current_file_header.transform_to_hardlink();
-					hardlink_filenumber = Some(*fno);
-				};
-	    	}
-     	}
-
-		let first_file_encoder = Some(FileEncoder::new(current_file_header, current_file, hash_types.clone(), encryption_key.clone(), signature_key, main_header.clone(), obj_header.compression_header(), encryption_header.clone(), current_chunk_number, symlink_real_path, header_encryption, hardlink_filenumber, current_directory_children)?);
+		let mut first_file_encoder = FileEncoder::new(current_file_header, opened_file.content, hash_types.clone(), encryption_key.clone(), signature_key, main_header.clone(), obj_header.compression_header(), encryption_header.clone(), current_chunk_number, symlink_real_path, header_encryption, opened_file.hardlink_filenumber, current_directory_children)?;
+		for (name, reader) in opened_file.data_streams {
+			first_file_encoder.add_data_stream(name, reader)?;
+		}
 		
 		let mut object_footer = ObjectFooterLogical::new_empty(DEFAULT_FOOTER_VERSION_OBJECT_FOOTER_LOGICAL);
 		for filenumber in root_dir_filenumbers {
@@ -454,7 +677,7 @@ impl LogicalObjectEncoder {
 			//encoded_header_remaining_bytes: encoded_header.len(),
 			encoded_header,
 			files,
-			current_file_encoder: first_file_encoder,
+			current_file_encoder: Some(first_file_encoder),
 			current_file_header_read: false,
 			current_file_number,
 			hash_types,
@@ -467,11 +690,95 @@ impl LogicalObjectEncoder {
 			symlink_real_paths,
 			hardlink_map,
 			directory_children,
+			virtual_files,
 			object_footer,
 			header_encryption,
+			deduplication_hash_type: None,
+			deduplication_candidates: HashMap::new(),
+			adaptive_compression: false,
+			fuzzy_hash_types: Vec::new(),
 		})
 	}
 
+	/// Returns a new [LogicalObjectEncoder] by the given [LogicalTree] (see [LogicalTreeBuilder](crate::LogicalTreeBuilder)) and values.
+	#[allow(clippy::too_many_arguments)]
+	pub fn with_tree(
+		obj_header: ObjectHeader,
+		tree: LogicalTree,
+		hash_types: Vec<HashType>,
+		encryption_key: Option<Vec<u8>>,
+		signature_key_bytes: Option<Vec<u8>>,
+		main_header: MainHeader,
+		current_chunk_number: u64,
+		header_encryption: bool) -> Result<LogicalObjectEncoder> {
+		Self::with_virtual_files(
+			obj_header,
+			tree.files,
+			tree.root_dir_filenumbers,
+			hash_types,
+			encryption_key,
+			signature_key_bytes,
+			main_header,
+			tree.symlink_real_paths,
+			tree.hardlink_map,
+			tree.directory_children,
+			tree.virtual_files,
+			current_chunk_number,
+			header_encryption)
+	}
+
+	/// Enables or disables the whole-file deduplication. If enabled, regular files with the same size and the same hash value
+	/// as an already encoded file will be stored as a reference to the chunks of the already encoded file.
+	/// The strongest collision resistant hash type of this object will be used (MD5 and SHA1 will never be used for the deduplication).
+	/// The file header and the hash values of the deduplicated files will be stored anyway.
+	/// Only files of the local filesystem can be deduplicated (no [VirtualFile]s).
+	/// # Error
+	/// Fails, if the deduplication should be enabled, but the object has no collision resistant hash type (see [HashType::is_collision_resistant]).
+	pub fn set_deduplication(&mut self, deduplication: bool) -> Result<()> {
+		self.deduplication_hash_type = if deduplication {
+			let hash_type = self.hash_types.iter()
+				.filter(|hash_type| hash_type.is_collision_resistant())
+				.fold(None, |strongest: Option<&HashType>, hash_type| match strongest {
+					Some(strongest) if strongest.default_len() >= hash_type.default_len() => Some(strongest),
+					_ => Some(hash_type),
+				});
+			match hash_type {
+				Some(hash_type) => Some(hash_type.clone()),
+				None => return Err(ZffError::new(ZffErrorKind::MissingHashType, format!("{ERROR_DEDUPLICATION_MISSING_HASH_TYPE}{:?}", self.hash_types))),
+			}
+		} else {
+			None
+		};
+		if let Some(file_encoder) = &mut self.current_file_encoder {
+			file_encoder.set_deduplication_hash_type(self.deduplication_hash_type.clone());
+		}
+		Ok(())
+	}
+
+	/// Returns true, if the whole-file deduplication is enabled.
+	pub fn deduplication(&self) -> bool {
+		self.deduplication_hash_type.is_some()
+	}
+
+	/// Enables or disables the adaptive compression. If enabled, the entropy of each chunk will be estimated before compression
+	/// and the compression of likely incompressible chunks (e.g. of encrypted containers) will be skipped.
+	/// The number of skipped chunks will be stored in the object footer.
+	pub fn set_adaptive_compression(&mut self, adaptive_compression: bool) {
+		self.adaptive_compression = adaptive_compression;
+		if let Some(file_encoder) = &mut self.current_file_encoder {
+			file_encoder.set_adaptive_compression(adaptive_compression);
+		}
+	}
+
+	/// Sets the fuzzy hash types (e.g. [FuzzyHashType::Ssdeep]), which will be calculated for each regular file of this object
+	/// (see [FileEncoder::set_fuzzy_hash_types]). The fuzzy hashes will be stored in the appropriate file footers.
+	pub fn set_fuzzy_hash_types(&mut self, fuzzy_hash_types: Vec<FuzzyHashType>) {
+		if let Some(file_encoder) = &mut self.current_file_encoder {
+			file_encoder.set_fuzzy_hash_types(&fuzzy_hash_types);
+		}
+		self.fuzzy_hash_types = fuzzy_hash_types;
+	}
+
 	/// Returns the appropriate object number.
 	pub fn obj_number(&self) -> u64 {
 		self.obj_number
@@ -523,7 +830,16 @@ impl LogicalObjectEncoder {
 				};
 
 				//return file footer, set next file_encoder
-				let file_footer = file_encoder.get_encoded_footer();
+				let file_footer = file_encoder.get_footer();
+				let compressor = file_encoder.take_compressor();
+				let skipped_compression_chunks = self.object_footer.skipped_compression_chunks() + file_encoder.skipped_compression_chunks();
+				self.object_footer.set_skipped_compression_chunks(skipped_compression_chunks);
+				if let (Some(hash_type), Some(first_chunk_hash)) = (&self.deduplication_hash_type, file_encoder.first_chunk_hash()) {
+					if let Some(candidate) = DeduplicationCandidate::from_footer(self.current_file_number, &file_footer, hash_type, first_chunk_hash) {
+						self.deduplication_candidates.entry(candidate.length_of_data()).or_default().push(candidate);
+					}
+				}
+				let file_footer = file_footer.encode_directly();
 				self.object_footer.add_file_footer_segment_number(self.current_file_number, current_segment_no);
 				self.object_footer.add_file_footer_offset(self.current_file_number, current_offset);
 				let (current_path, mut current_file_header) = match self.files.pop() {
@@ -533,7 +849,7 @@ impl LogicalObjectEncoder {
 						return Ok(file_footer)
 					},
 				};
-				let current_file = File::open(&current_path)?;
+				let opened_file = open_file(&current_path, &mut current_file_header, &mut self.virtual_files, &self.hardlink_map)?;
 				self.current_file_number = current_file_header.file_number();
 				let symlink_real_path = self.symlink_real_paths.get(&self.current_file_number).cloned();
 				let current_directory_children = match self.directory_children.get(&self.current_file_number) {
@@ -545,22 +861,19 @@ impl LogicalObjectEncoder {
 			    	None => None
 			    };
 
-			    let metadata = current_file.metadata()?;
-
-			    // transform the next header to hardlink, if the file is one.
-			    let mut hardlink_filenumber = None;
-			    #[cfg(target_family = "unix")]
-			    if let Some(inner_map) = self.hardlink_map.get(&metadata.dev()) {
-		    		if let Some(fno) = inner_map.get(&metadata.ino()) {
-	    				if *fno != current_file_header.file_number() {
-	    					current_file_header.transform_to_hardlink();
-	    					hardlink_filenumber = Some(*fno);
-	    				};
-    			    }
-       			}
-       			
 			    self.current_file_header_read = false;
-				self.current_file_encoder = Some(FileEncoder::new(current_file_header, current_file, self.hash_types.clone(), self.encryption_key.clone(), signature_key, self.main_header.clone(), self.compression_header.clone(), self.encryption_header.clone(), self.current_chunk_number, symlink_real_path, self.header_encryption, hardlink_filenumber, current_directory_children)?);
+				let mut file_encoder = FileEncoder::new(current_file_header, opened_file.content, self.hash_types.clone(), self.encryption_key.clone(), signature_key, self.main_header.clone(), self.compression_header.clone(), self.encryption_header.clone(), self.current_chunk_number, symlink_real_path, self.header_encryption, opened_file.hardlink_filenumber, current_directory_children)?;
+				for (name, reader) in opened_file.data_streams {
+					file_encoder.add_data_stream(name, reader)?;
+				}
+				file_encoder.set_adaptive_compression(self.adaptive_compression);
+				file_encoder.set_fuzzy_hash_types(&self.fuzzy_hash_types);
+				if let Some(compressor) = compressor {
+					file_encoder.set_compressor(compressor);
+				}
+				file_encoder.set_deduplication_hash_type(self.deduplication_hash_type.clone());
+				file_encoder.deduplicate(&self.deduplication_candidates)?;
+				self.current_file_encoder = Some(file_encoder);
 				Ok(file_footer)
 			},
 			None => {
@@ -580,4 +893,96 @@ impl LogicalObjectEncoder {
 		self.encryption_key.clone()
 	}
 
-}
\ No newline at end of file
+}
+
+// The opened file, which will be encoded by the next FileEncoder.
+struct OpenedFile {
+	content: Option<FileContent>,
+	// the file number of the hardlinked file, if the file is a hardlink.
+	hardlink_filenumber: Option<u64>,
+	// the named data streams (name, reader) of the file.
+	data_streams: Vec<(String, Box<dyn Read>)>,
+}
+
+// opens the file at the given path (or takes the appropriate virtual file) and transforms the file header to a hardlink, if the file is a hardlink to an already dumped file.
+fn open_file(
+	path: &Path,
+	file_header: &mut FileHeader,
+	virtual_files: &mut HashMap<u64, Box<dyn VirtualFile>>,
+	hardlink_map: &HashMap<u64, HashMap<u64, u64>>) -> Result<OpenedFile> {
+	if let Some(mut virtual_file) = virtual_files.remove(&file_header.file_number()) {
+		let opened_file = match file_header.file_type() {
+			FileType::File => OpenedFile { data_streams: virtual_file.data_streams(), content: Some(FileContent::Virtual(virtual_file)), hardlink_filenumber: None },
+			_ => OpenedFile { content: None, hardlink_filenumber: None, data_streams: Vec::new() },
+		};
+		return Ok(opened_file);
+	}
+	let file = open_local_file(path, file_header)?;
+	let metadata = file_metadata(path, &file)?;
+
+	let mut hardlink_filenumber = None;
+	#[cfg(target_family = "unix")]
+	if let Some(inner_map) = hardlink_map.get(&metadata.dev()) {
+		if let Some(fno) = inner_map.get(&metadata.ino()) {
+			if *fno != file_header.file_number() {
+				file_header.transform_to_hardlink();
+				hardlink_filenumber = Some(*fno);
+			};
+		}
+	}
+	#[cfg(not(target_family = "unix"))]
+	let _ = (metadata, hardlink_map);
+
+	let data_streams = match file_header.file_type() {
+		FileType::File => open_data_streams(path),
+		_ => Vec::new(),
+	};
+
+	Ok(OpenedFile {
+		content: file.map(FileContent::from),
+		hardlink_filenumber,
+		data_streams,
+	})
+}
+
+// opens the alternate data streams of the given file, which are provided by ntfs-3g (mounted with the option streams_interface=windows).
+// Data streams, which could not be opened, will be skipped.
+#[cfg(target_os = "linux")]
+fn open_data_streams(path: &Path) -> Vec<(String, Box<dyn Read>)> {
+	let mut data_streams: Vec<(String, Box<dyn Read>)> = Vec::new();
+	let stream_names = match xattr::get(path, NTFS_STREAMS_LIST_XATTR) {
+		Ok(Some(stream_names)) => stream_names,
+		_ => return data_streams,
+	};
+	for stream_name in stream_names.split(|byte| *byte == 0).filter(|name| !name.is_empty()) {
+		let stream_name = String::from_utf8_lossy(stream_name).to_string();
+		let mut stream_path = path.as_os_str().to_owned();
+		stream_path.push(":");
+		stream_path.push(&stream_name);
+		if let Ok(stream) = File::open(stream_path) {
+			data_streams.push((stream_name, Box::new(stream)));
+		}
+	}
+	data_streams
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_data_streams(_path: &Path) -> Vec<(String, Box<dyn Read>)> {
+	Vec::new()
+}
+
+// opens the file at the given path. Special files (fifos, sockets, devices) will not be opened, because their content will never be read.
+fn open_local_file(path: &Path, file_header: &FileHeader) -> Result<Option<File>> {
+	if file_header.file_type().is_special_file() {
+		return Ok(None);
+	}
+	Ok(Some(File::open(path)?))
+}
+
+// returns the metadata of the opened file or - if the file was not opened - the metadata of the path itself.
+fn file_metadata(path: &Path, file: &Option<File>) -> Result<Metadata> {
+	match file {
+		Some(file) => Ok(file.metadata()?),
+		None => Ok(std::fs::symlink_metadata(path)?),
+	}
+}
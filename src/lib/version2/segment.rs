@@ -1,5 +1,6 @@
 // - STD
 use std::io::{Read, Seek, SeekFrom};
+use std::collections::HashMap;
 
 // - internal
 use crate::{
@@ -11,11 +12,14 @@ use crate::{
 	Chunk,
 	Encryption,
 	Object,
-	decompress_buffer,
+	Compression,
+	Decompressor,
+	decompress_buffer_with_dictionary,
 	header::{SegmentHeader, ObjectHeader, ChunkHeader},
 	footer::{SegmentFooter, ObjectFooter},
 	ERROR_MISSING_OBJECT_HEADER_IN_SEGMENT,
 	ERROR_MISSING_OBJECT_FOOTER_IN_SEGMENT,
+	ERROR_BUFFER_TOO_SMALL,
 };
 
 /// Represents a full [Segment], containing a [crate::header::SegmentHeader],
@@ -26,6 +30,8 @@ pub struct Segment<R: Read + Seek> {
 	data: R,
 	footer: SegmentFooter,
 	raw_reader_position: u64,
+	decompressors: HashMap<u64, Box<dyn Decompressor>>, //<object number, decompressor>
+	read_buffer: Vec<u8>, // reusable buffer for the raw chunk data.
 }
 
 impl<R: Read + Seek> Segment<R> {
@@ -36,6 +42,8 @@ impl<R: Read + Seek> Segment<R> {
 			data,
 			footer,
 			raw_reader_position: 0,
+			decompressors: HashMap::new(),
+			read_buffer: Vec::new(),
 		}
 	}
 
@@ -91,6 +99,10 @@ impl<R: Read + Seek> Segment<R> {
 		self.data.seek(SeekFrom::Start(chunk_offset))?;
 		let chunk_header = ChunkHeader::decode_directly(&mut self.data)?;
 		let chunk_size = chunk_header.chunk_size();
+		// sparse chunks contain no data, the chunk size is the length of the appropriate hole.
+		if chunk_header.sparse_flag() {
+			return Ok(vec![0u8; *chunk_size as usize]);
+		}
 		self.data.seek(SeekFrom::Start(chunk_header.header_size() as u64 + chunk_offset))?;
 		let mut raw_data_buffer = vec![0u8; *chunk_size as usize];
 		self.data.read_exact(&mut raw_data_buffer)?;
@@ -106,14 +118,57 @@ impl<R: Read + Seek> Segment<R> {
 			None => raw_data_buffer,
 		};
 		if chunk_header.compression_flag() {
-			let compression_algorithm = object.header().compression_header().algorithm().clone();
-			decompress_buffer(&raw_data_buffer, compression_algorithm)
+			let compression_header = object.header().compression_header();
+			decompress_buffer_with_dictionary(&raw_data_buffer, compression_header.algorithm(), compression_header.dictionary())
 		} else {
 			Ok(raw_data_buffer)
 		}
 		
 	}
 
+	/// Reads the chunked data (uncompressed and unencrypted) of the given chunk into the given buffer and returns the number of read bytes.
+	/// In contrast to [Segment::chunk_data], the decompression context of the appropriate object and the internal read buffer will be reused for each call.
+	/// # Error
+	/// Fails, if the given buffer is too small for the chunk data (a buffer with the chunk size of the [crate::header::MainHeader] is always sufficient).
+	pub fn chunk_data_into(&mut self, chunk_number: u64, object: &Object, buffer: &mut [u8]) -> Result<usize> {
+		let chunk_offset = match self.footer().chunk_offsets().get(&chunk_number) {
+			Some(offset) => *offset,
+			None => return Err(ZffError::new(ZffErrorKind::DataDecodeChunkNumberNotInSegment, chunk_number.to_string()))
+		};
+		self.data.seek(SeekFrom::Start(chunk_offset))?;
+		let chunk_header = ChunkHeader::decode_directly(&mut self.data)?;
+		let chunk_size = *chunk_header.chunk_size() as usize;
+		// sparse chunks contain no data, the chunk size is the length of the appropriate hole.
+		if chunk_header.sparse_flag() {
+			if chunk_size > buffer.len() {
+				return Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", buffer.len())));
+			}
+			buffer[..chunk_size].fill(0);
+			return Ok(chunk_size);
+		}
+		self.data.seek(SeekFrom::Start(chunk_header.header_size() as u64 + chunk_offset))?;
+		self.read_buffer.resize(chunk_size, 0);
+		self.data.read_exact(&mut self.read_buffer)?;
+		if let (Some(algo), Some(encryption_key)) = (object.encryption_algorithm(), object.encryption_key()) {
+			Encryption::decrypt_message_in_place(encryption_key, &mut self.read_buffer, chunk_number, algo)?;
+		}
+		let raw_data = &self.read_buffer;
+		if chunk_header.compression_flag() {
+			let object_number = object.header().object_number();
+			let decompressor = match self.decompressors.get_mut(&object_number) {
+				Some(decompressor) => decompressor,
+				None => self.decompressors.entry(object_number).or_insert(Compression::new_decompressor(&object.header().compression_header())?),
+			};
+			decompressor.decompress_into(raw_data, buffer)
+		} else {
+			if raw_data.len() > buffer.len() {
+				return Err(ZffError::new(ZffErrorKind::BufferTooSmall, format!("{ERROR_BUFFER_TOO_SMALL}{}", buffer.len())));
+			}
+			buffer[..raw_data.len()].copy_from_slice(raw_data);
+			Ok(raw_data.len())
+		}
+	}
+
 	/// Returns the [crate::header::ObjectHeader] of the given object number, if available in this [Segment]. Otherwise, returns an error.
 	pub fn read_object_header(&mut self, object_number: u64) -> Result<ObjectHeader> {
 		let offset = match self.footer.object_header_offsets().get(&object_number) {